@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS read_cursors (
+    agent_id TEXT PRIMARY KEY,
+    last_message_id INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
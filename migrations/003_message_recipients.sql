@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS message_recipients (
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    agent_id TEXT NOT NULL,
+    PRIMARY KEY (message_id, agent_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_recipients_agent ON message_recipients(agent_id);
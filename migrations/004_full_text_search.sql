@@ -0,0 +1,41 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+    content,
+    content='messages',
+    content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+    INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages BEGIN
+    INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    INSERT INTO messages_fts(rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS artifacts_fts USING fts5(
+    description,
+    content='artifacts',
+    content_rowid='id'
+);
+
+CREATE TRIGGER IF NOT EXISTS artifacts_fts_insert AFTER INSERT ON artifacts BEGIN
+    INSERT INTO artifacts_fts(rowid, description) VALUES (new.id, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS artifacts_fts_delete AFTER DELETE ON artifacts BEGIN
+    INSERT INTO artifacts_fts(artifacts_fts, rowid, description) VALUES ('delete', old.id, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS artifacts_fts_update AFTER UPDATE OF description ON artifacts BEGIN
+    INSERT INTO artifacts_fts(artifacts_fts, rowid, description) VALUES ('delete', old.id, old.description);
+    INSERT INTO artifacts_fts(rowid, description) VALUES (new.id, new.description);
+END;
+
+-- Index rows that predate this migration
+INSERT INTO messages_fts(messages_fts) VALUES ('rebuild');
+INSERT INTO artifacts_fts(artifacts_fts) VALUES ('rebuild');
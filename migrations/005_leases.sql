@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS leases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL,
+    holder TEXT NOT NULL,
+    mode TEXT NOT NULL DEFAULT 'exclusive'
+        CHECK (mode IN ('exclusive', 'shared')),
+    acquired_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    UNIQUE (path, holder)
+);
+
+CREATE INDEX IF NOT EXISTS idx_leases_holder ON leases(holder);
+CREATE INDEX IF NOT EXISTS idx_leases_expires_at ON leases(expires_at);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS handoffs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_agent TEXT NOT NULL,
+    to_agent TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    refs TEXT NOT NULL DEFAULT '[]',
+    artifacts TEXT NOT NULL DEFAULT '[]',
+    priority TEXT NOT NULL DEFAULT 'normal'
+        CHECK (priority IN ('low', 'normal', 'high', 'critical')),
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined')),
+    message_id INTEGER REFERENCES messages(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    escalate_at TIMESTAMP NOT NULL,
+    escalated INTEGER NOT NULL DEFAULT 0,
+    responded_at TIMESTAMP,
+    response TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_handoffs_to_status ON handoffs(to_agent, status);
+CREATE INDEX IF NOT EXISTS idx_handoffs_escalate_at ON handoffs(escalate_at);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS questions (
+    message_id INTEGER PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    state TEXT NOT NULL DEFAULT 'open'
+        CHECK (state IN ('open', 'answered', 'closed')),
+    answer_id INTEGER REFERENCES messages(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_questions_state ON questions(state);
+
+-- Every message tagged `question` is tracked, however it was posted
+CREATE TRIGGER IF NOT EXISTS questions_track AFTER INSERT ON messages
+WHEN EXISTS (SELECT 1 FROM json_each(new.tags) WHERE value = 'question')
+BEGIN
+    INSERT OR IGNORE INTO questions (message_id) VALUES (new.id);
+END;
+
+-- Track questions that predate this migration
+INSERT OR IGNORE INTO questions (message_id)
+SELECT id FROM messages
+WHERE EXISTS (SELECT 1 FROM json_each(messages.tags) WHERE value = 'question');
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS agent_status_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    status TEXT NOT NULL
+        CHECK (status IN (
+            'idle', 'planning', 'coding', 'testing',
+            'reviewing', 'blocked', 'offline'
+        )),
+    current_task TEXT NOT NULL DEFAULT '',
+    progress INTEGER NOT NULL DEFAULT 0,
+    blockers TEXT,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_status_events_agent
+    ON agent_status_events(agent_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_agent_status_events_created_at
+    ON agent_status_events(created_at);
+
+-- Start each existing agent's history at its current state
+INSERT INTO agent_status_events (agent_id, status, current_task, progress, blockers, created_at)
+SELECT id, status, current_task, progress, blockers, updated_at FROM agents;
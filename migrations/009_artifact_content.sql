@@ -0,0 +1,5 @@
+-- What an artifact's file looked like when it was last registered. NULL for
+-- artifacts registered before this migration, and for directories.
+ALTER TABLE artifacts ADD COLUMN content_hash TEXT;
+ALTER TABLE artifacts ADD COLUMN size INTEGER;
+ALTER TABLE artifacts ADD COLUMN mtime TIMESTAMP;
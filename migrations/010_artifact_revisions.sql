@@ -0,0 +1,34 @@
+-- Every registration of an artifact. `artifacts` keeps the latest one for
+-- each path; this table keeps who registered it before, and what they said.
+CREATE TABLE artifact_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    artifact_id INTEGER NOT NULL REFERENCES artifacts(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    produced_by TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    version TEXT,
+    refs TEXT NOT NULL DEFAULT '[]'
+        CHECK (json_valid(refs)),
+    content_hash TEXT,
+    size INTEGER,
+    mtime TIMESTAMP,
+    registered_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_artifact_revisions_artifact ON artifact_revisions(artifact_id, id);
+
+-- `created_at` keeps its old meaning, the latest registration, for anything
+-- reading the table directly.
+ALTER TABLE artifacts ADD COLUMN first_registered_at TIMESTAMP;
+ALTER TABLE artifacts ADD COLUMN updated_at TIMESTAMP;
+
+-- Earlier registrations were overwritten, so the latest is all there is.
+UPDATE artifacts SET first_registered_at = created_at, updated_at = created_at;
+
+INSERT INTO artifact_revisions
+    (artifact_id, path, produced_by, description, version, refs,
+     content_hash, size, mtime, registered_at)
+SELECT id, path, produced_by, description, version, refs,
+       content_hash, size, mtime, created_at
+FROM artifacts
+ORDER BY id;
@@ -0,0 +1,10 @@
+-- Where an artifact's path stood in the project's git repository when it was
+-- registered: the HEAD commit, whether the path had uncommitted changes, and
+-- the last commit that touched it. NULL outside git and for older rows.
+ALTER TABLE artifacts ADD COLUMN git_head TEXT;
+ALTER TABLE artifacts ADD COLUMN git_dirty INTEGER;
+ALTER TABLE artifacts ADD COLUMN git_last_commit TEXT;
+
+ALTER TABLE artifact_revisions ADD COLUMN git_head TEXT;
+ALTER TABLE artifact_revisions ADD COLUMN git_dirty INTEGER;
+ALTER TABLE artifact_revisions ADD COLUMN git_last_commit TEXT;
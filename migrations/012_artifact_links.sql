@@ -0,0 +1,11 @@
+-- Declared dependencies between artifacts: `artifact_id` depends on, or was
+-- derived from, `upstream_id`. Keyed by id so links follow renames.
+CREATE TABLE artifact_links (
+    artifact_id INTEGER NOT NULL REFERENCES artifacts(id) ON DELETE CASCADE,
+    upstream_id INTEGER NOT NULL REFERENCES artifacts(id) ON DELETE CASCADE,
+    link TEXT NOT NULL
+        CHECK (link IN ('depends_on', 'derived_from')),
+    PRIMARY KEY (artifact_id, upstream_id, link)
+);
+
+CREATE INDEX idx_artifact_links_upstream ON artifact_links(upstream_id);
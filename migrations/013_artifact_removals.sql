@@ -0,0 +1,34 @@
+-- Removing an artifact keeps its revisions, so the board still knows who
+-- registered it and when. The revision with `removed` set records who removed
+-- it; `artifact_id` no longer cascades, since the artifact row is gone.
+CREATE TABLE artifact_revisions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    artifact_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    produced_by TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    version TEXT,
+    refs TEXT NOT NULL DEFAULT '[]'
+        CHECK (json_valid(refs)),
+    content_hash TEXT,
+    size INTEGER,
+    mtime TIMESTAMP,
+    registered_at TIMESTAMP NOT NULL,
+    git_head TEXT,
+    git_dirty INTEGER,
+    git_last_commit TEXT,
+    removed INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO artifact_revisions_new
+    (id, artifact_id, path, produced_by, description, version, refs,
+     content_hash, size, mtime, registered_at, git_head, git_dirty, git_last_commit)
+SELECT id, artifact_id, path, produced_by, description, version, refs,
+       content_hash, size, mtime, registered_at, git_head, git_dirty, git_last_commit
+FROM artifact_revisions;
+
+DROP TABLE artifact_revisions;
+ALTER TABLE artifact_revisions_new RENAME TO artifact_revisions;
+
+CREATE INDEX idx_artifact_revisions_artifact ON artifact_revisions(artifact_id, id);
+CREATE INDEX idx_artifact_revisions_path ON artifact_revisions(path, id);
@@ -0,0 +1,21 @@
+-- What an artifact depended on and was derived from at each revision, by
+-- path, as JSON arrays. Earlier revisions did not record their links; the
+-- latest revision of each artifact gets the links it has now.
+ALTER TABLE artifact_revisions ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]'
+    CHECK (json_valid(depends_on));
+ALTER TABLE artifact_revisions ADD COLUMN derived_from TEXT NOT NULL DEFAULT '[]'
+    CHECK (json_valid(derived_from));
+
+UPDATE artifact_revisions SET
+    depends_on = (
+        SELECT json_group_array(u.path) FROM artifact_links l
+        JOIN artifacts u ON u.id = l.upstream_id
+        WHERE l.artifact_id = artifact_revisions.artifact_id AND l.link = 'depends_on'),
+    derived_from = (
+        SELECT json_group_array(u.path) FROM artifact_links l
+        JOIN artifacts u ON u.id = l.upstream_id
+        WHERE l.artifact_id = artifact_revisions.artifact_id AND l.link = 'derived_from')
+WHERE id IN (
+    SELECT MAX(r.id) FROM artifact_revisions r
+    JOIN artifacts a ON a.id = r.artifact_id
+    GROUP BY r.artifact_id);
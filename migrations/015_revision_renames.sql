@@ -0,0 +1,3 @@
+-- A revision with `renamed_from` set records that `produced_by` moved the
+-- artifact from that path to `path` at `registered_at`.
+ALTER TABLE artifact_revisions ADD COLUMN renamed_from TEXT;
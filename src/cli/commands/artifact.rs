@@ -1,14 +1,18 @@
-use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::cli::output::{OutputFormat, OutputFormatter, format_graph_dot, format_graph_mermaid};
 use crate::core::errors::BBResult;
+use crate::core::models::artifact::GraphDirection;
 use crate::core::operations::agent as agent_ops;
 use crate::core::operations::artifact as artifact_ops;
+use crate::core::validation::limits::normalize_project_path;
 use crate::db::connection::with_connection;
 use crate::util::ref_::parse_ref;
 use std::path::Path;
 
+#[allow(clippy::too_many_arguments)]
 pub fn list(
     project_dir: &Path,
     produced_by: Option<&str>,
+    under: Option<&str>,
     ref_where: Option<&str>,
     ref_what: Option<&str>,
     ref_ref: Option<&str>,
@@ -21,8 +25,15 @@ pub fn list(
             let _ = agent_ops::touch_agent(conn, agent);
         }
 
-        let artifacts =
-            artifact_ops::list_artifacts(conn, produced_by, ref_where, ref_what, ref_ref, limit)?;
+        let artifacts = artifact_ops::list_artifacts(
+            conn,
+            produced_by,
+            under,
+            ref_where,
+            ref_what,
+            ref_ref,
+            limit,
+        )?;
 
         let formatter = OutputFormatter::new(format);
         print!("{}", formatter.format_artifacts(&artifacts));
@@ -31,6 +42,38 @@ pub fn list(
     })
 }
 
+/// Like `list`, with each artifact compared against its file on disk.
+#[allow(clippy::too_many_arguments)]
+pub fn check(
+    project_dir: &Path,
+    produced_by: Option<&str>,
+    under: Option<&str>,
+    ref_where: Option<&str>,
+    ref_what: Option<&str>,
+    ref_ref: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let checks = artifact_ops::check_artifacts(
+            conn,
+            produced_by,
+            under,
+            ref_where,
+            ref_what,
+            ref_ref,
+            limit,
+            project_dir,
+        )?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_artifact_checks(&checks));
+
+        Ok(())
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn add(
     project_dir: &Path,
     path: &str,
@@ -38,18 +81,24 @@ pub fn add(
     description: &str,
     version: Option<&str>,
     refs: Vec<String>,
+    depends_on: &[String],
+    derived_from: &[String],
+    clear_links: bool,
 ) -> BBResult<()> {
     let parsed_refs: Result<Vec<_>, _> = refs.iter().map(|r| parse_ref(r)).collect();
     let parsed_refs = parsed_refs?;
 
     with_connection(project_dir, |conn| {
-        let artifact = artifact_ops::register_artifact(
+        let artifact = artifact_ops::register_linked_artifact(
             conn,
             path,
             produced_by,
             description,
             version,
             parsed_refs,
+            depends_on,
+            derived_from,
+            clear_links,
             project_dir,
         )?;
 
@@ -62,14 +111,142 @@ pub fn add(
     })
 }
 
+/// Registers every file a commit added or changed.
+pub fn add_from_commit(
+    project_dir: &Path,
+    commit: &str,
+    produced_by: &str,
+    version: Option<&str>,
+    refs: Vec<String>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let parsed_refs: Result<Vec<_>, _> = refs.iter().map(|r| parse_ref(r)).collect();
+    let parsed_refs = parsed_refs?;
+
+    with_connection(project_dir, |conn| {
+        let registration = artifact_ops::register_commit(
+            conn,
+            commit,
+            produced_by,
+            None,
+            version,
+            parsed_refs,
+            project_dir,
+        )?;
+
+        match format {
+            OutputFormat::Human => {
+                println!(
+                    "Registered {} artifact(s) from commit {}",
+                    registration.registered.len(),
+                    &registration.commit[..12]
+                );
+                for artifact in &registration.registered {
+                    println!("  {}", artifact.path);
+                }
+                for path in &registration.skipped {
+                    println!("  {path} (skipped: no longer on disk)");
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&registration)?),
+        }
+
+        Ok(())
+    })
+}
+
+/// Lists artifacts whose files changed since a commit.
+pub fn changed_since(
+    project_dir: &Path,
+    commit: &str,
+    produced_by: Option<&str>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let artifacts = artifact_ops::changed_since_commit(conn, commit, produced_by, project_dir)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_artifacts(&artifacts));
+
+        Ok(())
+    })
+}
+
+/// Shows the artifact at `path` or, if there is none, the directory and glob
+/// artifacts it falls under.
 pub fn show(project_dir: &Path, path: &str, format: OutputFormat) -> BBResult<()> {
     with_connection(project_dir, |conn| {
-        let artifact = artifact_ops::get_artifact(conn, path)?.ok_or_else(|| {
-            crate::core::errors::BBError::NotFound(format!("artifact '{path}' not found"))
-        })?;
+        let formatter = OutputFormatter::new(format);
+        if let Some(artifact) = artifact_ops::get_artifact(conn, path)? {
+            print!("{}", formatter.format_artifacts(&[artifact]));
+            return Ok(());
+        }
+
+        let covering = artifact_ops::covering_artifacts(conn, path)?;
+        if covering.is_empty() {
+            return Err(crate::core::errors::BBError::NotFound(format!(
+                "artifact '{path}' not found"
+            )));
+        }
+        if matches!(format, OutputFormat::Human) {
+            println!("No artifact at {path}; it is covered by:");
+        }
+        print!("{}", formatter.format_artifacts(&covering));
+
+        Ok(())
+    })
+}
+
+pub fn remove(project_dir: &Path, agent_id: &str, path: &str) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let artifact = artifact_ops::remove_artifact(conn, agent_id, path)?;
+        println!("Removed artifact: {}", artifact.path);
+        Ok(())
+    })
+}
+
+pub fn rename(project_dir: &Path, agent_id: &str, old: &str, new: &str) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let artifact = artifact_ops::rename_artifact(conn, agent_id, old, new, project_dir)?;
+        println!("Renamed artifact: {old} -> {}", artifact.path);
+        Ok(())
+    })
+}
+
+pub fn history(project_dir: &Path, path: &str, format: OutputFormat) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let path = normalize_project_path(path)?;
+        let revisions = artifact_ops::artifact_history(conn, &path)?;
 
         let formatter = OutputFormatter::new(format);
-        print!("{}", formatter.format_artifacts(&[artifact]));
+        print!("{}", formatter.format_artifact_history(&path, &revisions));
+
+        Ok(())
+    })
+}
+
+/// Shows the artifacts linked to the one at `path`, as text, JSON, DOT or
+/// Mermaid.
+pub fn graph(
+    project_dir: &Path,
+    path: &str,
+    direction: GraphDirection,
+    depth: Option<usize>,
+    dot: bool,
+    mermaid: bool,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let graph = artifact_ops::artifact_graph(conn, path, direction, depth, project_dir)?;
+
+        if dot {
+            print!("{}", format_graph_dot(&graph));
+        } else if mermaid {
+            print!("{}", format_graph_mermaid(&graph));
+        } else {
+            let formatter = OutputFormatter::new(format);
+            print!("{}", formatter.format_artifact_graph(&graph));
+        }
 
         Ok(())
     })
@@ -84,7 +261,7 @@ mod tests {
 
     fn setup() -> TempDir {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         // Create a test file
         fs::create_dir_all(temp.path().join("src")).unwrap();
@@ -104,10 +281,23 @@ mod tests {
             "Main entry point",
             Some("v1.0.0"),
             vec![],
+            &[],
+            &[],
+            false,
         )
         .unwrap();
 
-        list(temp.path(), None, None, None, None, 10, OutputFormat::Human).unwrap();
+        list(
+            temp.path(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            10,
+            OutputFormat::Human,
+        )
+        .unwrap();
     }
 
     #[test]
@@ -121,9 +311,35 @@ mod tests {
             "Main entry point",
             None,
             vec![],
+            &[],
+            &[],
+            false,
         )
         .unwrap();
 
         show(temp.path(), "src/main.rs", OutputFormat::Human).unwrap();
     }
+
+    #[test]
+    fn test_history() {
+        let temp = setup();
+
+        for agent in ["agent-1", "agent-2"] {
+            add(
+                temp.path(),
+                "src/main.rs",
+                agent,
+                "Main",
+                None,
+                vec![],
+                &[],
+                &[],
+                false,
+            )
+            .unwrap();
+        }
+
+        history(temp.path(), "src/main.rs", OutputFormat::Human).unwrap();
+        assert!(history(temp.path(), "src/lib.rs", OutputFormat::Json).is_err());
+    }
 }
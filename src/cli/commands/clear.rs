@@ -1,9 +1,11 @@
+use crate::core::config;
 use crate::core::errors::BBResult;
 use crate::core::operations::{
     agent as agent_ops, artifact as artifact_ops, message as message_ops,
 };
 use crate::core::validation::duration::validate_duration;
 use crate::db::connection::with_connection;
+use crate::db::queries::message::MessageFilter;
 use chrono::Utc;
 use std::io::{self, Write};
 use std::path::Path;
@@ -25,17 +27,11 @@ pub fn clear(
 
         with_connection(project_dir, |conn| {
             // Count messages to be deleted
-            let msgs = message_ops::list_messages(
-                conn,
-                Some(cutoff),
-                &[],
-                None,
-                None,
-                None,
-                None,
-                None,
-                10000,
-            )?;
+            let filter = MessageFilter {
+                since: Some(cutoff),
+                ..Default::default()
+            };
+            let msgs = message_ops::list_messages(conn, &filter, 10000)?;
             counts.messages = msgs.len();
             Ok(())
         })?;
@@ -49,13 +45,10 @@ pub fn clear(
         }
     }
 
+    let liveness = &config::current().liveness;
     if reset_offline {
         with_connection(project_dir, |conn| {
-            let agents = agent_ops::get_all_agents_with_liveness(conn)?;
-            counts.offline_agents = agents
-                .iter()
-                .filter(|a| a.status == crate::core::models::agent::AgentStatus::Offline)
-                .count();
+            counts.offline_agents = agent_ops::offline_agents(conn, liveness)?.len();
             Ok(())
         })?;
 
@@ -66,7 +59,7 @@ pub fn clear(
 
     if artifacts {
         with_connection(project_dir, |conn| {
-            let arts = artifact_ops::list_artifacts(conn, None, None, None, None, 10000)?;
+            let arts = artifact_ops::list_artifacts(conn, None, None, None, None, None, 10000)?;
             counts.artifacts = arts.len();
             Ok(())
         })?;
@@ -121,7 +114,7 @@ pub fn clear(
 
     if reset_offline {
         with_connection(project_dir, |conn| {
-            let deleted = agent_ops::delete_offline_agents(conn)?;
+            let deleted = agent_ops::delete_offline_agents(conn, liveness)?;
             println!("Deleted {deleted} offline agents");
             Ok(())
         })?;
@@ -0,0 +1,64 @@
+use crate::cli::output::OutputFormat;
+use crate::core::config::Config;
+use crate::core::errors::BBResult;
+use std::path::Path;
+
+pub fn get(project_dir: &Path, key: &str, format: OutputFormat) -> BBResult<()> {
+    let value = Config::load_or_default(project_dir).get(key)?;
+
+    match format {
+        OutputFormat::Human => match value {
+            toml::Value::String(s) => println!("{s}"),
+            value => println!("{value}"),
+        },
+        OutputFormat::Json => println!("{}", serde_json::json!({ "key": key, "value": value })),
+    }
+    Ok(())
+}
+
+pub fn set(project_dir: &Path, key: &str, value: &str) -> BBResult<()> {
+    let value = Config::set(project_dir, key, value)?.get(key)?;
+    println!("{key} = {value}");
+    Ok(())
+}
+
+pub fn list(project_dir: &Path, format: OutputFormat) -> BBResult<()> {
+    let entries = Config::load_or_default(project_dir).entries();
+
+    match format {
+        OutputFormat::Human => {
+            for (key, value) in entries {
+                println!("{key} = {value}");
+            }
+        }
+        OutputFormat::Json => {
+            let map: serde_json::Map<String, serde_json::Value> = entries
+                .into_iter()
+                .map(|(key, value)| (key, serde_json::json!(value)))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&map)?);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::init;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_then_get() {
+        let temp = TempDir::new().unwrap();
+        init::run(temp.path(), None).unwrap();
+
+        set(temp.path(), "liveness.stale_minutes", "45").unwrap();
+        get(temp.path(), "liveness.stale_minutes", OutputFormat::Human).unwrap();
+        list(temp.path(), OutputFormat::Json).unwrap();
+
+        let config = Config::load(temp.path()).unwrap();
+        assert_eq!(config.liveness.stale_minutes, 45);
+        assert!(get(temp.path(), "liveness.stale", OutputFormat::Human).is_err());
+    }
+}
@@ -31,7 +31,7 @@ mod tests {
     #[test]
     fn test_destroy_without_confirm() {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         // Without confirm, should not delete
         run(temp.path(), false).unwrap();
@@ -41,7 +41,7 @@ mod tests {
     #[test]
     fn test_destroy_with_confirm() {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         // With confirm, should delete
         run(temp.path(), true).unwrap();
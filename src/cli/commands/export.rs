@@ -3,6 +3,7 @@ use crate::core::operations::agent as agent_ops;
 use crate::core::operations::artifact as artifact_ops;
 use crate::core::operations::message as message_ops;
 use crate::db::connection::with_connection;
+use crate::db::queries::message::MessageFilter;
 use chrono::Utc;
 use serde::Serialize;
 use std::path::Path;
@@ -18,10 +19,9 @@ struct ExportData {
 
 pub fn export(project_dir: &Path) -> BBResult<()> {
     let (agents, messages, artifacts) = with_connection(project_dir, |conn| {
-        let agents = agent_ops::get_all_agents_with_liveness(conn)?;
-        let messages =
-            message_ops::list_messages(conn, None, &[], None, None, None, None, None, 10000)?;
-        let artifacts = artifact_ops::list_artifacts(conn, None, None, None, None, 10000)?;
+        let agents = agent_ops::get_all_agents(conn)?;
+        let messages = message_ops::list_messages(conn, &MessageFilter::default(), 10000)?;
+        let artifacts = artifact_ops::list_artifacts(conn, None, None, None, None, None, 10000)?;
 
         Ok((agents, messages, artifacts))
     })?;
@@ -51,16 +51,17 @@ mod tests {
     #[test]
     fn test_export() {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         message::post(
             temp.path(),
             "agent-1",
             "Test message",
             vec![],
-            Priority::Normal,
+            Some(Priority::Normal),
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
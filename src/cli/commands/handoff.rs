@@ -0,0 +1,162 @@
+use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::config;
+use crate::core::errors::BBResult;
+use crate::core::models::handoff::{Handoff, HandoffStatus};
+use crate::core::models::message::Priority;
+use crate::core::operations::handoff as handoff_ops;
+use crate::db::connection::with_connection;
+use crate::db::queries::handoff::HandoffFilter;
+use crate::util::duration::parse_duration;
+use crate::util::ref_::parse_ref;
+use std::path::Path;
+
+#[allow(clippy::too_many_arguments)]
+pub fn handoff(
+    project_dir: &Path,
+    from_agent: &str,
+    to: &str,
+    summary: &str,
+    refs: Vec<String>,
+    artifacts: Vec<String>,
+    priority: Option<Priority>,
+    escalate_after: &str,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let priority = priority.unwrap_or(config::current().messages.default_priority);
+    let parsed_refs: Result<Vec<_>, _> = refs.iter().map(|r| parse_ref(r)).collect();
+    let parsed_refs = parsed_refs?;
+    let escalate_after = parse_duration(escalate_after)?;
+
+    with_connection(project_dir, |conn| {
+        let handoff = handoff_ops::create_handoff(
+            conn,
+            from_agent,
+            to,
+            summary,
+            parsed_refs,
+            artifacts,
+            priority,
+            escalate_after,
+        )?;
+
+        print_handoff(&handoff, format, || {
+            format!("Handed off #{} to {}", handoff.id, handoff.to_agent)
+        });
+        Ok(())
+    })
+}
+
+pub fn accept(
+    project_dir: &Path,
+    agent_id: &str,
+    id: i64,
+    note: Option<&str>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let handoff = handoff_ops::accept_handoff(conn, agent_id, id, note)?;
+        print_handoff(&handoff, format, || {
+            format!(
+                "Accepted handoff #{} from {}",
+                handoff.id, handoff.from_agent
+            )
+        });
+        Ok(())
+    })
+}
+
+pub fn decline(
+    project_dir: &Path,
+    agent_id: &str,
+    id: i64,
+    reason: Option<&str>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let handoff = handoff_ops::decline_handoff(conn, agent_id, id, reason)?;
+        print_handoff(&handoff, format, || {
+            format!(
+                "Declined handoff #{} from {}",
+                handoff.id, handoff.from_agent
+            )
+        });
+        Ok(())
+    })
+}
+
+pub fn list(
+    project_dir: &Path,
+    to: Option<&str>,
+    from: Option<&str>,
+    status: Option<HandoffStatus>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let filter = HandoffFilter {
+            to: to.map(String::from),
+            from: from.map(String::from),
+            status,
+        };
+        let handoffs = handoff_ops::list_handoffs(conn, &filter, limit)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_handoffs(&handoffs));
+
+        Ok(())
+    })
+}
+
+fn print_handoff(handoff: &Handoff, format: OutputFormat, human: impl FnOnce() -> String) {
+    match format {
+        OutputFormat::Human => println!("{}", human()),
+        OutputFormat::Json => print!(
+            "{}",
+            OutputFormatter::new(format).format_handoffs(std::slice::from_ref(handoff))
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::init;
+    use crate::core::errors::BBError;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_handoff_accept_decline() {
+        let temp = TempDir::new().unwrap();
+        init::run(temp.path(), None).unwrap();
+
+        for _ in 0..2 {
+            handoff(
+                temp.path(),
+                "agent-1",
+                "agent-2",
+                "Finish the parser",
+                vec!["tt:task:42".to_string()],
+                vec![],
+                Some(Priority::Normal),
+                "30m",
+                OutputFormat::Human,
+            )
+            .unwrap();
+        }
+
+        let err = accept(temp.path(), "agent-3", 1, None, OutputFormat::Human).unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { .. }));
+        accept(temp.path(), "agent-2", 1, Some("on it"), OutputFormat::Json).unwrap();
+        decline(temp.path(), "agent-2", 2, None, OutputFormat::Human).unwrap();
+
+        list(
+            temp.path(),
+            Some("agent-2"),
+            None,
+            None,
+            20,
+            OutputFormat::Human,
+        )
+        .unwrap();
+    }
+}
@@ -1,12 +1,29 @@
-use crate::core::errors::BBResult;
+use crate::core::config::{Config, TEMPLATES};
+use crate::core::errors::{BBError, BBResult};
 use crate::db::connection::with_connection;
 use crate::db::schema::init_schema;
 use crate::util::discovery::is_initialized;
 use std::fs;
 use std::path::Path;
 
-pub fn run(project_dir: &Path) -> BBResult<()> {
+pub fn run(project_dir: &Path, template: Option<&str>) -> BBResult<()> {
+    let template = match template {
+        Some(name) => Some(Config::template(name).ok_or_else(|| {
+            let names: Vec<&str> = TEMPLATES.iter().map(|(n, _)| *n).collect();
+            BBError::invalid_field(
+                "template",
+                format!("unknown template: {name} (available: {})", names.join(", ")),
+            )
+        })?),
+        None => None,
+    };
+
     if is_initialized(project_dir) {
+        if let Some(text) = template {
+            write_config(project_dir, text)?;
+        }
+        // Brings an older schema up to date, or refuses one newer than this bb
+        with_connection(project_dir, |_| Ok(()))?;
         println!(
             "Blackboard already initialized at {}",
             project_dir.display()
@@ -31,10 +48,28 @@ pub fn run(project_dir: &Path) -> BBResult<()> {
     // Initialize database schema
     with_connection(project_dir, |conn| init_schema(conn))?;
 
+    if let Some(text) = template {
+        write_config(project_dir, text)?;
+    }
+
     println!("Initialized blackboard at {}/.bb/", project_dir.display());
     Ok(())
 }
 
+/// Seeds the board's config from a template, unless it already has one.
+fn write_config(project_dir: &Path, text: &str) -> BBResult<()> {
+    let path = Config::path(project_dir);
+    if path.exists() {
+        return Err(BBError::InvalidInput(format!(
+            "{} already exists; change it with `bb config set`",
+            path.display()
+        )));
+    }
+    fs::write(&path, text)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,7 +79,7 @@ mod tests {
     fn test_init_creates_bb_directory() {
         let temp = TempDir::new().unwrap();
 
-        run(temp.path()).unwrap();
+        run(temp.path(), None).unwrap();
 
         assert!(temp.path().join(".bb").exists());
         assert!(temp.path().join(".bb/blackboard.db").exists());
@@ -55,7 +90,7 @@ mod tests {
     fn test_init_creates_gitignore_with_star() {
         let temp = TempDir::new().unwrap();
 
-        run(temp.path()).unwrap();
+        run(temp.path(), None).unwrap();
 
         let gitignore = fs::read_to_string(temp.path().join(".bb/.gitignore")).unwrap();
         assert_eq!(gitignore, "*\n");
@@ -66,11 +101,33 @@ mod tests {
         let temp = TempDir::new().unwrap();
 
         // First init
-        run(temp.path()).unwrap();
+        run(temp.path(), None).unwrap();
 
         // Second init should succeed (no-op)
-        run(temp.path()).unwrap();
+        run(temp.path(), None).unwrap();
 
         assert!(temp.path().join(".bb").exists());
     }
+
+    #[test]
+    fn test_init_with_template() {
+        let temp = TempDir::new().unwrap();
+
+        run(temp.path(), Some("team")).unwrap();
+
+        let config = Config::load(temp.path()).unwrap();
+        assert_eq!(config.liveness.stale_minutes, 120);
+
+        // An existing config is never overwritten
+        assert!(run(temp.path(), Some("ci")).is_err());
+        assert_eq!(Config::load(temp.path()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_init_unknown_template() {
+        let temp = TempDir::new().unwrap();
+
+        assert!(run(temp.path(), Some("nope")).is_err());
+        assert!(!temp.path().join(".bb").exists());
+    }
 }
@@ -0,0 +1,123 @@
+use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::config;
+use crate::core::errors::BBResult;
+use crate::core::models::lease::LeaseMode;
+use crate::core::operations::lease as lease_ops;
+use crate::db::connection::with_connection;
+use crate::util::duration::parse_duration;
+use std::path::Path;
+
+pub fn claim(
+    project_dir: &Path,
+    agent_id: &str,
+    path: &str,
+    shared: bool,
+    ttl: &str,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let ttl = parse_duration(ttl)?;
+    let mode = if shared {
+        LeaseMode::Shared
+    } else {
+        LeaseMode::Exclusive
+    };
+
+    let liveness = &config::current().liveness;
+    with_connection(project_dir, |conn| {
+        let lease = lease_ops::claim_path(conn, agent_id, path, mode, ttl, project_dir, liveness)?;
+
+        match format {
+            OutputFormat::Human => println!(
+                "Claimed {} ({}) until {}",
+                lease.path,
+                lease.mode.as_str(),
+                config::current().display.format_datetime(lease.expires_at)
+            ),
+            OutputFormat::Json => {
+                print!("{}", OutputFormatter::new(format).format_leases(&[lease]))
+            }
+        }
+
+        Ok(())
+    })
+}
+
+pub fn release(project_dir: &Path, agent_id: &str, path: Option<&str>) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        match path {
+            Some(path) => {
+                lease_ops::release_path(conn, agent_id, path)?;
+                println!("Released {path}");
+            }
+            None => {
+                let count = lease_ops::release_all(conn, agent_id)?;
+                println!("Released {count} claim(s)");
+            }
+        }
+
+        Ok(())
+    })
+}
+
+pub fn list(project_dir: &Path, path: Option<&str>, format: OutputFormat) -> BBResult<()> {
+    let liveness = &config::current().liveness;
+    with_connection(project_dir, |conn| {
+        let leases = lease_ops::list_claims(conn, path, liveness)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_leases(&leases));
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::init;
+    use crate::core::errors::BBError;
+    use tempfile::TempDir;
+
+    fn setup() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        init::run(temp.path(), None).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_claim_release_list() {
+        let temp = setup();
+
+        claim(
+            temp.path(),
+            "agent-1",
+            "src/**",
+            false,
+            "30m",
+            OutputFormat::Human,
+        )
+        .unwrap();
+        let err = claim(
+            temp.path(),
+            "agent-2",
+            "src/main.rs",
+            true,
+            "10m",
+            OutputFormat::Human,
+        )
+        .unwrap_err();
+        assert!(matches!(err, BBError::LeaseConflict { .. }));
+
+        list(temp.path(), Some("src/main.rs"), OutputFormat::Human).unwrap();
+        release(temp.path(), "agent-1", None).unwrap();
+        claim(
+            temp.path(),
+            "agent-2",
+            "src/main.rs",
+            true,
+            "10m",
+            OutputFormat::Json,
+        )
+        .unwrap();
+    }
+}
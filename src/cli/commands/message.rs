@@ -1,10 +1,13 @@
 use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::config;
 use crate::core::errors::BBResult;
 use crate::core::models::message::Priority;
 use crate::core::operations::agent as agent_ops;
+use crate::core::operations::inbox as inbox_ops;
 use crate::core::operations::message as message_ops;
 use crate::core::validation::duration::validate_duration;
 use crate::db::connection::with_connection;
+use crate::db::queries::message::MessageFilter;
 use crate::util::ref_::parse_ref;
 use chrono::Utc;
 use std::path::Path;
@@ -19,9 +22,11 @@ pub fn log(
     ref_where: Option<&str>,
     ref_what: Option<&str>,
     ref_ref: Option<&str>,
-    limit: usize,
+    to: Option<&str>,
+    limit: Option<usize>,
     format: OutputFormat,
 ) -> BBResult<()> {
+    let limit = limit.unwrap_or(config::current().log.default_limit);
     let since_dt = if let Some(s) = since {
         let duration = validate_duration(s)?;
         Some(Utc::now() - duration)
@@ -35,9 +40,18 @@ pub fn log(
             let _ = agent_ops::touch_agent(conn, agent);
         }
 
-        let messages = message_ops::list_messages(
-            conn, since_dt, &tags, from_agent, priority, ref_where, ref_what, ref_ref, limit,
-        )?;
+        let filter = MessageFilter {
+            since: since_dt,
+            tags,
+            from_agent: from_agent.map(String::from),
+            priority,
+            ref_where: ref_where.map(String::from),
+            ref_what: ref_what.map(String::from),
+            ref_ref: ref_ref.map(String::from),
+            to: to.map(String::from),
+            ..Default::default()
+        };
+        let messages = message_ops::list_messages(conn, &filter, limit)?;
 
         let formatter = OutputFormatter::new(format);
         print!("{}", formatter.format_messages(&messages));
@@ -46,15 +60,47 @@ pub fn log(
     })
 }
 
+pub fn mentions(
+    project_dir: &Path,
+    agent_id: &str,
+    since: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let since_dt = if let Some(s) = since {
+        let duration = validate_duration(s)?;
+        Some(Utc::now() - duration)
+    } else {
+        None
+    };
+
+    with_connection(project_dir, |conn| {
+        let filter = MessageFilter {
+            since: since_dt,
+            to: Some(agent_id.to_string()),
+            ..Default::default()
+        };
+        let messages = message_ops::list_messages(conn, &filter, limit)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_messages(&messages));
+
+        Ok(())
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn post(
     project_dir: &Path,
     from_agent: &str,
     content: &str,
     tags: Vec<String>,
-    priority: Priority,
+    priority: Option<Priority>,
     reply_to: Option<i64>,
     refs: Vec<String>,
+    to: Vec<String>,
 ) -> BBResult<()> {
+    let priority = priority.unwrap_or(config::current().messages.default_priority);
     let parsed_refs: Result<Vec<_>, _> = refs.iter().map(|r| parse_ref(r)).collect();
     let parsed_refs = parsed_refs?;
 
@@ -67,6 +113,7 @@ pub fn post(
             priority,
             reply_to,
             parsed_refs,
+            to,
         )?;
 
         println!("Posted message #{} from {}", message.id, message.from_agent);
@@ -75,12 +122,69 @@ pub fn post(
     })
 }
 
-pub fn show_message(project_dir: &Path, id: i64, format: OutputFormat) -> BBResult<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn wait(
+    project_dir: &Path,
+    reply_to: Option<i64>,
+    tags: Vec<String>,
+    mention: Option<&str>,
+    from_agent: Option<&str>,
+    priority: Option<Priority>,
+    after: Option<i64>,
+    timeout: &str,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let timeout = validate_duration(timeout)?;
+
+    let filter = MessageFilter {
+        tags,
+        from_agent: from_agent.map(String::from),
+        priority,
+        in_reply_to: reply_to,
+        to: mention.map(String::from),
+        ..Default::default()
+    };
+
+    let outcome = with_connection(project_dir, |conn| {
+        message_ops::wait_for_messages(conn, &filter, after, timeout, limit)
+    })?;
+
+    let formatter = OutputFormatter::new(format);
+    print!("{}", formatter.format_wait_outcome(&outcome));
+
+    Ok(())
+}
+
+pub fn inbox(
+    project_dir: &Path,
+    agent_id: &str,
+    limit: usize,
+    peek: bool,
+    format: OutputFormat,
+) -> BBResult<()> {
     with_connection(project_dir, |conn| {
-        let messages = message_ops::get_message_thread(conn, id)?;
+        let page = inbox_ops::read_inbox(conn, agent_id, limit, peek)?;
 
         let formatter = OutputFormatter::new(format);
-        print!("{}", formatter.format_message_thread(&messages));
+        print!("{}", formatter.format_inbox(&page));
+
+        Ok(())
+    })
+}
+
+pub fn show_message(
+    project_dir: &Path,
+    id: i64,
+    tree: bool,
+    depth: Option<usize>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let thread = message_ops::get_message_thread(conn, id, depth, 0, None)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_message_thread(&thread, tree));
 
         Ok(())
     })
@@ -94,7 +198,7 @@ mod tests {
 
     fn setup() -> TempDir {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
         temp
     }
 
@@ -107,9 +211,10 @@ mod tests {
             "test-agent",
             "Hello world",
             vec!["greeting".to_string()],
-            Priority::Normal,
+            Some(Priority::Normal),
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -122,12 +227,101 @@ mod tests {
             None,
             None,
             None,
+            None,
+            Some(10),
+            OutputFormat::Human,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_wait_returns_reply() {
+        let temp = setup();
+
+        post(
+            temp.path(),
+            "agent-1",
+            "Question",
+            vec![],
+            Some(Priority::Normal),
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        post(
+            temp.path(),
+            "agent-2",
+            "Answer",
+            vec![],
+            Some(Priority::Normal),
+            Some(1),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        wait(
+            temp.path(),
+            Some(1),
+            vec![],
+            None,
+            None,
+            None,
+            Some(1),
+            "1s",
             10,
             OutputFormat::Human,
         )
         .unwrap();
     }
 
+    #[test]
+    fn test_inbox() {
+        let temp = setup();
+
+        post(
+            temp.path(),
+            "agent-1",
+            "For everyone",
+            vec![],
+            Some(Priority::Normal),
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        inbox(temp.path(), "agent-2", 10, false, OutputFormat::Human).unwrap();
+
+        let unread =
+            with_connection(temp.path(), |conn| inbox_ops::unread_count(conn, "agent-2")).unwrap();
+        assert_eq!(unread, 0);
+    }
+
+    #[test]
+    fn test_post_with_recipients_and_mentions() {
+        let temp = setup();
+
+        post(
+            temp.path(),
+            "agent-1",
+            "@agent-3 can you pair with agent-2?",
+            vec![],
+            Some(Priority::Normal),
+            None,
+            vec![],
+            vec!["agent-2".to_string()],
+        )
+        .unwrap();
+
+        mentions(temp.path(), "agent-3", None, 10, OutputFormat::Human).unwrap();
+
+        let message =
+            with_connection(temp.path(), |conn| message_ops::get_message(conn, 1)).unwrap();
+        assert_eq!(message.recipients, vec!["agent-2", "agent-3"]);
+    }
+
     #[test]
     fn test_post_with_ref() {
         let temp = setup();
@@ -137,9 +331,10 @@ mod tests {
             "test-agent",
             "Hello with ref",
             vec![],
-            Priority::Normal,
+            Some(Priority::Normal),
             None,
             vec!["tt:task:13".to_string()],
+            vec![],
         )
         .unwrap();
 
@@ -152,7 +347,8 @@ mod tests {
             Some("tt"),
             Some("task"),
             Some("13"),
-            10,
+            None,
+            Some(10),
             OutputFormat::Human,
         )
         .unwrap();
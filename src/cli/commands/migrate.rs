@@ -0,0 +1,105 @@
+use crate::core::errors::BBResult;
+use crate::db::connection::with_unmigrated_connection;
+use crate::db::migrations::{
+    LATEST_VERSION, MIGRATIONS, current_version, pending_migrations, run_migrations,
+};
+use std::path::Path;
+
+pub fn run(project_dir: &Path, status: bool, dry_run: bool) -> BBResult<()> {
+    with_unmigrated_connection(project_dir, |conn| {
+        let current = current_version(conn)?;
+
+        if status {
+            println!("Schema version: {current} (this bb supports up to {LATEST_VERSION})");
+            for migration in MIGRATIONS {
+                let state = if migration.version <= current {
+                    "applied"
+                } else {
+                    "pending"
+                };
+                println!("  {:03}_{}  {}", migration.version, migration.name, state);
+            }
+            if current > LATEST_VERSION {
+                println!("  Database is newer than this bb. Upgrade bb to use it.");
+            }
+            return Ok(());
+        }
+
+        let pending = pending_migrations(conn)?;
+        if pending.is_empty() {
+            println!("Schema is up to date (version {current}).");
+            return Ok(());
+        }
+
+        if dry_run {
+            println!("Would apply {} migration(s):", pending.len());
+            for migration in pending {
+                println!("  {:03}_{}", migration.version, migration.name);
+            }
+            return Ok(());
+        }
+
+        for version in run_migrations(conn)? {
+            let name = MIGRATIONS
+                .iter()
+                .find(|m| m.version == version)
+                .map_or("", |m| m.name);
+            println!("Applied {version:03}_{name}");
+        }
+        println!("Schema is now at version {}.", current_version(conn)?);
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::init;
+    use rusqlite::Connection;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn setup_v1() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".bb")).unwrap();
+        let conn = Connection::open(temp.path().join(".bb/blackboard.db")).unwrap();
+        conn.execute_batch(include_str!("../../../tests/fixtures/v1_database.sql"))
+            .unwrap();
+        temp
+    }
+
+    fn version(temp: &TempDir) -> i64 {
+        let conn = Connection::open(temp.path().join(".bb/blackboard.db")).unwrap();
+        current_version(&conn).unwrap()
+    }
+
+    #[test]
+    fn test_status_and_dry_run_leave_schema_alone() {
+        let temp = setup_v1();
+
+        run(temp.path(), true, false).unwrap();
+        run(temp.path(), false, true).unwrap();
+
+        assert_eq!(version(&temp), 1);
+    }
+
+    #[test]
+    fn test_migrate_upgrades_v1_database() {
+        let temp = setup_v1();
+
+        run(temp.path(), false, false).unwrap();
+
+        assert_eq!(version(&temp), LATEST_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_on_fresh_init_is_noop() {
+        let temp = TempDir::new().unwrap();
+        init::run(temp.path(), None).unwrap();
+
+        run(temp.path(), false, false).unwrap();
+
+        assert_eq!(version(&temp), LATEST_VERSION);
+    }
+}
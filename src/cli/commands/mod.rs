@@ -1,10 +1,17 @@
 pub mod artifact;
 pub mod clear;
+pub mod config;
 pub mod destroy;
 pub mod export;
+pub mod handoff;
 pub mod init;
 pub mod install;
+pub mod lease;
 pub mod message;
+pub mod migrate;
+pub mod question;
 pub mod ref_;
+pub mod search;
 pub mod status;
 pub mod summary;
+pub mod tag;
@@ -0,0 +1,104 @@
+use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::errors::BBResult;
+use crate::core::models::question::QuestionState;
+use crate::core::operations::question as question_ops;
+use crate::db::connection::with_connection;
+use crate::db::queries::question::QuestionFilter;
+use std::path::Path;
+
+/// Accepts reply `accept` as the answer to question `id`, or closes the
+/// question when `accept` is `None`.
+pub fn answer(
+    project_dir: &Path,
+    agent_id: &str,
+    id: i64,
+    accept: Option<i64>,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let question = match accept {
+            Some(answer_id) => question_ops::accept_answer(conn, agent_id, id, answer_id)?,
+            None => question_ops::close_question(conn, agent_id, id)?,
+        };
+
+        match format {
+            OutputFormat::Human => match accept {
+                Some(answer_id) => println!("Accepted #{answer_id} as the answer to #{id}"),
+                None => println!("Closed question #{id}"),
+            },
+            OutputFormat::Json => print!(
+                "{}",
+                OutputFormatter::new(format).format_questions(std::slice::from_ref(&question))
+            ),
+        }
+        Ok(())
+    })
+}
+
+pub fn list(
+    project_dir: &Path,
+    state: Option<QuestionState>,
+    to: Option<&str>,
+    from: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let filter = QuestionFilter {
+            state,
+            to: to.map(String::from),
+            from: from.map(String::from),
+            before: None,
+        };
+        let questions = question_ops::list_questions(conn, &filter, limit)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_questions(&questions));
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::commands::{init, message};
+    use crate::core::models::message::Priority;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_answer_and_list() {
+        let temp = TempDir::new().unwrap();
+        init::run(temp.path(), None).unwrap();
+
+        for (from, tags, reply_to) in [
+            ("agent-1", vec!["question".to_string()], None),
+            ("agent-2", vec![], Some(1)),
+        ] {
+            message::post(
+                temp.path(),
+                from,
+                "Which port does the API use?",
+                tags,
+                Some(Priority::Normal),
+                reply_to,
+                vec![],
+                vec!["agent-2".to_string()],
+            )
+            .unwrap();
+        }
+
+        list(
+            temp.path(),
+            Some(QuestionState::Open),
+            Some("agent-2"),
+            None,
+            20,
+            OutputFormat::Human,
+        )
+        .unwrap();
+        answer(temp.path(), "agent-1", 1, Some(2), OutputFormat::Json).unwrap();
+        answer(temp.path(), "agent-1", 1, None, OutputFormat::Human).unwrap();
+        assert!(answer(temp.path(), "agent-1", 1, None, OutputFormat::Human).is_err());
+    }
+}
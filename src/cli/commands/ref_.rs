@@ -29,7 +29,7 @@ mod tests {
 
     fn setup() -> TempDir {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         fs::create_dir_all(temp.path().join("src")).unwrap();
         fs::write(temp.path().join("src/main.rs"), "").unwrap();
@@ -47,9 +47,10 @@ mod tests {
             "agent-1",
             "Message about task 13",
             vec![],
-            Priority::Normal,
+            Some(Priority::Normal),
             None,
             vec!["tt:task:13".to_string()],
+            vec![],
         )
         .unwrap();
 
@@ -61,6 +62,9 @@ mod tests {
             "File for task 13",
             None,
             vec!["tt:task:13".to_string()],
+            &[],
+            &[],
+            false,
         )
         .unwrap();
 
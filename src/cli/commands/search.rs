@@ -0,0 +1,52 @@
+use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::errors::BBResult;
+use crate::core::models::message::Priority;
+use crate::core::operations::search as search_ops;
+use crate::core::validation::duration::validate_duration;
+use crate::db::connection::with_connection;
+use crate::db::queries::message::MessageFilter;
+use chrono::Utc;
+use std::path::Path;
+
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    project_dir: &Path,
+    query: &str,
+    raw: bool,
+    since: Option<&str>,
+    tags: Vec<String>,
+    from_agent: Option<&str>,
+    priority: Option<Priority>,
+    ref_where: Option<&str>,
+    ref_what: Option<&str>,
+    ref_ref: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let since_dt = if let Some(s) = since {
+        let duration = validate_duration(s)?;
+        Some(Utc::now() - duration)
+    } else {
+        None
+    };
+
+    let filter = MessageFilter {
+        since: since_dt,
+        tags,
+        from_agent: from_agent.map(String::from),
+        priority,
+        ref_where: ref_where.map(String::from),
+        ref_what: ref_what.map(String::from),
+        ref_ref: ref_ref.map(String::from),
+        ..Default::default()
+    };
+
+    with_connection(project_dir, |conn| {
+        let results = search_ops::search(conn, query, raw, &filter, limit)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_search_results(&results));
+
+        Ok(())
+    })
+}
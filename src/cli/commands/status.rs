@@ -1,19 +1,23 @@
 use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::config;
 use crate::core::errors::BBResult;
 use crate::core::models::agent::AgentStatus;
 use crate::core::operations::agent as agent_ops;
 use crate::core::operations::classify_liveness;
+use crate::core::validation::duration::validate_duration;
 use crate::db::connection::with_connection;
+use chrono::Utc;
 use std::collections::HashMap;
 use std::path::Path;
 
 pub fn status(project_dir: &Path, format: OutputFormat) -> BBResult<()> {
+    let config = config::current();
     with_connection(project_dir, |conn| {
-        let agents = agent_ops::get_all_agents_with_liveness(conn)?;
+        let agents = agent_ops::get_all_agents(conn)?;
 
         let mut liveness_map = HashMap::new();
         for agent in &agents {
-            let liveness = classify_liveness(agent.last_seen);
+            let liveness = classify_liveness(&config.liveness, &agent.id, agent.last_seen);
             liveness_map.insert(agent.id.clone(), liveness);
         }
 
@@ -52,12 +56,13 @@ pub fn status_set(
 }
 
 pub fn status_get(project_dir: &Path, agent_id: &str, format: OutputFormat) -> BBResult<()> {
+    let config = config::current();
     with_connection(project_dir, |conn| {
         let agent = agent_ops::get_agent(conn, agent_id)?.ok_or_else(|| {
             crate::core::errors::BBError::NotFound(format!("agent '{agent_id}' not found"))
         })?;
 
-        let liveness = classify_liveness(agent.last_seen);
+        let liveness = classify_liveness(&config.liveness, &agent.id, agent.last_seen);
         let mut liveness_map = HashMap::new();
         liveness_map.insert(agent.id.clone(), liveness);
 
@@ -76,6 +81,45 @@ pub fn status_clear(project_dir: &Path, agent_id: &str) -> BBResult<()> {
     })
 }
 
+/// Marks `agent_id` as seen now, leaving its status, task and progress as
+/// they are.
+pub fn heartbeat(project_dir: &Path, agent_id: &str) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let agent = agent_ops::touch_agent(conn, agent_id)?;
+        println!(
+            "{} seen at {}",
+            agent.id,
+            config::current().display.format_datetime(agent.last_seen)
+        );
+        Ok(())
+    })
+}
+
+pub fn timeline(
+    project_dir: &Path,
+    agent_id: Option<&str>,
+    since: Option<&str>,
+    limit: usize,
+    format: OutputFormat,
+) -> BBResult<()> {
+    let since_dt = if let Some(s) = since {
+        let duration = validate_duration(s)?;
+        Some(Utc::now() - duration)
+    } else {
+        None
+    };
+
+    let config = config::current();
+    with_connection(project_dir, |conn| {
+        let timelines = agent_ops::timeline(conn, &config.liveness, agent_id, since_dt, limit)?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_timeline(&timelines));
+
+        Ok(())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,7 +128,7 @@ mod tests {
 
     fn setup() -> TempDir {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
         temp
     }
 
@@ -129,4 +173,31 @@ mod tests {
         let result = with_connection(temp.path(), |conn| agent_ops::get_agent(conn, "test-agent"));
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_timeline() {
+        let temp = setup();
+
+        for status in [AgentStatus::Coding, AgentStatus::Blocked] {
+            status_set(
+                temp.path(),
+                "test-agent",
+                "working",
+                None,
+                Some(status),
+                None,
+            )
+            .unwrap();
+        }
+
+        timeline(
+            temp.path(),
+            Some("test-agent"),
+            Some("2h"),
+            20,
+            OutputFormat::Human,
+        )
+        .unwrap();
+        assert!(timeline(temp.path(), None, Some("soon"), 20, OutputFormat::Json).is_err());
+    }
 }
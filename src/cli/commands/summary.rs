@@ -1,18 +1,21 @@
 use crate::cli::output::{OutputFormat, OutputFormatter, SummaryData};
+use crate::core::config;
 use crate::core::errors::BBResult;
 use crate::core::models::agent::AgentStatus;
 use crate::core::models::message::Priority;
 use crate::core::operations::agent as agent_ops;
 use crate::core::operations::artifact as artifact_ops;
+use crate::core::operations::handoff as handoff_ops;
 use crate::core::operations::message as message_ops;
+use crate::core::operations::question as question_ops;
 use crate::db::connection::with_connection;
+use crate::db::queries::message::MessageFilter;
 use chrono::Utc;
 use std::path::Path;
 
 pub fn summary(project_dir: &Path, format: OutputFormat) -> BBResult<()> {
     let data = with_connection(project_dir, |conn| {
-        // Get all agents (includes liveness side-effect)
-        let agents = agent_ops::get_all_agents_with_liveness(conn)?;
+        let agents = agent_ops::get_all_agents(conn)?;
 
         // Separate blocked agents
         let blocked_agents: Vec<_> = agents
@@ -23,45 +26,40 @@ pub fn summary(project_dir: &Path, format: OutputFormat) -> BBResult<()> {
 
         // Recent messages (last 30 minutes)
         let recent_since = Utc::now() - chrono::Duration::minutes(30);
-        let recent_messages = message_ops::list_messages(
-            conn,
-            Some(recent_since),
-            &[],
-            None,
-            None,
-            None,
-            None,
-            None,
-            20,
-        )?;
+        let recent_filter = MessageFilter {
+            since: Some(recent_since),
+            ..Default::default()
+        };
+        let recent_messages = message_ops::list_messages(conn, &recent_filter, 20)?;
 
         // High priority messages
-        let high_priority_messages = message_ops::list_messages(
-            conn,
-            None,
-            &[],
-            None,
-            Some(Priority::High),
-            None,
-            None,
-            None,
-            10,
-        )?;
+        let high_priority_filter = MessageFilter {
+            priority: Some(Priority::High),
+            ..Default::default()
+        };
+        let high_priority_messages = message_ops::list_messages(conn, &high_priority_filter, 10)?;
 
         // Recent artifacts (last hour)
         let artifact_since = Utc::now() - chrono::Duration::hours(1);
-        let recent_artifacts = artifact_ops::list_artifacts(conn, None, None, None, None, 20)?;
+        let recent_artifacts =
+            artifact_ops::list_artifacts(conn, None, None, None, None, None, 20)?;
         let recent_artifacts: Vec<_> = recent_artifacts
             .into_iter()
-            .filter(|a| a.created_at >= artifact_since)
+            .filter(|a| a.updated_at >= artifact_since)
             .collect();
 
+        let pending_handoffs = handoff_ops::pending_handoffs(conn)?;
+        let stale_questions =
+            question_ops::stale_question_count(conn, &config::current().questions)?;
+
         Ok(SummaryData {
             active_agents: agents,
             blocked_agents,
             recent_messages,
             high_priority_messages,
             recent_artifacts,
+            pending_handoffs,
+            stale_questions,
         })
     })?;
 
@@ -82,16 +80,17 @@ mod tests {
     #[test]
     fn test_summary() {
         let temp = TempDir::new().unwrap();
-        init::run(temp.path()).unwrap();
+        init::run(temp.path(), None).unwrap();
 
         message::post(
             temp.path(),
             "agent-1",
             "Test message",
             vec![],
-            Priority::Normal,
+            Some(Priority::Normal),
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -0,0 +1,16 @@
+use crate::cli::output::{OutputFormat, OutputFormatter};
+use crate::core::errors::BBResult;
+use crate::core::operations::tag as tag_ops;
+use crate::db::connection::with_connection;
+use std::path::Path;
+
+pub fn list(project_dir: &Path, format: OutputFormat) -> BBResult<()> {
+    with_connection(project_dir, |conn| {
+        let list = tag_ops::list_tags(conn, tag_ops::registry())?;
+
+        let formatter = OutputFormatter::new(format);
+        print!("{}", formatter.format_tags(&list));
+
+        Ok(())
+    })
+}
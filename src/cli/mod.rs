@@ -3,7 +3,10 @@ pub mod output;
 
 use crate::core::errors::BBResult;
 use crate::core::models::agent::AgentStatus;
+use crate::core::models::artifact::GraphDirection;
+use crate::core::models::handoff::HandoffStatus;
 use crate::core::models::message::Priority;
+use crate::core::models::question::QuestionState;
 use crate::util::discovery::find_blackboard_dir;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
@@ -36,7 +39,11 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize a new blackboard
-    Init,
+    Init {
+        /// Seed .bb/config.toml from a template (default, team, ci)
+        #[arg(long)]
+        template: Option<String>,
+    },
 
     /// Print MCP installation instructions
     Install {
@@ -53,6 +60,17 @@ pub enum Commands {
         local: bool,
     },
 
+    /// Apply pending database schema migrations
+    Migrate {
+        /// Show applied and pending migrations without changing anything
+        #[arg(long, conflicts_with = "dry_run")]
+        status: bool,
+
+        /// List the migrations that would be applied
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Remove the blackboard (use with caution)
     Destroy {
         /// Confirm destruction
@@ -66,6 +84,24 @@ pub enum Commands {
         command: Option<StatusCommands>,
     },
 
+    /// Mark yourself as seen now without changing your status
+    Heartbeat,
+
+    /// Show how agents' status, task and progress changed over time
+    Timeline {
+        /// Only this agent
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Only changes within this duration (e.g., 30m, 2h, 1d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Most recent changes to show per agent
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
     /// Show message log
     Log {
         /// Show messages since duration (e.g., 10m, 1h, 2d)
@@ -94,9 +130,17 @@ pub enum Commands {
         #[arg(long)]
         ref_ref: Option<String>,
 
-        /// Limit number of results
-        #[arg(short, long, default_value = "20")]
-        limit: usize,
+        /// Only messages addressed to this agent
+        #[arg(long, conflicts_with = "to_me")]
+        to: Option<String>,
+
+        /// Only messages addressed to you
+        #[arg(long)]
+        to_me: bool,
+
+        /// Limit number of results (default: log.default_limit in config)
+        #[arg(short, long)]
+        limit: Option<usize>,
     },
 
     /// Post a message
@@ -108,9 +152,9 @@ pub enum Commands {
         #[arg(long = "tag")]
         tags: Vec<String>,
 
-        /// Set priority
-        #[arg(long, default_value = "normal")]
-        priority: Priority,
+        /// Set priority (default: messages.default_priority in config)
+        #[arg(long)]
+        priority: Option<Priority>,
 
         /// Reply to message ID
         #[arg(long)]
@@ -119,12 +163,81 @@ pub enum Commands {
         /// Attach references (where:what:ref, repeatable)
         #[arg(long = "ref")]
         refs: Vec<String>,
+
+        /// Address the message to an agent (repeatable; @mentions are added too)
+        #[arg(long = "to")]
+        to: Vec<String>,
     },
 
-    /// Show a specific message and its thread
+    /// Block until a matching message arrives or the timeout elapses
+    Wait {
+        /// Only match replies to this message ID
+        #[arg(long)]
+        reply_to: Option<i64>,
+
+        /// Filter by tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Only match messages addressed to this agent (--to or @mention)
+        #[arg(long)]
+        mention: Option<String>,
+
+        /// Filter by agent
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Filter by minimum priority
+        #[arg(long)]
+        priority: Option<Priority>,
+
+        /// Only match messages with an ID greater than this (default: latest)
+        #[arg(long)]
+        after: Option<i64>,
+
+        /// Give up after this duration (e.g., 30s, 5m)
+        #[arg(long, default_value = "30s")]
+        timeout: String,
+
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show messages you have not read yet and mark them read
+    Inbox {
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+
+        /// Show unread messages without marking them read
+        #[arg(long)]
+        peek: bool,
+    },
+
+    /// Show messages addressed to you
+    Mentions {
+        /// Show messages since duration (e.g., 10m, 1h, 2d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show a message's whole thread, from its root down through every reply
     Message {
-        /// Message ID
+        /// Message ID (any message in the thread)
         id: i64,
+
+        /// Show replies as an indented tree
+        #[arg(long)]
+        tree: bool,
+
+        /// Only follow replies this many levels below the root
+        #[arg(long)]
+        depth: Option<usize>,
     },
 
     /// List artifacts
@@ -133,6 +246,10 @@ pub enum Commands {
         #[arg(long)]
         by: Option<String>,
 
+        /// Only the artifact at this directory and those below it
+        #[arg(long)]
+        under: Option<String>,
+
         /// Filter by reference
         #[arg(long)]
         ref_where: Option<String>,
@@ -143,34 +260,217 @@ pub enum Commands {
         #[arg(long)]
         ref_ref: Option<String>,
 
+        /// Compare each artifact with its file: unchanged, modified or deleted
+        #[arg(long)]
+        check: bool,
+
+        /// Only artifacts whose files changed since this git commit
+        #[arg(long, conflicts_with_all = ["check", "under", "ref_where", "ref_what", "ref_ref"])]
+        since_commit: Option<String>,
+
         /// Limit results
         #[arg(short, long, default_value = "50")]
         limit: usize,
     },
 
-    /// Add or update an artifact
+    /// Register, inspect and trace artifacts
+    Artifact {
+        #[command(subcommand)]
+        command: ArtifactCommands,
+    },
+
+    /// Same as `bb artifact add`
+    #[command(hide = true)]
     ArtifactAdd {
-        /// File path
         path: String,
-
-        /// Description
         description: String,
-
-        /// Version
         #[arg(long)]
         version: Option<String>,
-
-        /// References (repeatable)
         #[arg(long = "ref")]
         refs: Vec<String>,
     },
 
-    /// Show artifact details
-    ArtifactShow {
-        /// File path
+    /// Same as `bb artifact show`
+    #[command(hide = true)]
+    ArtifactShow { path: String },
+
+    /// Full-text search over messages and artifact descriptions
+    Search {
+        /// Words to find, all of them; a trailing * matches a prefix
+        query: String,
+
+        /// Read the query as FTS5 syntax ("phrases", prefix*, AND/OR/NOT)
+        #[arg(long)]
+        raw: bool,
+
+        /// Only match messages since duration (e.g., 10m, 1h, 2d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Filter by tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Filter by agent
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Filter by minimum priority
+        #[arg(long)]
+        priority: Option<Priority>,
+
+        /// Filter by reference (where:what:ref)
+        #[arg(long)]
+        ref_where: Option<String>,
+
+        #[arg(long)]
+        ref_what: Option<String>,
+
+        #[arg(long)]
+        ref_ref: Option<String>,
+
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Claim a path or glob before editing it
+    Claim {
+        /// File path or glob (e.g., src/auth.rs, src/db/**)
         path: String,
+
+        /// Allow other shared claims on the same path
+        #[arg(long)]
+        shared: bool,
+
+        /// How long the claim lasts (e.g., 30m, 2h)
+        #[arg(long, default_value = "30m")]
+        ttl: String,
+    },
+
+    /// Release a claim
+    Release {
+        /// File path or glob, as claimed
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        path: Option<String>,
+
+        /// Release all of your claims
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// List active claims
+    Claims {
+        /// Only show claims overlapping this path
+        path: Option<String>,
     },
 
+    /// Hand work over to another agent, who must accept or decline it
+    Handoff {
+        /// Agent to hand the work to
+        #[arg(long)]
+        to: String,
+
+        /// What is done, what is left, and where to start
+        #[arg(long)]
+        summary: String,
+
+        /// Attach references (where:what:ref, repeatable)
+        #[arg(long = "ref")]
+        refs: Vec<String>,
+
+        /// Registered artifact that goes with the work (repeatable)
+        #[arg(long = "artifact")]
+        artifacts: Vec<String>,
+
+        /// Set priority (default: messages.default_priority in config)
+        #[arg(long)]
+        priority: Option<Priority>,
+
+        /// Raise the priority if still pending after this long (e.g., 30m, 2h)
+        #[arg(long, default_value = "30m")]
+        escalate_after: String,
+    },
+
+    /// Accept a handoff addressed to you
+    Accept {
+        /// Handoff ID
+        id: i64,
+
+        /// Note for the sender
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Decline a handoff addressed to you
+    Decline {
+        /// Handoff ID
+        id: i64,
+
+        /// Why you are declining
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// List handoffs
+    Handoffs {
+        /// Only handoffs to this agent
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Only handoffs from this agent
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only handoffs in this state
+        #[arg(long)]
+        status: Option<HandoffStatus>,
+
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Accept a reply as the answer to your question, or close it
+    Answer {
+        /// Question message ID
+        id: i64,
+
+        /// Reply to accept as the answer
+        #[arg(long, required_unless_present = "close", conflicts_with = "close")]
+        accept: Option<i64>,
+
+        /// Close the question, answered or not
+        #[arg(long)]
+        close: bool,
+    },
+
+    /// List messages tagged `question`, oldest first
+    Questions {
+        /// Only unanswered questions (same as --state open)
+        #[arg(long, conflicts_with = "state")]
+        open: bool,
+
+        /// Only questions in this state
+        #[arg(long)]
+        state: Option<QuestionState>,
+
+        /// Only questions addressed to this agent
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Only questions from this agent
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Limit number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// List tags, their aliases and how often each is used
+    Tags,
+
     /// Find references
     Refs {
         /// Reference (where:what:ref)
@@ -196,6 +496,12 @@ pub enum Commands {
         confirm: bool,
     },
 
+    /// Show or change board settings in .bb/config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
     /// Export all data as JSON
     Export,
 
@@ -203,11 +509,31 @@ pub enum Commands {
     Summary,
 
     /// Run MCP server
+    ///
+    /// Settings are read when the server starts; restart it after `bb config set`.
     Mcp {
         /// Agent ID for MCP mode
         #[arg(long)]
         agent: Option<String>,
     },
+
+    /// Serve MCP over Streamable HTTP on localhost or a Unix socket
+    ///
+    /// Settings are read when the server starts; restart it after `bb config set`.
+    Serve {
+        /// Loopback address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+
+        /// Port to listen on (0 picks a free port)
+        #[arg(long, default_value_t = 7777)]
+        port: u16,
+
+        /// Listen on a Unix socket instead of a port
+        #[cfg(unix)]
+        #[arg(long, conflicts_with_all = ["host", "port"])]
+        socket: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -240,6 +566,121 @@ pub enum StatusCommands {
     Clear,
 }
 
+#[derive(Subcommand)]
+pub enum ArtifactCommands {
+    /// Add or update an artifact
+    Add {
+        /// File or directory path, or a glob such as 'src/auth/**'
+        #[arg(required_unless_present = "from_commit")]
+        path: Option<String>,
+
+        /// Description
+        #[arg(required_unless_present = "from_commit")]
+        description: Option<String>,
+
+        /// Version (default: the HEAD commit, marked -dirty if the file has
+        /// uncommitted changes)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// References (repeatable)
+        #[arg(long = "ref")]
+        refs: Vec<String>,
+
+        /// Register every file this commit added or changed, described by
+        /// its subject line
+        #[arg(long, conflicts_with_all = ["path", "description"])]
+        from_commit: Option<String>,
+
+        /// Registered artifact this one needs (repeatable; replaces earlier links)
+        #[arg(long, value_name = "PATH", conflicts_with = "from_commit")]
+        depends_on: Vec<String>,
+
+        /// Registered artifact this one was generated from (repeatable;
+        /// replaces earlier links)
+        #[arg(long, value_name = "PATH", conflicts_with = "from_commit")]
+        derived_from: Vec<String>,
+
+        /// Drop every link this artifact has
+        #[arg(long, conflicts_with_all = ["from_commit", "depends_on", "derived_from"])]
+        clear_links: bool,
+    },
+
+    /// Show artifact details, or the directory and glob artifacts a path falls under
+    Show {
+        /// File path
+        path: String,
+    },
+
+    /// Remove one artifact; its history is kept and records the removal
+    Remove {
+        /// Registered path
+        path: String,
+    },
+
+    /// Move an artifact to a new path, keeping its refs and history; the move
+    /// is recorded as a revision
+    Rename {
+        /// Registered path
+        old: String,
+
+        /// New path; must exist unless it is a glob
+        new: String,
+    },
+
+    /// Show every registration of an artifact, newest first
+    History {
+        /// File path
+        path: String,
+    },
+
+    /// Show the artifacts an artifact depends on or feeds, flagging those
+    /// whose upstream changed after they were registered
+    Graph {
+        /// Registered path
+        path: String,
+
+        /// Which links to follow
+        #[arg(long, value_enum, default_value = "both")]
+        direction: GraphDirection,
+
+        /// Follow at most this many links
+        #[arg(long)]
+        depth: Option<usize>,
+
+        /// Print Graphviz DOT
+        #[arg(long, conflicts_with = "mermaid")]
+        dot: bool,
+
+        /// Print a Mermaid flowchart
+        #[arg(long)]
+        mermaid: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show one setting (e.g., liveness.stale_minutes)
+    Get {
+        /// Dotted setting key
+        key: String,
+    },
+
+    /// Change one setting, keeping the rest of the file as it is
+    ///
+    /// A running `bb mcp` or `bb serve` keeps its settings until restarted.
+    Set {
+        /// Dotted setting key
+        key: String,
+
+        /// New value, as TOML (e.g., 30, true, '["todo", "done"]') or plain text
+        value: String,
+    },
+
+    /// Show every setting and its current value
+    List,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum InstallTool {
     Claude,
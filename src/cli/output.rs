@@ -1,7 +1,18 @@
+use crate::core::config;
 use crate::core::models::agent::{Agent, Liveness};
-use crate::core::models::artifact::Artifact;
-use crate::core::models::message::Message;
+use crate::core::models::artifact::{
+    Artifact, ArtifactCheck, ArtifactGraph, ArtifactLink, ArtifactRevision,
+};
+use crate::core::models::handoff::Handoff;
+use crate::core::models::lease::Lease;
+use crate::core::models::message::{Message, Thread};
+use crate::core::models::question::Question;
+use crate::core::operations::agent::AgentTimeline;
+use crate::core::operations::inbox::InboxPage;
+use crate::core::operations::message::WaitOutcome;
 use crate::core::operations::reference::ReferenceResults;
+use crate::core::operations::search::SearchResults;
+use crate::core::operations::tag::TagList;
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -158,6 +169,10 @@ impl OutputFormatter {
                 msg.id, priority_indicator, msg.from_agent, tags, time
             ));
 
+            if !msg.recipients.is_empty() {
+                lines.push(format!("  → To: {}", msg.recipients.join(", ")));
+            }
+
             // Format content with wrapping
             let content_lines = wrap_text(&msg.content, 80);
             for line in content_lines {
@@ -179,8 +194,124 @@ impl OutputFormatter {
         lines.join("\n")
     }
 
-    pub fn format_message_thread(&self, messages: &[Message]) -> String {
-        self.format_messages(messages)
+    pub fn format_wait_outcome(&self, outcome: &WaitOutcome) -> String {
+        match self.format {
+            OutputFormat::Human => {
+                if outcome.timed_out {
+                    "Timed out waiting for messages.\n".to_string()
+                } else {
+                    // format_messages_human expects the newest-first order of list queries
+                    let newest_first: Vec<_> = outcome.messages.iter().rev().cloned().collect();
+                    self.format_messages_human(&newest_first)
+                }
+            }
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(outcome).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    pub fn format_inbox(&self, page: &InboxPage) -> String {
+        match self.format {
+            OutputFormat::Human => {
+                if page.messages.is_empty() {
+                    return "No unread messages.\n".to_string();
+                }
+                let newest_first: Vec<_> = page.messages.iter().rev().cloned().collect();
+                let mut out = self.format_messages_human(&newest_first);
+                if page.remaining > 0 {
+                    out.push_str(&format!("{} more unread.\n", page.remaining));
+                }
+                out
+            }
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(page).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    /// Formats a thread as a flat message list, or with `tree` as an
+    /// indented reply tree.
+    pub fn format_message_thread(&self, thread: &Thread, tree: bool) -> String {
+        match self.format {
+            OutputFormat::Human => {
+                let mut out = if tree {
+                    self.format_thread_tree_human(thread)
+                } else {
+                    // format_messages_human expects the newest-first order of list queries
+                    let newest_first: Vec<_> = thread
+                        .messages
+                        .iter()
+                        .rev()
+                        .map(|t| t.message.clone())
+                        .collect();
+                    self.format_messages_human(&newest_first)
+                };
+                if thread.has_more {
+                    out.push_str(&format!(
+                        "Showing {} of {} messages in thread #{}.\n",
+                        thread.messages.len(),
+                        thread.total,
+                        thread.root_id
+                    ));
+                }
+                out
+            }
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(thread).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    fn format_thread_tree_human(&self, thread: &Thread) -> String {
+        let messages = &thread.messages;
+        let mut lines = Vec::new();
+        // For each depth above the current message, whether the branch
+        // there has more siblings to come (and so needs a vertical rule)
+        let mut open_branches: Vec<bool> = Vec::new();
+
+        for (i, entry) in messages.iter().enumerate() {
+            let depth = entry.depth;
+            let has_next_sibling = messages[i + 1..]
+                .iter()
+                .take_while(|t| t.depth >= depth)
+                .any(|t| t.depth == depth);
+            open_branches.truncate(depth);
+
+            let mut indent = String::new();
+            for &open in open_branches.iter().skip(1) {
+                indent.push_str(if open { "│  " } else { "   " });
+            }
+            let (branch, body) = match (depth, has_next_sibling) {
+                (0, _) => ("", "  "),
+                (_, true) => ("├─ ", "│    "),
+                (_, false) => ("└─ ", "     "),
+            };
+
+            let msg = &entry.message;
+            let tags = if msg.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", msg.tags.join(", "))
+            };
+            lines.push(format!(
+                "{indent}{branch}#{} {}{} ({})",
+                msg.id,
+                msg.from_agent,
+                tags,
+                format_timestamp_human(msg.created_at)
+            ));
+            for line in wrap_text(
+                &msg.content,
+                80usize.saturating_sub(indent.chars().count()).max(40),
+            ) {
+                lines.push(format!("{indent}{body}{line}"));
+            }
+
+            open_branches.push(has_next_sibling);
+        }
+
+        lines.join("\n") + "\n"
     }
 
     pub fn format_artifacts(&self, artifacts: &[Artifact]) -> String {
@@ -227,6 +358,32 @@ impl OutputFormatter {
                 lines.push(format!("  → Version: {version}"));
             }
 
+            if let Some(git) = &artifact.git {
+                let commit = git
+                    .last_commit
+                    .as_deref()
+                    .map_or("none", |sha| &sha[..sha.len().min(12)]);
+                let dirty = if git.dirty {
+                    " (uncommitted changes)"
+                } else {
+                    ""
+                };
+                lines.push(format!("  → Last commit: {commit}{dirty}"));
+            }
+
+            if !artifact.depends_on.is_empty() {
+                lines.push(format!(
+                    "  → Depends on: {}",
+                    artifact.depends_on.join(", ")
+                ));
+            }
+            if !artifact.derived_from.is_empty() {
+                lines.push(format!(
+                    "  → Derived from: {}",
+                    artifact.derived_from.join(", ")
+                ));
+            }
+
             if !artifact.refs.is_empty() {
                 let refs_str: Vec<_> = artifact
                     .refs
@@ -240,6 +397,371 @@ impl OutputFormatter {
         lines.join("\n") + "\n"
     }
 
+    pub fn format_artifact_checks(&self, checks: &[ArtifactCheck]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_artifact_checks_human(checks),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(checks).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_artifact_checks_human(&self, checks: &[ArtifactCheck]) -> String {
+        if checks.is_empty() {
+            return "No artifacts found.\n".to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{:<10} {:<40} {:<15} {:<10}",
+            "STATE", "PATH", "REGISTERED BY", "REGISTERED"
+        )];
+        lines.push("-".repeat(80));
+
+        for check in checks {
+            let artifact = &check.artifact;
+            let path = if artifact.path.len() > 37 {
+                format!("...{}", &artifact.path[artifact.path.len() - 34..])
+            } else {
+                artifact.path.clone()
+            };
+
+            lines.push(format!(
+                "{:<10} {:<40} {:<15} {:<10}",
+                check.state.as_str(),
+                path,
+                truncate(&artifact.produced_by, 15),
+                format_timestamp_human(artifact.updated_at)
+            ));
+
+            if !check.changed_upstream.is_empty() {
+                lines.push(format!(
+                    "  → Upstream changed: {}",
+                    check.changed_upstream.join(", ")
+                ));
+            }
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_artifact_graph(&self, graph: &ArtifactGraph) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_artifact_graph_human(graph),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(graph).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    fn format_artifact_graph_human(&self, graph: &ArtifactGraph) -> String {
+        let mut lines = vec![format!(
+            "{:<40} {:<15} {:<10}",
+            "PATH", "REGISTERED BY", "REGISTERED"
+        )];
+        lines.push("-".repeat(70));
+
+        for node in &graph.nodes {
+            let path = if node.path.len() > 37 {
+                format!("...{}", &node.path[node.path.len() - 34..])
+            } else {
+                node.path.clone()
+            };
+            lines.push(format!(
+                "{:<40} {:<15} {:<10}",
+                path,
+                truncate(&node.produced_by, 15),
+                format_timestamp_human(node.updated_at)
+            ));
+            for edge in graph.edges.iter().filter(|e| e.from == node.path) {
+                let link = match edge.link {
+                    ArtifactLink::DependsOn => "Depends on",
+                    ArtifactLink::DerivedFrom => "Derived from",
+                };
+                let changed = if node.changed_upstream.contains(&edge.to) {
+                    " (changed since)"
+                } else {
+                    ""
+                };
+                lines.push(format!("  → {link}: {}{changed}", edge.to));
+            }
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Formats the revisions of the artifact now at `path`.
+    pub fn format_artifact_history(&self, path: &str, revisions: &[ArtifactRevision]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_artifact_history_human(path, revisions),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(revisions).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_artifact_history_human(&self, path: &str, revisions: &[ArtifactRevision]) -> String {
+        if revisions.is_empty() {
+            return "No revisions found.\n".to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{:<10} {:<15} {:<15} {:<30}",
+            "WHEN", "REGISTERED BY", "CONTENT", "DESCRIPTION"
+        )];
+        lines.push("-".repeat(80));
+
+        for revision in revisions {
+            let content = revision
+                .content_hash
+                .as_deref()
+                .map(|hash| {
+                    let hex = hash.strip_prefix("sha256:").unwrap_or(hash);
+                    hex.chars().take(12).collect()
+                })
+                .unwrap_or_else(|| "-".to_string());
+
+            lines.push(format!(
+                "{:<10} {:<15} {:<15} {:<30}",
+                format_timestamp_human(revision.registered_at),
+                truncate(&revision.produced_by, 15),
+                content,
+                truncate(&revision.description, 30)
+            ));
+
+            if revision.removed {
+                lines.push("  → Removed".to_string());
+            } else if let Some(from) = &revision.renamed_from {
+                lines.push(format!("  → Renamed from: {from}"));
+            } else if let Some(version) = &revision.version {
+                lines.push(format!("  → Version: {version}"));
+            }
+            if !revision.depends_on.is_empty() {
+                lines.push(format!(
+                    "  → Depends on: {}",
+                    revision.depends_on.join(", ")
+                ));
+            }
+            if !revision.derived_from.is_empty() {
+                lines.push(format!(
+                    "  → Derived from: {}",
+                    revision.derived_from.join(", ")
+                ));
+            }
+            if revision.path != path {
+                lines.push(format!("  → Path: {}", revision.path));
+            }
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_leases(&self, leases: &[Lease]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_leases_human(leases),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(leases).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_leases_human(&self, leases: &[Lease]) -> String {
+        if leases.is_empty() {
+            return "No active claims.\n".to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{:<40} {:<15} {:<10} {:<15}",
+            "PATH", "HOLDER", "MODE", "EXPIRES"
+        )];
+        lines.push("-".repeat(80));
+
+        for lease in leases {
+            let path = if lease.path.len() > 37 {
+                format!("...{}", &lease.path[lease.path.len() - 34..])
+            } else {
+                lease.path.clone()
+            };
+
+            lines.push(format!(
+                "{:<40} {:<15} {:<10} {:<15}",
+                path,
+                truncate(&lease.holder, 15),
+                lease.mode.as_str(),
+                format_expiry_human(lease.expires_at)
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_handoffs(&self, handoffs: &[Handoff]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_handoffs_human(handoffs),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(handoffs).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_handoffs_human(&self, handoffs: &[Handoff]) -> String {
+        if handoffs.is_empty() {
+            return "No handoffs.\n".to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{:<6} {:<15} {:<15} {:<10} {:<10} {:<10} SUMMARY",
+            "ID", "FROM", "TO", "STATUS", "PRIORITY", "CREATED"
+        )];
+        lines.push("-".repeat(100));
+
+        for handoff in handoffs {
+            lines.push(format!(
+                "{:<6} {:<15} {:<15} {:<10} {:<10} {:<10} {}",
+                format!("#{}", handoff.id),
+                truncate(&handoff.from_agent, 15),
+                truncate(&handoff.to_agent, 15),
+                handoff.status.as_str(),
+                handoff.priority.as_str(),
+                format_timestamp_human(handoff.created_at),
+                truncate(&handoff.summary, 40)
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_tags(&self, list: &TagList) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_tags_human(list),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(list).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    fn format_tags_human(&self, list: &TagList) -> String {
+        let mut lines = vec![format!(
+            "{:<15} {:>6} {:<10} {:<25} DESCRIPTION",
+            "TAG", "USES", "SOURCE", "ALIASES"
+        )];
+        lines.push("-".repeat(100));
+
+        for tag in &list.tags {
+            lines.push(format!(
+                "{:<15} {:>6} {:<10} {:<25} {}",
+                truncate(&tag.name, 15),
+                tag.count,
+                tag.source.as_str(),
+                truncate(&tag.aliases.join(", "), 25),
+                truncate(&tag.description, 40)
+            ));
+        }
+
+        if list.strict {
+            lines.push(String::new());
+            lines.push("Strict: only defined tags and their aliases can be posted.".to_string());
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_questions(&self, questions: &[Question]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_questions_human(questions),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(questions).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_questions_human(&self, questions: &[Question]) -> String {
+        if questions.is_empty() {
+            return "No questions.\n".to_string();
+        }
+
+        let mut lines = vec![format!(
+            "{:<6} {:<10} {:<15} {:<15} {:<12} {:<8} QUESTION",
+            "ID", "ASKED", "FROM", "TO", "STATE", "REPLIES"
+        )];
+        lines.push("-".repeat(100));
+
+        for question in questions {
+            let message = &question.message;
+            let to = if message.recipients.is_empty() {
+                "-".to_string()
+            } else {
+                message.recipients.join(",")
+            };
+            let state = match question.answer_id {
+                Some(id) => format!("{} #{id}", question.state.as_str()),
+                None => question.state.as_str().to_string(),
+            };
+            lines.push(format!(
+                "{:<6} {:<10} {:<15} {:<15} {:<12} {:<8} {}",
+                format!("#{}", message.id),
+                format_timestamp_human(message.created_at),
+                truncate(&message.from_agent, 15),
+                truncate(&to, 15),
+                state,
+                question.replies,
+                truncate(&message.content, 40)
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    pub fn format_timeline(&self, timelines: &[AgentTimeline]) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_timeline_human(timelines),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(timelines).unwrap_or_else(|_| "[]".to_string())
+            }
+        }
+    }
+
+    fn format_timeline_human(&self, timelines: &[AgentTimeline]) -> String {
+        if timelines.is_empty() {
+            return "No status history.\n".to_string();
+        }
+
+        let mut lines = Vec::new();
+        for timeline in timelines {
+            lines.push(timeline.agent_id.clone());
+            for entry in &timeline.entries {
+                let event = &entry.event;
+                let mut task = event.current_task.clone();
+                if let Some(blockers) = &event.blockers {
+                    task = format!("{task} (blocked: {blockers})");
+                }
+                let lasted = match entry.ended_at {
+                    Some(_) => format_duration_human(entry.duration_secs),
+                    None => format!("{} so far", format_duration_human(entry.duration_secs)),
+                };
+                lines.push(format!(
+                    "  {:<10} {:<10} {:>4}  {:<12} {}",
+                    format_timestamp_human(event.created_at),
+                    event.status.as_str(),
+                    format!("{}%", event.progress),
+                    lasted,
+                    truncate(&task, 50)
+                ));
+            }
+            let totals: Vec<_> = timeline
+                .time_in_status
+                .iter()
+                .filter(|(_, secs)| **secs > 0)
+                .map(|(status, secs)| format!("{status} {}", format_duration_human(*secs)))
+                .collect();
+            if !totals.is_empty() {
+                lines.push(format!("  Time: {}", totals.join(", ")));
+            }
+            lines.push(String::new());
+        }
+
+        lines.join("\n")
+    }
+
     pub fn format_summary(&self, summary: &SummaryData) -> String {
         match self.format {
             OutputFormat::Human => self.format_summary_human(summary),
@@ -309,6 +831,47 @@ impl OutputFormatter {
             lines.push(String::new());
         }
 
+        // Pending handoffs, grouped by recipient
+        if !summary.pending_handoffs.is_empty() {
+            lines.push(format!(
+                "Pending Handoffs: {}",
+                summary.pending_handoffs.len()
+            ));
+            let mut recipients: Vec<&str> = summary
+                .pending_handoffs
+                .iter()
+                .map(|h| h.to_agent.as_str())
+                .collect();
+            recipients.sort_unstable();
+            recipients.dedup();
+            for recipient in recipients {
+                lines.push(format!("  {recipient}:"));
+                for handoff in summary
+                    .pending_handoffs
+                    .iter()
+                    .filter(|h| h.to_agent == recipient)
+                {
+                    lines.push(format!(
+                        "    #{} from {} [{}]: {}",
+                        handoff.id,
+                        handoff.from_agent,
+                        handoff.priority.as_str(),
+                        truncate(&handoff.summary, 40)
+                    ));
+                }
+            }
+            lines.push(String::new());
+        }
+
+        if summary.stale_questions > 0 {
+            lines.push(format!(
+                "Unanswered Questions (open over {}m): {} (see `bb questions --open`)",
+                config::current().questions.stale_minutes,
+                summary.stale_questions
+            ));
+            lines.push(String::new());
+        }
+
         // Recent artifacts
         lines.push(format!(
             "Recent Artifacts (last hour): {}",
@@ -355,6 +918,46 @@ impl OutputFormatter {
 
         lines.join("\n") + "\n"
     }
+
+    pub fn format_search_results(&self, results: &SearchResults) -> String {
+        match self.format {
+            OutputFormat::Human => self.format_search_results_human(results),
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(results).unwrap_or_else(|_| "{}".to_string())
+            }
+        }
+    }
+
+    fn format_search_results_human(&self, results: &SearchResults) -> String {
+        if results.messages.is_empty() && results.artifacts.is_empty() {
+            return "No matches found.\n".to_string();
+        }
+
+        let mut lines = Vec::new();
+
+        lines.push(format!("Messages: {}", results.messages.len()));
+        for hit in &results.messages {
+            lines.push(format!(
+                "  #{} {} ({})",
+                hit.message.id,
+                hit.message.from_agent,
+                format_timestamp_human(hit.message.created_at)
+            ));
+            lines.push(format!("    {}", hit.snippet.replace('\n', " ")));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Artifacts: {}", results.artifacts.len()));
+        for hit in &results.artifacts {
+            lines.push(format!(
+                "  • {} by {}",
+                hit.artifact.path, hit.artifact.produced_by
+            ));
+            lines.push(format!("    {}", hit.snippet.replace('\n', " ")));
+        }
+
+        lines.join("\n") + "\n"
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -364,6 +967,76 @@ pub struct SummaryData {
     pub recent_messages: Vec<Message>,
     pub high_priority_messages: Vec<Message>,
     pub recent_artifacts: Vec<Artifact>,
+    pub pending_handoffs: Vec<Handoff>,
+    /// Questions open for longer than `questions.stale_minutes`.
+    pub stale_questions: i64,
+}
+
+/// Renders `graph` in Graphviz DOT. Edges point from upstream to downstream,
+/// the root is bold and artifacts with a changed upstream are red.
+pub fn format_graph_dot(graph: &ArtifactGraph) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut lines = vec![
+        "digraph artifacts {".to_string(),
+        "  rankdir=LR;".to_string(),
+    ];
+    for node in &graph.nodes {
+        let mut attrs = Vec::new();
+        if node.path == graph.root {
+            attrs.push("style=bold");
+        }
+        if !node.changed_upstream.is_empty() {
+            attrs.push("color=red");
+        }
+        if attrs.is_empty() {
+            lines.push(format!("  {};", quote(&node.path)));
+        } else {
+            lines.push(format!("  {} [{}];", quote(&node.path), attrs.join(", ")));
+        }
+    }
+    for edge in &graph.edges {
+        lines.push(format!(
+            "  {} -> {} [label={}];",
+            quote(&edge.to),
+            quote(&edge.from),
+            quote(edge.link.as_str())
+        ));
+    }
+    lines.push("}".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// Renders `graph` as a Mermaid flowchart, laid out like `format_graph_dot`.
+pub fn format_graph_mermaid(graph: &ArtifactGraph) -> String {
+    let id = |path: &str| {
+        let index = graph.nodes.iter().position(|n| n.path == path);
+        format!("n{}", index.unwrap_or(graph.nodes.len()))
+    };
+    let mut lines = vec!["flowchart LR".to_string()];
+    for node in &graph.nodes {
+        lines.push(format!(
+            "  {}[\"{}\"]",
+            id(&node.path),
+            node.path.replace('"', "#quot;")
+        ));
+    }
+    for edge in &graph.edges {
+        lines.push(format!(
+            "  {} -->|{}| {}",
+            id(&edge.to),
+            edge.link.as_str(),
+            id(&edge.from)
+        ));
+    }
+    lines.push(format!("  style {} stroke-width:3px", id(&graph.root)));
+    for node in graph
+        .nodes
+        .iter()
+        .filter(|n| !n.changed_upstream.is_empty())
+    {
+        lines.push(format!("  style {} stroke:#d00", id(&node.path)));
+    }
+    lines.join("\n") + "\n"
 }
 
 fn format_timestamp_human(dt: chrono::DateTime<chrono::Utc>) -> String {
@@ -381,9 +1054,40 @@ fn format_timestamp_human(dt: chrono::DateTime<chrono::Utc>) -> String {
     }
 }
 
+/// Formats a span of seconds as e.g. "45s", "25m" or "1h 5m".
+fn format_duration_human(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+    } else {
+        format!("{}d {}h", secs / 86400, secs % 86400 / 3600)
+    }
+}
+
+fn format_expiry_human(dt: chrono::DateTime<chrono::Utc>) -> String {
+    let remaining = dt.signed_duration_since(chrono::Utc::now());
+
+    if remaining.num_minutes() < 1 {
+        "in <1m".to_string()
+    } else if remaining.num_minutes() < 60 {
+        format!("in {}m", remaining.num_minutes())
+    } else {
+        format!("in {}h", remaining.num_hours())
+    }
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
+        // Cut on a char boundary so multi-byte text cannot panic
+        let mut end = max_len - 3;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &s[..end])
     } else {
         s.to_string()
     }
@@ -433,6 +1137,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_duration_human() {
+        assert_eq!(format_duration_human(45), "45s");
+        assert_eq!(format_duration_human(25 * 60), "25m");
+        assert_eq!(format_duration_human(3900), "1h 5m");
+        assert_eq!(format_duration_human(90000), "1d 1h");
+    }
+
+    #[test]
+    fn test_format_graph_dot_and_mermaid() {
+        use crate::core::models::artifact::{ArtifactEdge, GraphNode};
+
+        let node = |path: &str, changed: &[&str]| GraphNode {
+            path: path.to_string(),
+            produced_by: "agent".to_string(),
+            updated_at: chrono::Utc::now(),
+            changed_upstream: changed.iter().map(|p| p.to_string()).collect(),
+        };
+        let graph = ArtifactGraph {
+            root: "gen/api.rs".to_string(),
+            nodes: vec![
+                node("gen/api.rs", &["schema \"v2\".sql"]),
+                node("schema \"v2\".sql", &[]),
+            ],
+            edges: vec![ArtifactEdge {
+                from: "gen/api.rs".to_string(),
+                to: "schema \"v2\".sql".to_string(),
+                link: ArtifactLink::DerivedFrom,
+            }],
+        };
+
+        let dot = format_graph_dot(&graph);
+        assert!(dot.starts_with("digraph artifacts {\n"));
+        assert!(dot.contains("  \"gen/api.rs\" [style=bold, color=red];\n"));
+        assert!(
+            dot.contains(
+                "  \"schema \\\"v2\\\".sql\" -> \"gen/api.rs\" [label=\"derived_from\"];\n"
+            )
+        );
+
+        let mermaid = format_graph_mermaid(&graph);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("  n1[\"schema #quot;v2#quot;.sql\"]\n"));
+        assert!(mermaid.contains("  n1 -->|derived_from| n0\n"));
+        assert!(mermaid.contains("  style n0 stroke:#d00\n"));
+    }
+
     #[test]
     fn test_truncate() {
         assert_eq!(truncate("hello", 10), "hello");
@@ -474,4 +1225,23 @@ mod tests {
                 || output.contains("\"liveness\": \"active\"")
         );
     }
+
+    #[test]
+    fn test_output_formatter_messages_show_recipients() {
+        let formatter = OutputFormatter::new(OutputFormat::Human);
+        let message = Message {
+            id: 1,
+            from_agent: "agent-1".to_string(),
+            recipients: vec!["agent-2".to_string(), "agent-3".to_string()],
+            content: "@agent-2 ready for review".to_string(),
+            tags: vec![],
+            priority: crate::core::models::message::Priority::Normal,
+            in_reply_to: None,
+            refs: vec![],
+            created_at: chrono::Utc::now(),
+        };
+
+        let output = formatter.format_messages(&[message]);
+        assert!(output.contains("→ To: agent-2, agent-3"));
+    }
 }
@@ -0,0 +1,573 @@
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::message::Priority;
+use crate::core::operations::tag::TagRegistry;
+use crate::core::validation::duration::validate_duration;
+use crate::core::validation::limits::Limits;
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static CURRENT: OnceLock<Config> = OnceLock::new();
+
+/// Makes `config` the one `current` returns for the rest of the process.
+/// `bb` calls this once at startup; later calls have no effect.
+pub fn install(config: Config) {
+    let _ = CURRENT.set(config);
+}
+
+/// The config installed at startup, or the defaults if there is none.
+pub fn current() -> &'static Config {
+    CURRENT.get_or_init(Config::default)
+}
+
+/// Config files `bb init --template <name>` can start a board with.
+pub const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "default",
+        include_str!("../../templates/config/default.toml"),
+    ),
+    ("team", include_str!("../../templates/config/team.toml")),
+    ("ci", include_str!("../../templates/config/ci.toml")),
+];
+
+/// Per-board settings, read from `.bb/config.toml`. A missing file, section
+/// or key means the default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub liveness: LivenessConfig,
+    pub log: LogConfig,
+    pub limits: Limits,
+    pub tags: TagsConfig,
+    pub messages: MessagesConfig,
+    pub questions: QuestionsConfig,
+    pub retention: RetentionConfig,
+    pub display: DisplayConfig,
+}
+
+impl Config {
+    pub fn path(project_dir: &Path) -> PathBuf {
+        project_dir.join(".bb").join("config.toml")
+    }
+
+    /// Reads and validates the board's config, or the defaults if it has
+    /// none.
+    pub fn load(project_dir: &Path) -> BBResult<Self> {
+        let path = Self::path(project_dir);
+        let invalid = |reason: String| BBError::InvalidConfig {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        match read_config_file(&path)? {
+            Some(text) => Self::parse(&text).map_err(invalid),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Like `load`, but an unreadable or invalid config only warns and
+    /// yields the defaults. For commands that repair or remove a board,
+    /// which must work whatever state its config is in.
+    pub fn load_or_default(project_dir: &Path) -> Self {
+        Self::load(project_dir).unwrap_or_else(|e| {
+            eprintln!("Warning: {e}; using the default settings");
+            Self::default()
+        })
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.liveness.default_thresholds().validate("liveness")?;
+        for agent_id in self.liveness.agents.keys() {
+            self.liveness
+                .thresholds_for(agent_id)
+                .validate(&format!("liveness.agents.{agent_id}"))?;
+        }
+        if self.log.default_limit == 0 {
+            return Err("log.default_limit must be positive".to_string());
+        }
+        self.limits.validate()?;
+        TagRegistry::new(&self.tags)?;
+        if self.questions.stale_minutes <= 0 {
+            return Err("questions.stale_minutes must be positive".to_string());
+        }
+        self.retention.validate()?;
+        self.display.offset().map(|_| ())
+    }
+
+    /// Every setting that has a value, as `(dotted.key, value)` pairs.
+    pub fn entries(&self) -> Vec<(String, toml::Value)> {
+        let mut entries = Vec::new();
+        if let Ok(table) = toml::Table::try_from(self) {
+            flatten("", table, &mut entries);
+        }
+        entries
+    }
+
+    /// The value of one setting, by dotted key (e.g. `liveness.stale_minutes`).
+    pub fn get(&self, key: &str) -> BBResult<toml::Value> {
+        self.entries()
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+            .ok_or_else(|| BBError::NotFound(format!("no value for config key {key}")))
+    }
+
+    /// Sets `key` in the board's config file and returns the resulting
+    /// config. The rest of the file, comments included, is kept as it is.
+    /// `value` is read as TOML (`30`, `true`, `["a", "b"]`), or as a string
+    /// if it is not valid TOML. Nothing is written unless the result is a
+    /// valid config.
+    pub fn set(project_dir: &Path, key: &str, value: &str) -> BBResult<Self> {
+        let path = Self::path(project_dir);
+        let text = read_config_file(&path)?.unwrap_or_default();
+        let mut doc: toml_edit::DocumentMut =
+            text.parse()
+                .map_err(|e: toml_edit::TomlError| BBError::InvalidConfig {
+                    path: path.display().to_string(),
+                    reason: e.message().to_string(),
+                })?;
+
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.iter().any(|p| p.is_empty()) {
+            return Err(BBError::invalid_field(
+                "key",
+                format!("invalid config key: {key}"),
+            ));
+        }
+        let (name, sections) = parts.split_last().expect("split yields at least one part");
+
+        let mut table: &mut dyn toml_edit::TableLike = doc.as_table_mut();
+        for section in sections {
+            let mut implicit = toml_edit::Table::new();
+            implicit.set_implicit(true);
+            table = table
+                .entry(section)
+                .or_insert(toml_edit::Item::Table(implicit))
+                .as_table_like_mut()
+                .ok_or_else(|| {
+                    BBError::invalid_field("key", format!("{section} in {key} is not a section"))
+                })?;
+        }
+        let value = value
+            .parse::<toml_edit::Value>()
+            .unwrap_or_else(|_| value.into());
+        table.insert(name, toml_edit::value(value));
+
+        let text = doc.to_string();
+        let config = Self::parse(&text).map_err(|reason| BBError::invalid_field(key, reason))?;
+        std::fs::write(&path, text)?;
+        Ok(config)
+    }
+
+    /// The text of the named template, if there is one.
+    pub fn template(name: &str) -> Option<&'static str> {
+        TEMPLATES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, text)| *text)
+    }
+}
+
+fn read_config_file(path: &Path) -> BBResult<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn flatten(prefix: &str, table: toml::Table, entries: &mut Vec<(String, toml::Value)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}.{name}")
+        };
+        match value {
+            toml::Value::Table(inner) => flatten(&key, inner, entries),
+            value => entries.push((key, value)),
+        }
+    }
+}
+
+/// Minutes since an agent was last seen before it counts as stale, then
+/// offline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessThresholds {
+    pub active_minutes: i64,
+    pub stale_minutes: i64,
+}
+
+impl LivenessThresholds {
+    fn validate(&self, section: &str) -> Result<(), String> {
+        if self.active_minutes < 0 {
+            return Err(format!("{section}.active_minutes must not be negative"));
+        }
+        if self.stale_minutes < self.active_minutes {
+            return Err(format!(
+                "{section}.stale_minutes ({}) must be at least active_minutes ({})",
+                self.stale_minutes, self.active_minutes
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The `[liveness]` section: board-wide thresholds, and overrides for
+/// agents that check in more or less often than the rest, e.g.
+///
+/// ```toml
+/// [liveness]
+/// active_minutes = 5
+/// stale_minutes = 30
+///
+/// [liveness.agents.nightly-builder]
+/// stale_minutes = 240
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LivenessConfig {
+    pub active_minutes: i64,
+    pub stale_minutes: i64,
+    pub agents: BTreeMap<String, LivenessOverride>,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            active_minutes: 5,
+            stale_minutes: 30,
+            agents: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LivenessOverride {
+    pub active_minutes: Option<i64>,
+    pub stale_minutes: Option<i64>,
+}
+
+impl LivenessConfig {
+    pub fn default_thresholds(&self) -> LivenessThresholds {
+        LivenessThresholds {
+            active_minutes: self.active_minutes,
+            stale_minutes: self.stale_minutes,
+        }
+    }
+
+    /// The thresholds that apply to `agent_id`: its override where it has
+    /// one, the board's otherwise.
+    pub fn thresholds_for(&self, agent_id: &str) -> LivenessThresholds {
+        let defaults = self.default_thresholds();
+        match self.agents.get(agent_id) {
+            Some(o) => LivenessThresholds {
+                active_minutes: o.active_minutes.unwrap_or(defaults.active_minutes),
+                stale_minutes: o.stale_minutes.unwrap_or(defaults.stale_minutes),
+            },
+            None => defaults,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// How many messages `bb log` and `read_messages` show without a limit.
+    pub default_limit: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self { default_limit: 20 }
+    }
+}
+
+/// The `[tags]` section: tags this board uses on top of the standard ones,
+/// and whether any other tag is allowed, e.g.
+///
+/// ```toml
+/// [tags]
+/// strict = true
+///
+/// [tags.defined.deploy]
+/// description = "A release went out"
+/// aliases = ["release", "shipped"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagsConfig {
+    /// Reject tags that are neither defined nor an alias of one.
+    pub strict: bool,
+    /// Board tags by name. A standard tag defined here is replaced.
+    pub defined: BTreeMap<String, TagDefinition>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TagDefinition {
+    pub description: String,
+    /// Other spellings (e.g. `blocked` for `blocker`). Messages posted
+    /// with one are stored with the tag itself.
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MessagesConfig {
+    /// Priority of messages and handoffs posted without one.
+    pub default_priority: Priority,
+}
+
+impl Default for MessagesConfig {
+    fn default() -> Self {
+        Self {
+            default_priority: Priority::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuestionsConfig {
+    /// Minutes a question may stay open before `bb summary` counts it as
+    /// unanswered.
+    pub stale_minutes: i64,
+}
+
+impl Default for QuestionsConfig {
+    fn default() -> Self {
+        Self { stale_minutes: 60 }
+    }
+}
+
+/// How long the board keeps history, as durations like `"90d"`. Unset keeps
+/// it forever.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Threads whose newest message is older than this are deleted.
+    pub messages: Option<String>,
+    /// Status changes older than this are deleted, except each agent's
+    /// latest.
+    pub status_events: Option<String>,
+}
+
+impl RetentionConfig {
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("messages", &self.messages),
+            ("status_events", &self.status_events),
+        ] {
+            if let Some(value) = value {
+                let keep =
+                    validate_duration(value).map_err(|e| format!("retention.{name}: {e}"))?;
+                if keep <= chrono::Duration::zero() {
+                    return Err(format!("retention.{name} must be positive"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Messages in threads last active before this time may be deleted.
+    pub fn messages_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        cutoff(self.messages.as_deref(), now)
+    }
+
+    /// Status events before this time may be deleted.
+    pub fn status_events_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        cutoff(self.status_events.as_deref(), now)
+    }
+}
+
+fn cutoff(keep: Option<&str>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // Checked by `RetentionConfig::validate` when the config was loaded
+    keep.and_then(|k| validate_duration(k).ok())
+        .map(|keep| now - keep)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Timezone for times shown to people: `"utc"`, `"local"` or an offset
+    /// such as `"+02:00"`. Stored times and JSON output are always UTC.
+    pub timezone: String,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            timezone: "utc".to_string(),
+        }
+    }
+}
+
+impl DisplayConfig {
+    /// The fixed offset to show times in, or `None` for the system's local
+    /// time.
+    fn offset(&self) -> Result<Option<FixedOffset>, String> {
+        match self.timezone.to_lowercase().as_str() {
+            "utc" => Ok(Some(FixedOffset::east_opt(0).expect("zero offset is valid"))),
+            "local" => Ok(None),
+            other => other.parse::<FixedOffset>().map(Some).map_err(|_| {
+                format!(
+                    "display.timezone must be \"utc\", \"local\" or an offset like \"+02:00\", not {:?}",
+                    self.timezone
+                )
+            }),
+        }
+    }
+
+    /// Formats `dt` in the display timezone, e.g. "2026-03-01 14:05:09 UTC"
+    /// or "2026-03-01 16:05:09 +02:00".
+    pub fn format_datetime(&self, dt: DateTime<Utc>) -> String {
+        let local = match self.offset() {
+            Ok(Some(offset)) => dt.with_timezone(&offset),
+            _ => dt.with_timezone(&Local).fixed_offset(),
+        };
+        if self.timezone.eq_ignore_ascii_case("utc") {
+            local.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+        } else {
+            local.format("%Y-%m-%d %H:%M:%S %:z").to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_config(text: &str) -> TempDir {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join(".bb")).unwrap();
+        std::fs::write(Config::path(temp.path()), text).unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(Config::load(temp.path()).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_agent_overrides() {
+        let temp = write_config(
+            "[liveness]\nstale_minutes = 60\n\n[liveness.agents.builder]\nstale_minutes = 240\n",
+        );
+        let liveness = Config::load(temp.path()).unwrap().liveness;
+
+        assert_eq!(
+            liveness.thresholds_for("agent-1"),
+            LivenessThresholds {
+                active_minutes: 5,
+                stale_minutes: 60
+            }
+        );
+        assert_eq!(liveness.thresholds_for("builder").stale_minutes, 240);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        for text in [
+            "[liveness]\nactive_minutes = 40\n",
+            "[liveness.agents.builder]\nstale_minutes = 1\n",
+            "[liveness]\nstale_minute = 60\n",
+            "liveness = 3",
+            "[limits]\nmax_tag_len = 0\n",
+            "[tags.defined.\"two words\"]\n",
+            "[tags.defined.stuck]\naliases = [\"blocked\"]\n",
+            "[messages]\ndefault_priority = \"urgent\"\n",
+            "[questions]\nstale_minutes = 0\n",
+            "[retention]\nmessages = \"forever\"\n",
+            "[display]\ntimezone = \"Mars/Olympus\"\n",
+        ] {
+            let temp = write_config(text);
+            let err = Config::load(temp.path()).unwrap_err();
+            assert!(matches!(err, BBError::InvalidConfig { .. }), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_templates_are_valid() {
+        for (name, text) in TEMPLATES {
+            assert!(Config::parse(text).is_ok(), "template {name}");
+        }
+    }
+
+    #[test]
+    fn test_set_keeps_comments_and_validates() {
+        let temp = write_config("# Our board\n[liveness]\nstale_minutes = 60 # slow agents\n");
+
+        let config = Config::set(temp.path(), "log.default_limit", "50").unwrap();
+        assert_eq!(config.log.default_limit, 50);
+        Config::set(temp.path(), "liveness.agents.builder.stale_minutes", "240").unwrap();
+        Config::set(temp.path(), "display.timezone", "local").unwrap();
+        Config::set(temp.path(), "tags.strict", "true").unwrap();
+
+        let text = std::fs::read_to_string(Config::path(temp.path())).unwrap();
+        assert!(text.contains("# Our board"));
+        assert!(text.contains("stale_minutes = 60 # slow agents"));
+
+        let config = Config::load(temp.path()).unwrap();
+        assert_eq!(config.liveness.thresholds_for("builder").stale_minutes, 240);
+        assert_eq!(config.display.timezone, "local");
+        assert!(config.tags.strict);
+
+        for (key, value) in [
+            ("log.default_limit", "many"),
+            ("log.default_limt", "50"),
+            ("liveness.active_minutes", "90"),
+            ("log", "1"),
+        ] {
+            let err = Config::set(temp.path(), key, value).unwrap_err();
+            assert!(
+                matches!(&err, BBError::InvalidField { field, .. } if field == key),
+                "{key}: {err:?}"
+            );
+        }
+        assert_eq!(Config::load(temp.path()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_get_and_entries() {
+        let config = Config::default();
+        assert_eq!(
+            config.get("liveness.stale_minutes").unwrap(),
+            toml::Value::Integer(30)
+        );
+        assert!(config.get("retention.messages").is_err());
+        assert!(
+            config
+                .entries()
+                .iter()
+                .any(|(k, _)| k == "limits.max_tag_len")
+        );
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let dt = DateTime::parse_from_rfc3339("2026-03-01T14:05:09Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            DisplayConfig::default().format_datetime(dt),
+            "2026-03-01 14:05:09 UTC"
+        );
+        let display = DisplayConfig {
+            timezone: "+02:00".to_string(),
+        };
+        assert_eq!(display.format_datetime(dt), "2026-03-01 16:05:09 +02:00");
+    }
+}
@@ -11,6 +11,10 @@ pub enum BBError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    /// Invalid input that can be pinned on one named parameter.
+    #[error("Invalid input: {reason}")]
+    InvalidField { field: String, reason: String },
+
     #[error("Invalid ref format '{0}': expected where:what:ref")]
     InvalidRefFormat(String),
 
@@ -23,6 +27,24 @@ pub enum BBError {
     #[error("Identity required. Configure --agent, set BB_AGENT_ID, or call bb_identify.")]
     IdentityRequired,
 
+    #[error(
+        "Conflicts with claim on '{path}' held by {holder} until {}",
+        expires_at.format("%Y-%m-%d %H:%M:%S UTC")
+    )]
+    LeaseConflict {
+        path: String,
+        holder: String,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    },
+
+    #[error(
+        "Database schema version {found} is newer than this bb supports ({supported}). Upgrade bb."
+    )]
+    SchemaTooNew { found: i64, supported: i64 },
+
+    #[error("Invalid config {path}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -33,4 +55,62 @@ pub enum BBError {
     JsonError(#[from] serde_json::Error),
 }
 
+impl BBError {
+    pub fn invalid_field(field: &str, reason: impl Into<String>) -> Self {
+        Self::InvalidField {
+            field: field.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Stable machine-readable code, as returned in the MCP error envelope.
+    /// Codes are part of the public interface: never rename one.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotInitialized => "NOT_INITIALIZED",
+            Self::DatabaseBusy => "DATABASE_BUSY",
+            Self::InvalidInput(_) | Self::InvalidField { .. } => "INVALID_INPUT",
+            Self::InvalidRefFormat(_) => "INVALID_REF_FORMAT",
+            Self::PathTraversal(_) => "PATH_TRAVERSAL",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::IdentityRequired => "IDENTITY_REQUIRED",
+            Self::LeaseConflict { .. } => "LEASE_CONFLICT",
+            Self::SchemaTooNew { .. } => "SCHEMA_TOO_NEW",
+            Self::InvalidConfig { .. } => "INVALID_CONFIG",
+            Self::SqliteError(e) if is_busy(e) => "DATABASE_BUSY",
+            Self::IoError(_) | Self::SqliteError(_) | Self::JsonError(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Structured context for the error, or an empty object.
+    pub fn details(&self) -> serde_json::Value {
+        match self {
+            Self::InvalidField { field, .. } => serde_json::json!({ "field": field }),
+            Self::InvalidRefFormat(value) => serde_json::json!({ "value": value }),
+            Self::LeaseConflict {
+                path,
+                holder,
+                expires_at,
+            } => serde_json::json!({
+                "path": path,
+                "holder": holder,
+                "expires_at": expires_at,
+            }),
+            Self::SchemaTooNew { found, supported } => serde_json::json!({
+                "found": found,
+                "supported": supported,
+            }),
+            Self::InvalidConfig { path, .. } => serde_json::json!({ "path": path }),
+            _ => serde_json::json!({}),
+        }
+    }
+}
+
+fn is_busy(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
 pub type BBResult<T> = Result<T, BBError>;
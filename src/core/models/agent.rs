@@ -27,7 +27,45 @@ impl Agent {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+/// One change to an agent's status, task, progress or blockers. Events are
+/// never updated; each holds the agent's whole state after the change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusEvent {
+    pub id: i64,
+    pub agent_id: String,
+    pub status: AgentStatus,
+    pub current_task: String,
+    pub progress: u8,
+    pub blockers: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl StatusEvent {
+    /// The state `agent` is in now, as an event at its `updated_at`.
+    pub fn of(agent: &Agent) -> Self {
+        Self {
+            id: 0,
+            agent_id: agent.id.clone(),
+            status: agent.status,
+            current_task: agent.current_task.clone(),
+            progress: agent.progress,
+            blockers: agent.blockers.clone(),
+            created_at: agent.updated_at,
+        }
+    }
+
+    /// Whether `agent` is in a different state than this event records.
+    pub fn differs_from(&self, agent: &Agent) -> bool {
+        self.status != agent.status
+            || self.current_task != agent.current_task
+            || self.progress != agent.progress
+            || self.blockers != agent.blockers
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, schemars::JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum AgentStatus {
     Idle,
@@ -10,11 +10,195 @@ pub struct Artifact {
     pub description: String,
     pub version: Option<String>,
     pub refs: Vec<Reference>,
-    pub created_at: DateTime<Utc>,
+    pub first_registered_at: DateTime<Utc>,
+    /// When it was last registered, by `produced_by`.
+    pub updated_at: DateTime<Utc>,
+    /// The file as it was when last registered; `None` for directories and
+    /// artifacts registered before contents were recorded.
+    pub content_hash: Option<String>,
+    pub size: Option<i64>,
+    pub mtime: Option<DateTime<Utc>>,
+    /// The repository state when last registered; `None` outside git.
+    pub git: Option<GitVersion>,
+    /// Artifacts this one needs, by path.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Artifacts this one was generated or copied from, by path.
+    #[serde(default)]
+    pub derived_from: Vec<String>,
 }
 
 impl Artifact {}
 
+/// How an artifact relates to an upstream artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactLink {
+    DependsOn,
+    DerivedFrom,
+}
+
+impl ArtifactLink {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DependsOn => "depends_on",
+            Self::DerivedFrom => "derived_from",
+        }
+    }
+}
+
+impl std::str::FromStr for ArtifactLink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "depends_on" => Ok(Self::DependsOn),
+            "derived_from" => Ok(Self::DerivedFrom),
+            _ => Err(format!("invalid artifact link: {s}")),
+        }
+    }
+}
+
+/// Which way `bb artifact graph` follows links from its artifact.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphDirection {
+    /// What the artifact depends on or was derived from
+    Upstream,
+    /// What depends on or was derived from the artifact
+    Downstream,
+    Both,
+}
+
+/// A link in an artifact graph: `from` depends on, or was derived from, `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactEdge {
+    pub from: String,
+    pub to: String,
+    pub link: ArtifactLink,
+}
+
+/// An artifact in a graph, with the upstream artifacts that changed after it
+/// was last registered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub path: String,
+    pub produced_by: String,
+    pub updated_at: DateTime<Utc>,
+    pub changed_upstream: Vec<String>,
+}
+
+/// The artifacts reachable from `root` by following links.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactGraph {
+    pub root: String,
+    /// `root` first, then in the order they were reached.
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<ArtifactEdge>,
+}
+
+/// Where a path stood in the project's git repository when it was
+/// registered. Recorded by bb, unlike the free-form `version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitVersion {
+    /// The HEAD commit.
+    pub head: String,
+    /// Whether the path had changes not in HEAD, or was not tracked.
+    pub dirty: bool,
+    /// The last commit that touched the path, if any did.
+    pub last_commit: Option<String>,
+}
+
+impl GitVersion {
+    /// The version recorded when none is given: the short HEAD SHA, with
+    /// `-dirty` appended if the path had uncommitted changes.
+    pub fn describe(&self) -> String {
+        let short = &self.head[..self.head.len().min(12)];
+        if self.dirty {
+            format!("{short}-dirty")
+        } else {
+            short.to_string()
+        }
+    }
+}
+
+/// One registration of an artifact, as listed by `bb artifact history`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactRevision {
+    pub id: i64,
+    pub artifact_id: i64,
+    /// The path it was registered under.
+    pub path: String,
+    pub produced_by: String,
+    pub description: String,
+    pub version: Option<String>,
+    pub refs: Vec<Reference>,
+    pub content_hash: Option<String>,
+    pub size: Option<i64>,
+    pub mtime: Option<DateTime<Utc>>,
+    pub git: Option<GitVersion>,
+    pub registered_at: DateTime<Utc>,
+    /// Records the artifact's removal: `produced_by` removed it at
+    /// `registered_at`, and the rest is what it was when removed.
+    pub removed: bool,
+    /// Artifacts it needed at this revision, by path.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Artifacts it was generated or copied from at this revision, by path.
+    #[serde(default)]
+    pub derived_from: Vec<String>,
+    /// Records a rename: `produced_by` moved it here from this path at
+    /// `registered_at`.
+    #[serde(default)]
+    pub renamed_from: Option<String>,
+}
+
+/// A file's contents and metadata, as compared by `bb artifacts --check`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    /// `sha256:` followed by the lowercase hex digest.
+    pub content_hash: String,
+    pub size: i64,
+    pub mtime: DateTime<Utc>,
+}
+
+/// How an artifact's file compares with when it was last registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactState {
+    Unchanged,
+    Modified,
+    Deleted,
+    /// Nothing recorded to compare with: a directory or glob, or registered
+    /// before contents were recorded. Registering it again starts tracking it.
+    Unknown,
+}
+
+impl ArtifactState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Modified => "modified",
+            Self::Deleted => "deleted",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// An artifact next to the current state of its file. Who last registered
+/// it, and when, are the artifact's `produced_by` and `updated_at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactCheck {
+    pub artifact: Artifact,
+    pub state: ArtifactState,
+    /// The file as it is now, if it is a file.
+    pub current: Option<FileFingerprint>,
+    /// Upstream artifacts that changed after this one was last registered.
+    pub changed_upstream: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,7 +212,14 @@ mod tests {
             description: "Main entry point".to_string(),
             version: None,
             refs: vec![],
-            created_at: Utc::now(),
+            first_registered_at: Utc::now(),
+            updated_at: Utc::now(),
+            content_hash: None,
+            size: None,
+            mtime: None,
+            git: None,
+            depends_on: vec![],
+            derived_from: vec![],
         };
         assert_eq!(artifact.path, "src/main.rs");
         assert_eq!(artifact.produced_by, "agent-1");
@@ -46,7 +237,14 @@ mod tests {
             description: "Main entry".to_string(),
             version: None,
             refs: vec![],
-            created_at: Utc::now(),
+            first_registered_at: Utc::now(),
+            updated_at: Utc::now(),
+            content_hash: None,
+            size: None,
+            mtime: None,
+            git: None,
+            depends_on: vec![],
+            derived_from: vec![],
         };
         let json = serde_json::to_string(&artifact).unwrap();
         assert!(json.contains("\"path\":\"src/main.rs\""));
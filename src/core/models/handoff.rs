@@ -0,0 +1,74 @@
+use crate::core::models::message::Priority;
+use crate::core::models::reference::Reference;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Work passed from one agent to another, which the recipient must accept
+/// or decline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Handoff {
+    pub id: i64,
+    pub from_agent: String,
+    pub to_agent: String,
+    pub summary: String,
+    pub refs: Vec<Reference>,
+    /// Paths of registered artifacts that go with the work.
+    pub artifacts: Vec<String>,
+    /// Raised one level if the handoff is still pending at `escalate_at`.
+    pub priority: Priority,
+    pub status: HandoffStatus,
+    /// Message announcing the handoff; responses are posted as replies to it.
+    /// `None` once the message has been cleared.
+    pub message_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub escalate_at: DateTime<Utc>,
+    pub escalated: bool,
+    pub responded_at: Option<DateTime<Utc>>,
+    /// Note given on accepting, or reason given on declining.
+    pub response: Option<String>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HandoffStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+impl HandoffStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "accepted" => Self::Accepted,
+            "declined" => Self::Declined,
+            _ => Self::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handoff_status_parse() {
+        for status in [
+            HandoffStatus::Pending,
+            HandoffStatus::Accepted,
+            HandoffStatus::Declined,
+        ] {
+            assert_eq!(HandoffStatus::parse(status.as_str()), status);
+        }
+        assert_eq!(HandoffStatus::parse("bogus"), HandoffStatus::Pending);
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An advisory, time-limited claim on a path or glob.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lease {
+    pub id: i64,
+    pub path: String,
+    pub holder: String,
+    pub mode: LeaseMode,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaseMode {
+    Exclusive,
+    Shared,
+}
+
+impl LeaseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exclusive => "exclusive",
+            Self::Shared => "shared",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "shared" => Self::Shared,
+            _ => Self::Exclusive,
+        }
+    }
+
+    /// Shared leases only conflict with exclusive ones.
+    pub fn conflicts_with(&self, other: LeaseMode) -> bool {
+        *self == Self::Exclusive || other == Self::Exclusive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lease_mode_conflicts() {
+        assert!(LeaseMode::Exclusive.conflicts_with(LeaseMode::Shared));
+        assert!(LeaseMode::Shared.conflicts_with(LeaseMode::Exclusive));
+        assert!(!LeaseMode::Shared.conflicts_with(LeaseMode::Shared));
+    }
+
+    #[test]
+    fn test_lease_mode_parse() {
+        assert_eq!(LeaseMode::parse("shared"), LeaseMode::Shared);
+        assert_eq!(LeaseMode::parse("EXCLUSIVE"), LeaseMode::Exclusive);
+        assert_eq!(LeaseMode::parse("bogus"), LeaseMode::Exclusive);
+    }
+}
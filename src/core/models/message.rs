@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 pub struct Message {
     pub id: i64,
     pub from_agent: String,
+    /// Agents the message is addressed to, from `--to` and `@mentions`.
+    #[serde(default)]
+    pub recipients: Vec<String>,
     pub content: String,
     pub tags: Vec<String>,
     pub priority: Priority,
@@ -16,8 +19,39 @@ pub struct Message {
 
 impl Message {}
 
+/// A message in a thread, with its distance from the thread's root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadMessage {
+    #[serde(flatten)]
+    pub message: Message,
+    /// 0 for the root, 1 for its direct replies, and so on.
+    pub depth: usize,
+}
+
+/// A page of a whole reply tree, in depth-first order: each message is
+/// followed by its replies, oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Thread {
+    pub root_id: i64,
+    pub messages: Vec<ThreadMessage>,
+    /// Messages in the tree within the depth limit, across all pages.
+    pub total: usize,
+    /// Whether messages remain after this page.
+    pub has_more: bool,
+}
+
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    schemars::JsonSchema,
 )]
 #[serde(rename_all = "lowercase")]
 pub enum Priority {
@@ -55,6 +89,15 @@ impl Priority {
             Self::Critical => 3,
         }
     }
+
+    /// The next priority up; `Critical` stays `Critical`.
+    pub fn escalated(&self) -> Self {
+        match self {
+            Self::Low => Self::Normal,
+            Self::Normal => Self::High,
+            Self::High | Self::Critical => Self::Critical,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -66,6 +109,7 @@ mod tests {
         let msg = Message {
             id: 0,
             from_agent: "agent-1".to_string(),
+            recipients: vec![],
             content: "Hello world".to_string(),
             tags: vec![],
             priority: Priority::Normal,
@@ -111,6 +155,13 @@ mod tests {
         assert_eq!(Priority::Critical.level(), 3);
     }
 
+    #[test]
+    fn test_priority_escalated() {
+        assert_eq!(Priority::Low.escalated(), Priority::Normal);
+        assert_eq!(Priority::Normal.escalated(), Priority::High);
+        assert_eq!(Priority::Critical.escalated(), Priority::Critical);
+    }
+
     #[test]
     fn test_priority_ordering() {
         assert!(Priority::Low < Priority::Normal);
@@ -130,6 +181,7 @@ mod tests {
         let msg = Message {
             id: 0,
             from_agent: "agent-1".to_string(),
+            recipients: vec![],
             content: "Hello".to_string(),
             tags: vec![],
             priority: Priority::Normal,
@@ -1,4 +1,8 @@
 pub mod agent;
 pub mod artifact;
+pub mod handoff;
+pub mod lease;
 pub mod message;
+pub mod question;
 pub mod reference;
+pub mod tag;
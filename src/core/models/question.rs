@@ -0,0 +1,61 @@
+use crate::core::models::message::Message;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A message tagged `question`, tracked until it is answered or closed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Question {
+    pub message: Message,
+    pub state: QuestionState,
+    /// Reply the asker accepted as the answer.
+    pub answer_id: Option<i64>,
+    /// Number of direct replies.
+    pub replies: i64,
+    /// When the question was last answered or closed.
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionState {
+    Open,
+    Answered,
+    Closed,
+}
+
+impl QuestionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Answered => "answered",
+            Self::Closed => "closed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "answered" => Self::Answered,
+            "closed" => Self::Closed,
+            _ => Self::Open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_state_parse() {
+        for state in [
+            QuestionState::Open,
+            QuestionState::Answered,
+            QuestionState::Closed,
+        ] {
+            assert_eq!(QuestionState::parse(state.as_str()), state);
+        }
+        assert_eq!(QuestionState::parse("bogus"), QuestionState::Open);
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// A tag as the board knows it: its definition, if any, and how many
+/// messages carry it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub description: String,
+    pub aliases: Vec<String>,
+    pub source: TagSource,
+    /// Messages tagged with this tag or one of its aliases.
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagSource {
+    /// One of the conventional tags every board has.
+    Standard,
+    /// Defined in the board's `.bb/config.toml`.
+    Board,
+    /// Used on messages but not defined anywhere.
+    Undefined,
+}
+
+impl TagSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Board => "board",
+            Self::Undefined => "undefined",
+        }
+    }
+}
@@ -1,12 +1,13 @@
+use crate::core::config::LivenessConfig;
 use crate::core::errors::BBResult;
-use crate::core::models::agent::{Agent, AgentStatus, Liveness};
+use crate::core::models::agent::{Agent, AgentStatus, Liveness, StatusEvent};
+use crate::core::operations::retention;
 use crate::core::validation::limits::{validate_agent_id, validate_blockers, validate_task};
 use crate::db::queries::agent as agent_queries;
-use chrono::Utc;
-use rusqlite::Connection;
-
-pub const LIVENESS_ACTIVE_MINUTES: i64 = 5;
-pub const LIVENESS_STALE_MINUTES: i64 = 30;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+use std::collections::BTreeMap;
 
 pub fn update_agent_status(
     conn: &mut Connection,
@@ -18,8 +19,9 @@ pub fn update_agent_status(
 ) -> BBResult<Agent> {
     validate_agent_id(agent_id)?;
 
-    let mut agent =
-        agent_queries::get_agent(conn, agent_id)?.unwrap_or_else(|| Agent::new(agent_id));
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let previous = agent_queries::get_agent(&tx, agent_id)?;
+    let mut agent = previous.clone().unwrap_or_else(|| Agent::new(agent_id));
 
     if let Some(task) = current_task {
         validate_task(task)?;
@@ -47,49 +49,73 @@ pub fn update_agent_status(
     agent.last_seen = now;
     agent.updated_at = now;
 
-    agent_queries::upsert_agent(conn, &agent)?;
+    save_agent(&tx, previous.as_ref(), &agent)?;
+    tx.commit()?;
+    retention::after_write(conn, now);
     Ok(agent)
 }
 
+/// Stores `agent` and, if its state differs from `previous` (or it is new),
+/// appends the change to its status history.
+fn save_agent(conn: &Connection, previous: Option<&Agent>, agent: &Agent) -> BBResult<()> {
+    agent_queries::upsert_agent(conn, agent)?;
+    if previous.is_none_or(|p| StatusEvent::of(p).differs_from(agent)) {
+        agent_queries::insert_status_event(conn, &StatusEvent::of(agent))?;
+    }
+    Ok(())
+}
+
 pub fn get_agent(conn: &mut Connection, agent_id: &str) -> BBResult<Option<Agent>> {
     validate_agent_id(agent_id)?;
     agent_queries::get_agent(conn, agent_id)
 }
 
-pub fn get_all_agents_with_liveness(conn: &mut Connection) -> BBResult<Vec<Agent>> {
-    // Side-effect: update stale agents to offline
-    agent_queries::update_offline_status(conn, LIVENESS_STALE_MINUTES)?;
+/// Every agent, most recently seen first. Liveness is not stored: derive it
+/// with `classify_liveness`.
+pub fn get_all_agents(conn: &mut Connection) -> BBResult<Vec<Agent>> {
     agent_queries::get_all_agents(conn)
 }
 
-pub fn classify_liveness(last_seen: chrono::DateTime<Utc>) -> Liveness {
-    let elapsed = Utc::now().signed_duration_since(last_seen);
-    let minutes = elapsed.num_minutes();
+/// How recently `agent_id` has been seen, against its thresholds in
+/// `config`. This never changes the agent's stored status.
+pub fn classify_liveness(
+    config: &LivenessConfig,
+    agent_id: &str,
+    last_seen: DateTime<Utc>,
+) -> Liveness {
+    let thresholds = config.thresholds_for(agent_id);
+    let minutes = Utc::now().signed_duration_since(last_seen).num_minutes();
 
-    if minutes <= LIVENESS_ACTIVE_MINUTES {
+    if minutes <= thresholds.active_minutes {
         Liveness::Active
-    } else if minutes <= LIVENESS_STALE_MINUTES {
+    } else if minutes <= thresholds.stale_minutes {
         Liveness::Stale
     } else {
         Liveness::Offline
     }
 }
 
-pub fn touch_agent(conn: &mut Connection, agent_id: &str) -> BBResult<()> {
+/// Marks `agent_id` as seen now, registering it if it is new. Its status,
+/// task and progress are left alone.
+pub fn touch_agent(conn: &mut Connection, agent_id: &str) -> BBResult<Agent> {
     validate_agent_id(agent_id)?;
 
-    let mut agent =
-        agent_queries::get_agent(conn, agent_id)?.unwrap_or_else(|| Agent::new(agent_id));
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let previous = agent_queries::get_agent(&tx, agent_id)?;
+    let mut agent = previous.clone().unwrap_or_else(|| Agent::new(agent_id));
 
     agent.last_seen = Utc::now();
-    agent_queries::upsert_agent(conn, &agent)
+    save_agent(&tx, previous.as_ref(), &agent)?;
+    tx.commit()?;
+    Ok(agent)
 }
 
 pub fn clear_agent_status(conn: &mut Connection, agent_id: &str) -> BBResult<Agent> {
     validate_agent_id(agent_id)?;
 
-    let mut agent =
-        agent_queries::get_agent(conn, agent_id)?.unwrap_or_else(|| Agent::new(agent_id));
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let previous = agent_queries::get_agent(&tx, agent_id)?;
+    let mut agent = previous.clone().unwrap_or_else(|| Agent::new(agent_id));
 
     agent.current_task = String::new();
     agent.progress = 0;
@@ -99,17 +125,137 @@ pub fn clear_agent_status(conn: &mut Connection, agent_id: &str) -> BBResult<Age
     agent.last_seen = now;
     agent.updated_at = now;
 
-    agent_queries::upsert_agent(conn, &agent)?;
+    save_agent(&tx, previous.as_ref(), &agent)?;
+    tx.commit()?;
+    retention::after_write(conn, now);
     Ok(agent)
 }
 
-pub fn delete_offline_agents(conn: &mut Connection) -> BBResult<usize> {
-    agent_queries::delete_offline_agents(conn)
+/// Agents that are offline by `config`'s thresholds.
+pub fn offline_agents(conn: &Connection, config: &LivenessConfig) -> BBResult<Vec<Agent>> {
+    Ok(agent_queries::get_all_agents(conn)?
+        .into_iter()
+        .filter(|a| classify_liveness(config, &a.id, a.last_seen) == Liveness::Offline)
+        .collect())
+}
+
+pub fn delete_offline_agents(conn: &mut Connection, config: &LivenessConfig) -> BBResult<usize> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut deleted = 0;
+    for agent in offline_agents(&tx, config)? {
+        deleted += agent_queries::delete_agent(&tx, &agent.id)?;
+    }
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// One state in an agent's history and how long it lasted.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEntry {
+    #[serde(flatten)]
+    pub event: StatusEvent,
+    /// When the agent next changed state, or when it was last seen if it
+    /// has since gone offline; `None` for the state it is still in.
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Seconds until `ended_at`, or until now for the current state.
+    pub duration_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentTimeline {
+    pub agent_id: String,
+    /// Oldest first. The first entry may start before the window, showing
+    /// what the agent was doing when it opened.
+    pub entries: Vec<TimelineEntry>,
+    /// Seconds spent in each status within the window, by status. Time since
+    /// an offline agent was last seen counts as `offline`.
+    pub time_in_status: BTreeMap<String, i64>,
+}
+
+/// Each agent's status history since `since` (or ever), with how long each
+/// state lasted and the time spent in each status. Only the latest `limit`
+/// entries per agent are returned, but the totals cover the whole window.
+pub fn timeline(
+    conn: &mut Connection,
+    config: &LivenessConfig,
+    agent_id: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    limit: usize,
+) -> BBResult<Vec<AgentTimeline>> {
+    if let Some(agent_id) = agent_id {
+        validate_agent_id(agent_id)?;
+    }
+    let now = Utc::now();
+    // An offline agent's last state ends when it was last seen, rather than
+    // running on until now
+    let offline_since: BTreeMap<String, DateTime<Utc>> = offline_agents(conn, config)?
+        .into_iter()
+        .map(|a| (a.id, a.last_seen))
+        .collect();
+    let mut timelines: Vec<AgentTimeline> = Vec::new();
+    let mut events = agent_queries::list_status_events(conn, agent_id, since)?
+        .into_iter()
+        .peekable();
+
+    while let Some(event) = events.next() {
+        let is_last = events
+            .peek()
+            .is_none_or(|next| next.agent_id != event.agent_id);
+        let ended_at = if is_last {
+            offline_since
+                .get(&event.agent_id)
+                .map(|seen| (*seen).max(event.created_at))
+        } else {
+            events.peek().map(|next| next.created_at)
+        };
+        let end = ended_at.unwrap_or(now);
+
+        let window_start = |at: DateTime<Utc>| since.map_or(at, |s| s.max(at));
+        let in_window = |from: DateTime<Utc>, to: DateTime<Utc>| {
+            to.signed_duration_since(window_start(from))
+                .num_seconds()
+                .max(0)
+        };
+
+        if timelines
+            .last()
+            .is_none_or(|t| t.agent_id != event.agent_id)
+        {
+            timelines.push(AgentTimeline {
+                agent_id: event.agent_id.clone(),
+                entries: Vec::new(),
+                time_in_status: BTreeMap::new(),
+            });
+        }
+        let timeline = timelines.last_mut().expect("pushed above");
+        *timeline
+            .time_in_status
+            .entry(event.status.as_str().to_string())
+            .or_default() += in_window(event.created_at, end);
+        if is_last && ended_at.is_some() {
+            *timeline
+                .time_in_status
+                .entry(AgentStatus::Offline.as_str().to_string())
+                .or_default() += in_window(end, now);
+        }
+        timeline.entries.push(TimelineEntry {
+            duration_secs: end.signed_duration_since(event.created_at).num_seconds(),
+            ended_at,
+            event,
+        });
+    }
+
+    for timeline in &mut timelines {
+        let excess = timeline.entries.len().saturating_sub(limit);
+        timeline.entries.drain(..excess);
+    }
+    Ok(timelines)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::LivenessOverride;
     use crate::db::migrations::run_migrations;
     use rusqlite::Connection;
 
@@ -122,19 +268,50 @@ mod tests {
     #[test]
     fn test_classify_liveness_active() {
         let last_seen = Utc::now() - chrono::Duration::minutes(3);
-        assert_eq!(classify_liveness(last_seen), Liveness::Active);
+        assert_eq!(
+            classify_liveness(&LivenessConfig::default(), "agent-1", last_seen),
+            Liveness::Active
+        );
     }
 
     #[test]
     fn test_classify_liveness_stale() {
         let last_seen = Utc::now() - chrono::Duration::minutes(15);
-        assert_eq!(classify_liveness(last_seen), Liveness::Stale);
+        assert_eq!(
+            classify_liveness(&LivenessConfig::default(), "agent-1", last_seen),
+            Liveness::Stale
+        );
     }
 
     #[test]
     fn test_classify_liveness_offline() {
         let last_seen = Utc::now() - chrono::Duration::minutes(35);
-        assert_eq!(classify_liveness(last_seen), Liveness::Offline);
+        assert_eq!(
+            classify_liveness(&LivenessConfig::default(), "agent-1", last_seen),
+            Liveness::Offline
+        );
+    }
+
+    #[test]
+    fn test_classify_liveness_agent_override() {
+        let mut config = LivenessConfig::default();
+        config.agents.insert(
+            "builder".to_string(),
+            LivenessOverride {
+                stale_minutes: Some(240),
+                ..Default::default()
+            },
+        );
+        let last_seen = Utc::now() - chrono::Duration::hours(2);
+
+        assert_eq!(
+            classify_liveness(&config, "agent-1", last_seen),
+            Liveness::Offline
+        );
+        assert_eq!(
+            classify_liveness(&config, "builder", last_seen),
+            Liveness::Stale
+        );
     }
 
     #[test]
@@ -212,6 +389,29 @@ mod tests {
         assert!(agent.blockers.is_none());
     }
 
+    #[test]
+    fn test_status_writes_survive_failed_upkeep() {
+        let mut conn = setup();
+        // Escalation after the write can no longer read its table
+        conn.execute_batch("DROP TABLE handoffs").unwrap();
+
+        update_agent_status(
+            &mut conn,
+            "test-agent",
+            Some("working"),
+            None,
+            Some(AgentStatus::Coding),
+            None,
+        )
+        .unwrap();
+        let stored = get_agent(&mut conn, "test-agent").unwrap().unwrap();
+        assert_eq!(stored.current_task, "working");
+
+        clear_agent_status(&mut conn, "test-agent").unwrap();
+        let stored = get_agent(&mut conn, "test-agent").unwrap().unwrap();
+        assert_eq!(stored.status, AgentStatus::Idle);
+    }
+
     #[test]
     fn test_blockers_cleared_when_not_blocked() {
         let mut conn = setup();
@@ -260,20 +460,144 @@ mod tests {
     fn test_delete_offline_agents() {
         let mut conn = setup();
 
-        // Create agents
+        // Create agents; liveness, not the stored status, decides who is offline
         let mut offline_agent = Agent::new("offline-agent");
-        offline_agent.status = AgentStatus::Offline;
+        offline_agent.status = AgentStatus::Coding;
         offline_agent.last_seen = Utc::now() - chrono::Duration::hours(2);
-        agent_queries::upsert_agent(&mut conn, &offline_agent).unwrap();
+        agent_queries::upsert_agent(&conn, &offline_agent).unwrap();
 
         let active_agent = Agent::new("active-agent");
-        agent_queries::upsert_agent(&mut conn, &active_agent).unwrap();
+        agent_queries::upsert_agent(&conn, &active_agent).unwrap();
 
-        let deleted = delete_offline_agents(&mut conn).unwrap();
+        let deleted = delete_offline_agents(&mut conn, &LivenessConfig::default()).unwrap();
         assert_eq!(deleted, 1);
 
-        let remaining = agent_queries::get_all_agents(&mut conn).unwrap();
+        let remaining = get_all_agents(&mut conn).unwrap();
         assert_eq!(remaining.len(), 1);
         assert_eq!(remaining[0].id, "active-agent");
     }
+
+    #[test]
+    fn test_status_changes_are_recorded_once() {
+        let mut conn = setup();
+
+        touch_agent(&mut conn, "agent-1").unwrap();
+        for _ in 0..2 {
+            update_agent_status(
+                &mut conn,
+                "agent-1",
+                Some("parser"),
+                Some(40),
+                Some(AgentStatus::Coding),
+                None,
+            )
+            .unwrap();
+        }
+        touch_agent(&mut conn, "agent-1").unwrap();
+        clear_agent_status(&mut conn, "agent-1").unwrap();
+
+        let events = agent_queries::list_status_events(&conn, Some("agent-1"), None).unwrap();
+        let statuses: Vec<_> = events.iter().map(|e| e.status).collect();
+        assert_eq!(
+            statuses,
+            vec![AgentStatus::Idle, AgentStatus::Coding, AgentStatus::Idle]
+        );
+        assert_eq!(events[1].current_task, "parser");
+    }
+
+    #[test]
+    fn test_timeline_durations() {
+        let mut conn = setup();
+        let now = Utc::now();
+        let agent = Agent::new("agent-1");
+        agent_queries::upsert_agent(&conn, &agent).unwrap();
+        for (mins_ago, status) in [
+            (90, AgentStatus::Coding),
+            (60, AgentStatus::Blocked),
+            (20, AgentStatus::Coding),
+        ] {
+            let event = StatusEvent {
+                status,
+                created_at: now - chrono::Duration::minutes(mins_ago),
+                ..StatusEvent::of(&agent)
+            };
+            agent_queries::insert_status_event(&conn, &event).unwrap();
+        }
+
+        let timelines = timeline(
+            &mut conn,
+            &LivenessConfig::default(),
+            Some("agent-1"),
+            Some(now - chrono::Duration::minutes(75)),
+            10,
+        )
+        .unwrap();
+        assert_eq!(timelines.len(), 1);
+        let entries = &timelines[0].entries;
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].duration_secs, 30 * 60);
+        assert_eq!(entries[1].event.status, AgentStatus::Blocked);
+        assert_eq!(entries[1].duration_secs, 40 * 60);
+        assert!(entries[2].ended_at.is_none());
+
+        // Coding before the window opened is not counted
+        let totals = &timelines[0].time_in_status;
+        assert_eq!(totals["blocked"], 40 * 60);
+        assert!((totals["coding"] - 35 * 60).abs() <= 2);
+
+        let latest = timeline(&mut conn, &LivenessConfig::default(), None, None, 1).unwrap();
+        assert_eq!(latest[0].entries.len(), 1);
+        assert_eq!(latest[0].entries[0].event.status, AgentStatus::Coding);
+    }
+
+    #[test]
+    fn test_touch_keeps_status_of_quiet_agent() {
+        let mut conn = setup();
+        let mut agent = Agent::new("agent-1");
+        agent.status = AgentStatus::Coding;
+        agent.last_seen = Utc::now() - chrono::Duration::hours(2);
+        agent_queries::upsert_agent(&conn, &agent).unwrap();
+        let config = LivenessConfig::default();
+
+        // Reading liveness leaves the stored status alone
+        let agents = get_all_agents(&mut conn).unwrap();
+        assert_eq!(
+            classify_liveness(&config, "agent-1", agents[0].last_seen),
+            Liveness::Offline
+        );
+        assert_eq!(agents[0].status, AgentStatus::Coding);
+
+        let touched = touch_agent(&mut conn, "agent-1").unwrap();
+        assert_eq!(touched.status, AgentStatus::Coding);
+        assert_eq!(
+            classify_liveness(&config, "agent-1", touched.last_seen),
+            Liveness::Active
+        );
+        assert_eq!(
+            agent_queries::list_status_events(&conn, Some("agent-1"), None)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_timeline_ends_at_last_seen_when_offline() {
+        let mut conn = setup();
+        let now = Utc::now();
+        let mut agent = Agent::new("agent-1");
+        agent.status = AgentStatus::Coding;
+        agent.updated_at = now - chrono::Duration::minutes(90);
+        agent.last_seen = now - chrono::Duration::minutes(60);
+        agent_queries::upsert_agent(&conn, &agent).unwrap();
+        agent_queries::insert_status_event(&conn, &StatusEvent::of(&agent)).unwrap();
+
+        let timelines = timeline(&mut conn, &LivenessConfig::default(), None, None, 10).unwrap();
+        let entry = &timelines[0].entries[0];
+        assert_eq!(entry.ended_at, Some(agent.last_seen));
+        assert_eq!(entry.duration_secs, 30 * 60);
+        let totals = &timelines[0].time_in_status;
+        assert_eq!(totals["coding"], 30 * 60);
+        assert!((totals["offline"] - 60 * 60).abs() <= 2);
+    }
 }
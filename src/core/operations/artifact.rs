@@ -1,14 +1,26 @@
 use crate::core::errors::BBError;
 use crate::core::errors::BBResult;
-use crate::core::models::artifact::Artifact;
+use crate::core::models::artifact::{
+    Artifact, ArtifactCheck, ArtifactEdge, ArtifactGraph, ArtifactLink, ArtifactRevision,
+    ArtifactState, FileFingerprint, GitVersion, GraphDirection, GraphNode,
+};
 use crate::core::models::reference::Reference;
 use crate::core::validation::limits::{
-    MAX_REFS_PER_ENTITY, validate_artifact_description, validate_artifact_path, validate_version,
+    limits, normalize_project_path, validate_artifact_description, validate_artifact_path,
+    validate_version,
 };
 use crate::db::queries::artifact as artifact_queries;
-use rusqlite::Connection;
+use crate::util::git;
+use crate::util::glob::{glob_match, is_glob};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
 use std::path::Path;
 
+/// Registers an artifact without declaring links.
+#[cfg(test)]
 pub fn register_artifact(
     conn: &mut Connection,
     path: &str,
@@ -18,50 +30,535 @@ pub fn register_artifact(
     refs: Vec<Reference>,
     project_root: &Path,
 ) -> BBResult<Artifact> {
+    register_linked_artifact(
+        conn,
+        path,
+        produced_by,
+        description,
+        version,
+        refs,
+        &[],
+        &[],
+        false,
+        project_root,
+    )
+}
+
+/// Registers an artifact along with the artifacts it depends on or was
+/// derived from, which must already be registered. Declaring any link
+/// replaces the artifact's links; declaring none keeps them, unless
+/// `clear_links` is set, which drops them all.
+#[allow(clippy::too_many_arguments)]
+pub fn register_linked_artifact(
+    conn: &mut Connection,
+    path: &str,
+    produced_by: &str,
+    description: &str,
+    version: Option<&str>,
+    refs: Vec<Reference>,
+    depends_on: &[String],
+    derived_from: &[String],
+    clear_links: bool,
+    project_root: &Path,
+) -> BBResult<Artifact> {
+    if clear_links && !(depends_on.is_empty() && derived_from.is_empty()) {
+        return Err(BBError::invalid_field(
+            "clear_links",
+            "cannot be combined with depends_on or derived_from",
+        ));
+    }
     // Validate path and check it doesn't escape project
-    validate_artifact_path(path, project_root)?;
+    let path = &validate_artifact_path(path, project_root)?;
+    let (mut artifact, links) = prepare_registration(
+        conn,
+        path,
+        produced_by,
+        description,
+        version,
+        refs,
+        depends_on,
+        derived_from,
+        project_root,
+    )?;
+    set_git(&mut artifact, git::version(project_root, path)?);
+
+    // Declaring no links keeps the ones the artifact has
+    let links = (clear_links || !links.is_empty()).then_some(links.as_slice());
+    artifact_queries::upsert_artifact(conn, &artifact, links)?;
+
+    // Return the artifact (get it to get the ID)
+    artifact_queries::get_artifact_by_path(conn, path)?
+        .ok_or_else(|| BBError::NotFound(format!("artifact {path} not found after upsert")))
+}
+
+/// Validates a registration of the artifact at `path`, already validated,
+/// and returns the artifact to store, without its git state, along with the
+/// ids of the upstream artifacts it links to. Nothing is written.
+#[allow(clippy::too_many_arguments)]
+fn prepare_registration(
+    conn: &Connection,
+    path: &str,
+    produced_by: &str,
+    description: &str,
+    version: Option<&str>,
+    refs: Vec<Reference>,
+    depends_on: &[String],
+    derived_from: &[String],
+    project_root: &Path,
+) -> BBResult<(Artifact, Vec<(i64, ArtifactLink)>)> {
     validate_artifact_description(description)?;
 
     if let Some(ver) = version {
         validate_version(ver)?;
     }
 
-    if refs.len() > MAX_REFS_PER_ENTITY {
-        return Err(BBError::InvalidInput(format!(
-            "too many refs (max {MAX_REFS_PER_ENTITY})"
-        )));
+    let limits = limits();
+    if refs.len() > limits.max_refs_per_entity {
+        return Err(BBError::invalid_field(
+            "refs",
+            format!("too many refs (max {})", limits.max_refs_per_entity),
+        ));
     }
+    // Resolved before anything is written, so a bad link registers nothing
+    let mut links = resolve_links(
+        conn,
+        path,
+        "depends_on",
+        depends_on,
+        ArtifactLink::DependsOn,
+    )?;
+    links.extend(resolve_links(
+        conn,
+        path,
+        "derived_from",
+        derived_from,
+        ArtifactLink::DerivedFrom,
+    )?);
 
+    let current = fingerprint(&project_root.join(path))?;
+    let now = Utc::now();
     let artifact = Artifact {
         id: 0,
         path: path.to_string(),
         produced_by: produced_by.to_string(),
         description: description.to_string(),
-        version: version.map(|v| v.to_string()),
+        version: version.map(String::from),
         refs,
-        created_at: chrono::Utc::now(),
+        first_registered_at: now,
+        updated_at: now,
+        content_hash: current.as_ref().map(|f| f.content_hash.clone()),
+        size: current.as_ref().map(|f| f.size),
+        mtime: current.map(|f| f.mtime),
+        git: None,
+        depends_on: vec![],
+        derived_from: vec![],
     };
+    Ok((artifact, links))
+}
 
-    artifact_queries::upsert_artifact(conn, &artifact)?;
+/// Records where the artifact's path stands in git. The git state is
+/// recorded whether or not a version was given; only the latter is the
+/// agent's word, so it also stands in for a missing version.
+fn set_git(artifact: &mut Artifact, git: Option<GitVersion>) {
+    if artifact.version.is_none() {
+        artifact.version = git.as_ref().map(GitVersion::describe);
+    }
+    artifact.git = git;
+}
 
-    // Return the artifact (get it to get the ID)
-    artifact_queries::get_artifact_by_path(conn, path)?
-        .ok_or_else(|| BBError::NotFound(format!("artifact {path} not found after upsert")))
+/// The ids of the registered artifacts at `paths`, which `path` links to
+/// as `link`.
+fn resolve_links(
+    conn: &Connection,
+    path: &str,
+    field: &str,
+    paths: &[String],
+    link: ArtifactLink,
+) -> BBResult<Vec<(i64, ArtifactLink)>> {
+    let limits = limits();
+    if paths.len() > limits.max_refs_per_entity {
+        return Err(BBError::invalid_field(
+            field,
+            format!("too many links (max {})", limits.max_refs_per_entity),
+        ));
+    }
+    let mut links = Vec::new();
+    for upstream in paths {
+        let upstream = normalize_project_path(upstream)?;
+        if upstream == path {
+            return Err(BBError::invalid_field(
+                field,
+                format!("'{path}' cannot link to itself"),
+            ));
+        }
+        let artifact = artifact_queries::get_artifact_by_path(conn, &upstream)?
+            .ok_or_else(|| BBError::NotFound(format!("artifact '{upstream}' not found")))?;
+        links.push((artifact.id, link));
+    }
+    Ok(links)
+}
+
+/// What `register_commit` registered.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitRegistration {
+    /// The full SHA of the commit.
+    pub commit: String,
+    pub registered: Vec<Artifact>,
+    /// Files the commit added or changed that are no longer on disk.
+    pub skipped: Vec<String>,
+}
+
+/// Registers every file that `commit` added or modified under
+/// `project_root`, all in one transaction. The description defaults to the
+/// commit's subject.
+pub fn register_commit(
+    conn: &mut Connection,
+    commit: &str,
+    produced_by: &str,
+    description: Option<&str>,
+    version: Option<&str>,
+    refs: Vec<Reference>,
+    project_root: &Path,
+) -> BBResult<CommitRegistration> {
+    let commit = git::resolve_commit(project_root, commit)?;
+    let description = match description {
+        Some(d) => d.to_string(),
+        None => git::commit_subject(project_root, &commit)?,
+    };
+
+    let (present, skipped): (Vec<_>, Vec<_>) = git::files_in_commit(project_root, &commit)?
+        .into_iter()
+        .partition(|path| project_root.join(path).exists());
+    let mut versions = git::versions(project_root, &present)?.unwrap_or_default();
+    let mut artifacts = Vec::new();
+    for path in &present {
+        let (mut artifact, _) = prepare_registration(
+            conn,
+            &validate_artifact_path(path, project_root)?,
+            produced_by,
+            &description,
+            version,
+            refs.clone(),
+            &[],
+            &[],
+            project_root,
+        )?;
+        set_git(&mut artifact, versions.remove(path));
+        artifacts.push(artifact);
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut registered = Vec::new();
+    for artifact in &artifacts {
+        artifact_queries::upsert_artifact_in(&tx, artifact, None)?;
+        registered.push(
+            artifact_queries::get_artifact_by_path(&tx, &artifact.path)?.ok_or_else(|| {
+                BBError::NotFound(format!("artifact {} not found after upsert", artifact.path))
+            })?,
+        );
+    }
+    tx.commit()?;
+
+    Ok(CommitRegistration {
+        commit,
+        registered,
+        skipped,
+    })
+}
+
+/// Artifacts whose files differ from `commit`: changed by later commits,
+/// changed in the working tree, or not in the commit at all. Newest first.
+pub fn changed_since_commit(
+    conn: &mut Connection,
+    commit: &str,
+    produced_by: Option<&str>,
+    project_root: &Path,
+) -> BBResult<Vec<Artifact>> {
+    let commit = git::resolve_commit(project_root, commit)?;
+    let mut artifacts = Vec::new();
+    for path in git::changed_since(project_root, &commit)? {
+        if let Some(artifact) = artifact_queries::get_artifact_by_path(conn, &path)?
+            && produced_by.is_none_or(|p| artifact.produced_by == p)
+        {
+            artifacts.push(artifact);
+        }
+    }
+    artifacts.sort_by_key(|a| std::cmp::Reverse(a.updated_at));
+    Ok(artifacts)
 }
 
 pub fn get_artifact(conn: &mut Connection, path: &str) -> BBResult<Option<Artifact>> {
     artifact_queries::get_artifact_by_path(conn, path)
 }
 
+/// The directory and glob artifacts that `path` falls under, most specific
+/// first.
+pub fn covering_artifacts(conn: &mut Connection, path: &str) -> BBResult<Vec<Artifact>> {
+    let path = normalize_project_path(path)?;
+    let mut artifacts = artifact_queries::list_covering_artifacts(conn, &path)?;
+    artifacts.retain(|a| !is_glob(&a.path) || glob_match(&a.path, &path));
+    Ok(artifacts)
+}
+
+/// Removes the artifact at `path` on behalf of `agent_id`. Its history is
+/// kept, ending in a revision that records the removal.
+pub fn remove_artifact(conn: &mut Connection, agent_id: &str, path: &str) -> BBResult<Artifact> {
+    let path = normalize_project_path(path)?;
+    let artifact = artifact_queries::get_artifact_by_path(conn, &path)?
+        .ok_or_else(|| BBError::NotFound(format!("artifact '{path}' not found")))?;
+    artifact_queries::delete_artifact(conn, &path, agent_id, Utc::now())?;
+    Ok(artifact)
+}
+
+/// Moves the artifact at `old` to `new`, which must exist unless it is a
+/// glob, and records the move as a revision by `agent_id`. Its refs and
+/// history go with it; earlier revisions keep the path they were registered
+/// under.
+pub fn rename_artifact(
+    conn: &mut Connection,
+    agent_id: &str,
+    old: &str,
+    new: &str,
+    project_root: &Path,
+) -> BBResult<Artifact> {
+    let old = normalize_project_path(old)?;
+    let new = validate_artifact_path(new, project_root)?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    if artifact_queries::get_artifact_by_path(&tx, &new)?.is_some() {
+        return Err(BBError::invalid_field(
+            "path",
+            format!("artifact '{new}' is already registered"),
+        ));
+    }
+    if artifact_queries::rename_artifact(&tx, &old, &new, agent_id, Utc::now())? == 0 {
+        return Err(BBError::NotFound(format!("artifact '{old}' not found")));
+    }
+    let artifact = artifact_queries::get_artifact_by_path(&tx, &new)?
+        .ok_or_else(|| BBError::NotFound(format!("artifact {new} not found after rename")))?;
+    tx.commit()?;
+    Ok(artifact)
+}
+
+/// Every registration of the artifact at `path`, newest first. If nothing is
+/// registered there, the history of the artifact last removed from it.
+pub fn artifact_history(conn: &mut Connection, path: &str) -> BBResult<Vec<ArtifactRevision>> {
+    let path = normalize_project_path(path)?;
+    let artifact_id = match artifact_queries::get_artifact_by_path(conn, &path)? {
+        Some(artifact) => artifact.id,
+        None => artifact_queries::removed_artifact_id(conn, &path)?
+            .ok_or_else(|| BBError::NotFound(format!("artifact '{path}' not found")))?,
+    };
+    artifact_queries::list_artifact_revisions(conn, artifact_id)
+}
+
+/// Lists artifacts, newest first. `under` is a directory: only the artifact
+/// there and those below it are listed.
 pub fn list_artifacts(
     conn: &mut Connection,
     produced_by: Option<&str>,
+    under: Option<&str>,
     ref_where: Option<&str>,
     ref_what: Option<&str>,
     ref_ref: Option<&str>,
     limit: usize,
 ) -> BBResult<Vec<Artifact>> {
-    artifact_queries::list_artifacts(conn, produced_by, ref_where, ref_what, ref_ref, limit)
+    let under = under.map(normalize_project_path).transpose()?;
+    artifact_queries::list_artifacts(
+        conn,
+        produced_by,
+        under.as_deref(),
+        ref_where,
+        ref_what,
+        ref_ref,
+        limit,
+    )
+}
+
+/// Compares each matching artifact with its file on disk.
+#[allow(clippy::too_many_arguments)]
+pub fn check_artifacts(
+    conn: &mut Connection,
+    produced_by: Option<&str>,
+    under: Option<&str>,
+    ref_where: Option<&str>,
+    ref_what: Option<&str>,
+    ref_ref: Option<&str>,
+    limit: usize,
+    project_root: &Path,
+) -> BBResult<Vec<ArtifactCheck>> {
+    let artifacts = list_artifacts(
+        conn,
+        produced_by,
+        under,
+        ref_where,
+        ref_what,
+        ref_ref,
+        limit,
+    )?;
+    let mut checks = Vec::with_capacity(artifacts.len());
+    for artifact in artifacts {
+        let changed_upstream = changed_upstream(conn, &artifact, project_root)?;
+        let mut check = check_artifact(artifact, project_root)?;
+        check.changed_upstream = changed_upstream;
+        checks.push(check);
+    }
+    Ok(checks)
+}
+
+/// The artifacts `artifact` links to that changed after it was last
+/// registered: re-registered since, or modified on disk since.
+pub fn changed_upstream(
+    conn: &mut Connection,
+    artifact: &Artifact,
+    project_root: &Path,
+) -> BBResult<Vec<String>> {
+    let mut changed = Vec::new();
+    for path in artifact.depends_on.iter().chain(&artifact.derived_from) {
+        if changed.contains(path) {
+            continue;
+        }
+        let Some(upstream) = artifact_queries::get_artifact_by_path(conn, path)? else {
+            continue;
+        };
+        let check = check_artifact(upstream, project_root)?;
+        let last_changed = match (&check.state, &check.current) {
+            (ArtifactState::Modified, Some(now)) => now.mtime.max(check.artifact.updated_at),
+            _ => check.artifact.updated_at,
+        };
+        if last_changed > artifact.updated_at {
+            changed.push(path.clone());
+        }
+    }
+    Ok(changed)
+}
+
+/// The artifacts reachable from the one at `path` by following links in
+/// `direction`, at most `depth` links away.
+pub fn artifact_graph(
+    conn: &mut Connection,
+    path: &str,
+    direction: GraphDirection,
+    depth: Option<usize>,
+    project_root: &Path,
+) -> BBResult<ArtifactGraph> {
+    let root = normalize_project_path(path)?;
+    if artifact_queries::get_artifact_by_path(conn, &root)?.is_none() {
+        return Err(BBError::NotFound(format!("artifact '{root}' not found")));
+    }
+    let links = artifact_queries::list_artifact_links(conn)?;
+
+    let mut paths = vec![root.clone()];
+    let mut edges: Vec<ArtifactEdge> = Vec::new();
+    // Upstream and downstream are walked separately, so `Both` does not pick
+    // up the other artifacts derived from an upstream
+    let walks: &[bool] = match direction {
+        GraphDirection::Upstream => &[true],
+        GraphDirection::Downstream => &[false],
+        GraphDirection::Both => &[true, false],
+    };
+    for &upstream in walks {
+        let mut seen = HashSet::from([root.clone()]);
+        let mut queue = VecDeque::from([(root.clone(), 0)]);
+        while let Some((current, distance)) = queue.pop_front() {
+            if depth.is_some_and(|d| distance >= d) {
+                continue;
+            }
+            for link in &links {
+                let (near, far) = if upstream {
+                    (&link.from, &link.to)
+                } else {
+                    (&link.to, &link.from)
+                };
+                if *near != current {
+                    continue;
+                }
+                if !edges.contains(link) {
+                    edges.push(link.clone());
+                }
+                if seen.insert(far.clone()) {
+                    if !paths.contains(far) {
+                        paths.push(far.clone());
+                    }
+                    queue.push_back((far.clone(), distance + 1));
+                }
+            }
+        }
+    }
+
+    let mut nodes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let artifact = artifact_queries::get_artifact_by_path(conn, &path)?
+            .ok_or_else(|| BBError::NotFound(format!("artifact '{path}' not found")))?;
+        nodes.push(GraphNode {
+            changed_upstream: changed_upstream(conn, &artifact, project_root)?,
+            path,
+            produced_by: artifact.produced_by,
+            updated_at: artifact.updated_at,
+        });
+    }
+    Ok(ArtifactGraph { root, nodes, edges })
+}
+
+fn check_artifact(artifact: Artifact, project_root: &Path) -> BBResult<ArtifactCheck> {
+    if is_glob(&artifact.path) {
+        return Ok(ArtifactCheck {
+            artifact,
+            state: ArtifactState::Unknown,
+            current: None,
+            changed_upstream: vec![],
+        });
+    }
+    let path = project_root.join(&artifact.path);
+    if let Err(e) = std::fs::symlink_metadata(&path) {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            return Ok(ArtifactCheck {
+                artifact,
+                state: ArtifactState::Deleted,
+                current: None,
+                changed_upstream: vec![],
+            });
+        }
+        return Err(e.into());
+    }
+
+    let current = fingerprint(&path)?;
+    let state = match (&artifact.content_hash, &current) {
+        (None, _) => ArtifactState::Unknown,
+        (Some(hash), Some(now)) if *hash == now.content_hash => ArtifactState::Unchanged,
+        (Some(_), _) => ArtifactState::Modified,
+    };
+    Ok(ArtifactCheck {
+        artifact,
+        state,
+        current,
+        changed_upstream: vec![],
+    })
+}
+
+/// Hashes the file at `path`, or returns `None` if there is no regular file
+/// there.
+pub fn fingerprint(path: &Path) -> BBResult<Option<FileFingerprint>> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        Ok(_) => return Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+
+    Ok(Some(FileFingerprint {
+        content_hash: format!("sha256:{hex}"),
+        size: metadata.len() as i64,
+        mtime: DateTime::<Utc>::from(metadata.modified()?),
+    }))
 }
 
 pub fn clear_artifacts(conn: &mut Connection) -> BBResult<usize> {
@@ -72,6 +569,7 @@ pub fn clear_artifacts(conn: &mut Connection) -> BBResult<usize> {
 mod tests {
     use super::*;
     use crate::db::migrations::run_migrations;
+    use crate::util::git::tests as git_tests;
     use rusqlite::Connection;
     use std::fs;
     use tempfile::TempDir;
@@ -148,6 +646,273 @@ mod tests {
         assert_eq!(artifact.version, Some("v2.0.0".to_string()));
     }
 
+    #[test]
+    fn test_artifact_history_keeps_every_registration() {
+        let (mut conn, temp) = setup();
+        fs::write(temp.path().join("api.md"), "v1").unwrap();
+
+        let first = register_artifact(
+            &mut conn,
+            "api.md",
+            "writer",
+            "Draft",
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+        fs::write(temp.path().join("api.md"), "v2").unwrap();
+        let latest = register_artifact(
+            &mut conn,
+            "api.md",
+            "reviewer",
+            "Reviewed",
+            Some("v2"),
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+
+        assert_eq!(latest.first_registered_at, first.first_registered_at);
+        assert!(latest.updated_at >= first.updated_at);
+
+        let history = artifact_history(&mut conn, "api.md").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            (
+                history[0].produced_by.as_str(),
+                history[0].description.as_str()
+            ),
+            ("reviewer", "Reviewed")
+        );
+        assert_eq!(
+            (
+                history[1].produced_by.as_str(),
+                history[1].description.as_str()
+            ),
+            ("writer", "Draft")
+        );
+        assert_ne!(history[0].content_hash, history[1].content_hash);
+
+        assert!(matches!(
+            artifact_history(&mut conn, "missing.md"),
+            Err(BBError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_artifact_records_git_version() {
+        let temp = git_tests::repo();
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let head = git_tests::git(temp.path(), &["rev-parse", "HEAD"]);
+
+        let clean = register_artifact(
+            &mut conn,
+            "a.txt",
+            "agent-1",
+            "A",
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+        assert_eq!(clean.version.as_deref(), Some(&head[..12]));
+        let git = clean.git.unwrap();
+        assert_eq!(git.last_commit.as_deref(), Some(head.as_str()));
+        assert!(!git.dirty);
+
+        fs::write(temp.path().join("b.txt"), "edited").unwrap();
+        let dirty = register_artifact(
+            &mut conn,
+            "b.txt",
+            "agent-1",
+            "B",
+            Some("v2"),
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+        assert_eq!(dirty.version.as_deref(), Some("v2"));
+        assert!(dirty.git.unwrap().dirty);
+
+        let history = artifact_history(&mut conn, "b.txt").unwrap();
+        assert_eq!(history[0].git.as_ref().map(|g| g.dirty), Some(true));
+    }
+
+    #[test]
+    fn test_register_commit_and_changed_since() {
+        let temp = git_tests::repo();
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let first = git_tests::git(temp.path(), &["rev-parse", "HEAD"]);
+        fs::remove_file(temp.path().join("b.txt")).unwrap();
+
+        let registration = register_commit(
+            &mut conn,
+            "HEAD",
+            "agent-1",
+            None,
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+        assert_eq!(registration.commit, first);
+        assert_eq!(registration.registered.len(), 1);
+        assert_eq!(registration.registered[0].description, "Add a and b");
+        assert_eq!(registration.skipped, vec!["b.txt"]);
+        // Looked up for the whole batch at once
+        let git = registration.registered[0].git.as_ref().unwrap();
+        assert_eq!(git.head, first);
+        assert_eq!(git.last_commit.as_deref(), Some(first.as_str()));
+        assert!(!git.dirty);
+        assert_eq!(
+            registration.registered[0].version.as_deref(),
+            Some(&first[..12])
+        );
+
+        assert!(
+            changed_since_commit(&mut conn, &first, None, temp.path())
+                .unwrap()
+                .is_empty()
+        );
+        fs::write(temp.path().join("a.txt"), "a2").unwrap();
+        let changed = changed_since_commit(&mut conn, &first, None, temp.path()).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path, "a.txt");
+        assert!(
+            changed_since_commit(&mut conn, &first, Some("agent-2"), temp.path())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_directory_and_glob_artifacts() {
+        let (mut conn, temp) = setup();
+        fs::create_dir_all(temp.path().join("src/auth/oauth")).unwrap();
+        fs::write(temp.path().join("src/auth/oauth/token.rs"), "").unwrap();
+        fs::create_dir_all(temp.path().join("src/auth_old")).unwrap();
+
+        for (path, by) in [
+            ("src/auth/", "security"),
+            ("src/auth/**/*.rs", "backend"),
+            ("src/auth/oauth/token.rs", "backend"),
+            ("src/auth_old", "security"),
+        ] {
+            register_artifact(&mut conn, path, by, "", None, vec![], temp.path()).unwrap();
+        }
+        assert!(get_artifact(&mut conn, "src/auth").unwrap().is_some());
+
+        let under =
+            list_artifacts(&mut conn, None, Some("src/auth"), None, None, None, 10).unwrap();
+        let mut paths: Vec<_> = under.iter().map(|a| a.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["src/auth", "src/auth/**/*.rs", "src/auth/oauth/token.rs"]
+        );
+
+        let covering = covering_artifacts(&mut conn, "src/auth/oauth/session.rs").unwrap();
+        let paths: Vec<_> = covering.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/auth/**/*.rs", "src/auth"]);
+        assert!(
+            covering_artifacts(&mut conn, "README.md")
+                .unwrap()
+                .is_empty()
+        );
+
+        let checks = check_artifacts(
+            &mut conn,
+            None,
+            Some("src/auth"),
+            None,
+            None,
+            None,
+            10,
+            temp.path(),
+        )
+        .unwrap();
+        assert!(
+            checks
+                .iter()
+                .filter(|c| c.artifact.path != "src/auth/oauth/token.rs")
+                .all(|c| c.state == ArtifactState::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_remove_and_rename_artifacts() {
+        let (mut conn, temp) = setup();
+        fs::write(temp.path().join("old.md"), "v1").unwrap();
+        fs::write(temp.path().join("other.md"), "").unwrap();
+        let refs = vec![Reference {
+            where_: "tt".to_string(),
+            what: "task".to_string(),
+            ref_: serde_json::json!(7),
+        }];
+        for by in ["writer", "reviewer"] {
+            register_artifact(&mut conn, "old.md", by, "", None, refs.clone(), temp.path())
+                .unwrap();
+        }
+        register_artifact(
+            &mut conn,
+            "other.md",
+            "writer",
+            "",
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+
+        fs::rename(temp.path().join("old.md"), temp.path().join("new.md")).unwrap();
+        assert!(matches!(
+            rename_artifact(&mut conn, "mover", "old.md", "other.md", temp.path()),
+            Err(BBError::InvalidField { .. })
+        ));
+        assert!(matches!(
+            rename_artifact(&mut conn, "mover", "gone.md", "new.md", temp.path()),
+            Err(BBError::NotFound(_))
+        ));
+        let renamed = rename_artifact(&mut conn, "mover", "old.md", "new.md", temp.path()).unwrap();
+        assert_eq!(renamed.path, "new.md");
+        assert_eq!(renamed.refs, refs);
+        assert!(get_artifact(&mut conn, "old.md").unwrap().is_none());
+
+        // The move is a revision of its own, by whoever moved it
+        let history = artifact_history(&mut conn, "new.md").unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].path, "new.md");
+        assert_eq!(history[0].produced_by, "mover");
+        assert_eq!(history[0].renamed_from.as_deref(), Some("old.md"));
+        assert_eq!(history[0].refs, refs);
+        assert_eq!(history[1].path, "old.md");
+        assert!(history[1].renamed_from.is_none());
+
+        let removed = remove_artifact(&mut conn, "cleaner", "new.md").unwrap();
+        assert_eq!(removed.produced_by, "reviewer");
+        assert!(get_artifact(&mut conn, "new.md").unwrap().is_none());
+        assert!(get_artifact(&mut conn, "other.md").unwrap().is_some());
+        assert!(matches!(
+            remove_artifact(&mut conn, "cleaner", "new.md"),
+            Err(BBError::NotFound(_))
+        ));
+
+        // Removal keeps the history and records who removed it
+        let history = artifact_history(&mut conn, "new.md").unwrap();
+        let producers: Vec<_> = history.iter().map(|r| r.produced_by.as_str()).collect();
+        assert_eq!(producers, ["cleaner", "mover", "reviewer", "writer"]);
+        assert!(history[0].removed);
+        assert_eq!(history[0].refs, refs);
+        assert!(history[1..].iter().all(|r| !r.removed));
+        assert!(matches!(
+            artifact_history(&mut conn, "never.md"),
+            Err(BBError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn test_register_artifact_traversal_fails() {
         let (mut conn, temp) = setup();
@@ -196,7 +961,8 @@ mod tests {
         )
         .unwrap();
 
-        let results = list_artifacts(&mut conn, Some("agent-1"), None, None, None, 10).unwrap();
+        let results =
+            list_artifacts(&mut conn, Some("agent-1"), None, None, None, None, 10).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].path, "src/main.rs");
     }
@@ -222,7 +988,207 @@ mod tests {
         let cleared = clear_artifacts(&mut conn).unwrap();
         assert_eq!(cleared, 1);
 
-        let remaining = list_artifacts(&mut conn, None, None, None, None, 10).unwrap();
+        let remaining = list_artifacts(&mut conn, None, None, None, None, None, 10).unwrap();
         assert!(remaining.is_empty());
     }
+
+    #[test]
+    fn test_register_records_contents() {
+        let (mut conn, temp) = setup();
+        fs::write(temp.path().join("notes.txt"), "hello").unwrap();
+
+        let artifact = register_artifact(
+            &mut conn,
+            "notes.txt",
+            "agent-1",
+            "Notes",
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            artifact.content_hash.as_deref(),
+            Some("sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        assert_eq!(artifact.size, Some(5));
+        assert!(artifact.mtime.is_some());
+    }
+
+    #[test]
+    fn test_check_artifacts() {
+        let (mut conn, temp) = setup();
+        for path in ["same.txt", "edited.txt", "gone.txt"] {
+            fs::write(temp.path().join(path), "v1").unwrap();
+            register_artifact(&mut conn, path, "agent-1", "", None, vec![], temp.path()).unwrap();
+        }
+        fs::create_dir(temp.path().join("dir")).unwrap();
+        register_artifact(&mut conn, "dir", "agent-2", "", None, vec![], temp.path()).unwrap();
+
+        fs::write(temp.path().join("edited.txt"), "v2").unwrap();
+        fs::remove_file(temp.path().join("gone.txt")).unwrap();
+
+        let checks =
+            check_artifacts(&mut conn, None, None, None, None, None, 50, temp.path()).unwrap();
+        let state = |path: &str| {
+            checks
+                .iter()
+                .find(|c| c.artifact.path == path)
+                .map(|c| c.state)
+                .unwrap()
+        };
+
+        assert_eq!(state("same.txt"), ArtifactState::Unchanged);
+        assert_eq!(state("edited.txt"), ArtifactState::Modified);
+        assert_eq!(state("gone.txt"), ArtifactState::Deleted);
+        assert_eq!(state("dir"), ArtifactState::Unknown);
+    }
+
+    #[test]
+    fn test_linked_artifacts_and_graph() {
+        let (mut conn, temp) = setup();
+        for path in ["schema.sql", "config.toml", "api.rs", "client.rs"] {
+            fs::write(temp.path().join(path), "v1").unwrap();
+        }
+        let register = |conn: &mut Connection, path: &str, on: &[&str], from: &[&str]| {
+            let on: Vec<String> = on.iter().map(|p| p.to_string()).collect();
+            let from: Vec<String> = from.iter().map(|p| p.to_string()).collect();
+            register_linked_artifact(
+                conn,
+                path,
+                "codegen",
+                "",
+                None,
+                vec![],
+                &on,
+                &from,
+                false,
+                temp.path(),
+            )
+        };
+        register(&mut conn, "schema.sql", &[], &[]).unwrap();
+        register(&mut conn, "config.toml", &[], &[]).unwrap();
+
+        // Upstream artifacts must be registered, and an artifact cannot link
+        // to itself; neither registers anything
+        assert!(register(&mut conn, "api.rs", &[], &["missing.sql"]).is_err());
+        assert!(register(&mut conn, "api.rs", &["./api.rs"], &[]).is_err());
+        assert!(get_artifact(&mut conn, "api.rs").unwrap().is_none());
+
+        let api = register(&mut conn, "api.rs", &["config.toml"], &["schema.sql"]).unwrap();
+        assert_eq!(api.depends_on, vec!["config.toml"]);
+        assert_eq!(api.derived_from, vec!["schema.sql"]);
+
+        // Registering without links keeps them
+        let api = register(&mut conn, "api.rs", &[], &[]).unwrap();
+        assert_eq!(api.derived_from, vec!["schema.sql"]);
+        register(&mut conn, "client.rs", &[], &["api.rs"]).unwrap();
+
+        let graph =
+            artifact_graph(&mut conn, "api.rs", GraphDirection::Both, None, temp.path()).unwrap();
+        let paths: Vec<_> = graph.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["api.rs", "config.toml", "schema.sql", "client.rs"]
+        );
+        assert_eq!(graph.edges.len(), 3);
+
+        let graph = artifact_graph(
+            &mut conn,
+            "schema.sql",
+            GraphDirection::Downstream,
+            Some(1),
+            temp.path(),
+        )
+        .unwrap();
+        let paths: Vec<_> = graph.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(paths, vec!["schema.sql", "api.rs"]);
+        assert!(graph.nodes.iter().all(|n| n.changed_upstream.is_empty()));
+
+        // Re-registering the schema, or editing the config on disk, makes
+        // api.rs stale; client.rs only depends on api.rs
+        register(&mut conn, "schema.sql", &[], &[]).unwrap();
+        let config = fs::File::options()
+            .write(true)
+            .open(temp.path().join("config.toml"))
+            .unwrap();
+        config.set_len(0).unwrap();
+        config
+            .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let checks =
+            check_artifacts(&mut conn, None, None, None, None, None, 50, temp.path()).unwrap();
+        let changed = |path: &str| {
+            checks
+                .iter()
+                .find(|c| c.artifact.path == path)
+                .map(|c| c.changed_upstream.clone())
+                .unwrap()
+        };
+        assert_eq!(changed("api.rs"), vec!["config.toml", "schema.sql"]);
+        assert!(changed("client.rs").is_empty());
+
+        let graph = artifact_graph(
+            &mut conn,
+            "client.rs",
+            GraphDirection::Upstream,
+            None,
+            temp.path(),
+        )
+        .unwrap();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes[1].path, "api.rs");
+        assert_eq!(graph.nodes[1].changed_upstream.len(), 2);
+    }
+
+    #[test]
+    fn test_link_changes_are_recorded() {
+        let (mut conn, temp) = setup();
+        for path in ["schema.sql", "config.toml", "api.rs"] {
+            fs::write(temp.path().join(path), "v1").unwrap();
+        }
+        let register = |conn: &mut Connection, path: &str, on: &[&str], clear: bool| {
+            let on: Vec<String> = on.iter().map(|p| p.to_string()).collect();
+            register_linked_artifact(
+                conn,
+                path,
+                "codegen",
+                "",
+                None,
+                vec![],
+                &on,
+                &[],
+                clear,
+                temp.path(),
+            )
+        };
+        register(&mut conn, "schema.sql", &[], false).unwrap();
+        register(&mut conn, "config.toml", &[], false).unwrap();
+        register(&mut conn, "api.rs", &["schema.sql", "config.toml"], false).unwrap();
+
+        // Removing an upstream artifact drops the links to it, and the
+        // artifacts that had them get a revision saying so
+        remove_artifact(&mut conn, "cleaner", "schema.sql").unwrap();
+        let api = get_artifact(&mut conn, "api.rs").unwrap().unwrap();
+        assert_eq!(api.depends_on, vec!["config.toml"]);
+        let history = artifact_history(&mut conn, "api.rs").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].produced_by, "cleaner");
+        assert!(!history[0].removed);
+        assert_eq!(history[0].depends_on, vec!["config.toml"]);
+        assert_eq!(history[1].depends_on, vec!["config.toml", "schema.sql"]);
+
+        // Clearing links cannot be mixed with declaring them
+        assert!(matches!(
+            register(&mut conn, "api.rs", &["config.toml"], true),
+            Err(BBError::InvalidField { .. })
+        ));
+        let api = register(&mut conn, "api.rs", &[], true).unwrap();
+        assert!(api.depends_on.is_empty());
+        let history = artifact_history(&mut conn, "api.rs").unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].depends_on.is_empty());
+    }
 }
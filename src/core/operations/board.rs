@@ -0,0 +1,224 @@
+use crate::core::config::LivenessConfig;
+use crate::core::errors::BBResult;
+use crate::core::operations::agent as agent_ops;
+use crate::db::queries::agent as agent_queries;
+use crate::db::queries::artifact as artifact_queries;
+use crate::db::queries::message as message_queries;
+use rusqlite::Connection;
+
+/// How far `changes_since` has seen the board.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardCursor {
+    message_id: i64,
+    agents: String,
+    artifacts_at: Option<String>,
+}
+
+/// Rows that appeared or changed since a `BoardCursor`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardChanges {
+    /// New messages as `(id, in_reply_to)`, oldest first.
+    pub messages: Vec<(i64, Option<i64>)>,
+    /// Every message in a thread that gained a reply. Any of them shows the
+    /// whole thread, so all of their thread views changed.
+    pub threads: Vec<i64>,
+    /// Whether any agent was added, removed, changed status or went offline.
+    pub agents: bool,
+    /// Paths of artifacts registered or updated.
+    pub artifacts: Vec<String>,
+}
+
+impl BoardChanges {
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty() && !self.agents && self.artifacts.is_empty()
+    }
+}
+
+/// Agents' stored state plus who is offline now, since liveness is derived
+/// and going offline writes nothing.
+fn agents_state(conn: &Connection, liveness: &LivenessConfig) -> BBResult<String> {
+    let offline: Vec<String> = agent_ops::offline_agents(conn, liveness)?
+        .into_iter()
+        .map(|a| a.id)
+        .collect();
+    Ok(format!(
+        "{}:{}",
+        agent_queries::agents_fingerprint(conn)?,
+        offline.join(",")
+    ))
+}
+
+/// A cursor at the current state of the board.
+pub fn board_cursor(conn: &mut Connection, liveness: &LivenessConfig) -> BBResult<BoardCursor> {
+    Ok(BoardCursor {
+        message_id: message_queries::max_message_id(conn)?,
+        agents: agents_state(conn, liveness)?,
+        artifacts_at: artifact_queries::list_artifact_updates_since(conn, None)?
+            .pop()
+            .map(|(_, at)| at),
+    })
+}
+
+/// Reports what changed since `cursor` and advances it past those changes.
+pub fn changes_since(
+    conn: &mut Connection,
+    cursor: &mut BoardCursor,
+    liveness: &LivenessConfig,
+) -> BBResult<BoardChanges> {
+    let messages = message_queries::list_message_ids_after(conn, cursor.message_id)?;
+    if let Some((id, _)) = messages.last() {
+        cursor.message_id = *id;
+    }
+
+    let mut roots = Vec::new();
+    for &(id, in_reply_to) in &messages {
+        if in_reply_to.is_none() {
+            continue;
+        }
+        if let Some(root) = message_queries::find_thread_root(conn, id)?
+            && !roots.contains(&root)
+        {
+            roots.push(root);
+        }
+    }
+    let mut threads = Vec::new();
+    for root in roots {
+        let tree = message_queries::list_thread(conn, root, None, 0, None)?;
+        threads.extend(tree.into_iter().map(|t| t.message.id));
+    }
+
+    let agents = agents_state(conn, liveness)?;
+    let agents_changed = agents != cursor.agents;
+    cursor.agents = agents;
+
+    let updates =
+        artifact_queries::list_artifact_updates_since(conn, cursor.artifacts_at.as_deref())?;
+    if let Some((_, at)) = updates.last() {
+        cursor.artifacts_at = Some(at.clone());
+    }
+
+    Ok(BoardChanges {
+        messages,
+        threads,
+        agents: agents_changed,
+        artifacts: updates.into_iter().map(|(path, _)| path).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::agent::AgentStatus;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::{agent, artifact, message};
+    use crate::db::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn post(conn: &mut Connection, content: &str, in_reply_to: Option<i64>) -> i64 {
+        message::post_message(
+            conn,
+            "agent-1",
+            content,
+            vec![],
+            Priority::Normal,
+            in_reply_to,
+            vec![],
+            vec![],
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_changes_since_reports_new_rows_once() {
+        let mut conn = setup();
+        post(&mut conn, "before", None);
+        let mut cursor = board_cursor(&mut conn, &LivenessConfig::default()).unwrap();
+        assert!(
+            changes_since(&mut conn, &mut cursor, &LivenessConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+
+        let root = post(&mut conn, "root", None);
+        let reply = post(&mut conn, "reply", Some(root));
+        let nested = post(&mut conn, "nested", Some(reply));
+        agent::update_agent_status(
+            &mut conn,
+            "agent-1",
+            None,
+            None,
+            Some(AgentStatus::Coding),
+            None,
+        )
+        .unwrap();
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.md"), "hi").unwrap();
+        artifact::register_artifact(
+            &mut conn,
+            "notes.md",
+            "agent-1",
+            "Notes",
+            None,
+            vec![],
+            dir.path(),
+        )
+        .unwrap();
+
+        let changes = changes_since(&mut conn, &mut cursor, &LivenessConfig::default()).unwrap();
+        assert_eq!(
+            changes.messages,
+            vec![(root, None), (reply, Some(root)), (nested, Some(reply))]
+        );
+        assert_eq!(changes.threads, vec![root, reply, nested]);
+        assert!(changes.agents);
+        assert_eq!(changes.artifacts, vec!["notes.md".to_string()]);
+
+        assert!(
+            changes_since(&mut conn, &mut cursor, &LivenessConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_touching_an_agent_is_not_a_change() {
+        let mut conn = setup();
+        agent::touch_agent(&mut conn, "agent-1").unwrap();
+        let mut cursor = board_cursor(&mut conn, &LivenessConfig::default()).unwrap();
+
+        agent::touch_agent(&mut conn, "agent-1").unwrap();
+        assert!(
+            !changes_since(&mut conn, &mut cursor, &LivenessConfig::default())
+                .unwrap()
+                .agents
+        );
+    }
+
+    #[test]
+    fn test_going_offline_is_a_change() {
+        let mut conn = setup();
+        agent::touch_agent(&mut conn, "agent-1").unwrap();
+        let mut cursor = board_cursor(&mut conn, &LivenessConfig::default()).unwrap();
+
+        conn.execute(
+            "UPDATE agents SET last_seen = ?1",
+            [(chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339()],
+        )
+        .unwrap();
+        let changes = changes_since(&mut conn, &mut cursor, &LivenessConfig::default()).unwrap();
+        assert!(changes.agents);
+        assert!(
+            !changes_since(&mut conn, &mut cursor, &LivenessConfig::default())
+                .unwrap()
+                .agents
+        );
+    }
+}
@@ -0,0 +1,430 @@
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::handoff::{Handoff, HandoffStatus};
+use crate::core::models::message::Priority;
+use crate::core::models::reference::Reference;
+use crate::core::operations::message as message_ops;
+use crate::core::validation::limits::{
+    limits, normalize_project_path, validate_agent_id, validate_handoff_escalation,
+    validate_handoff_response, validate_handoff_summary,
+};
+use crate::db::queries::artifact as artifact_queries;
+use crate::db::queries::handoff as handoff_queries;
+use crate::db::queries::handoff::HandoffFilter;
+use crate::db::queries::message as message_queries;
+use chrono::{Duration, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+
+/// How long a handoff may stay pending before it is escalated, unless the
+/// sender chooses another delay.
+pub const DEFAULT_ESCALATE_AFTER_MINS: i64 = 30;
+
+/// Tag on every message a handoff posts.
+pub const HANDOFF_TAG: &str = "handoff";
+
+/// Posts a message on `handoff`'s thread, addressed to `to`.
+fn post_to_thread(
+    conn: &Connection,
+    handoff: &Handoff,
+    from_agent: &str,
+    to: &str,
+    content: &str,
+    priority: Priority,
+) -> BBResult<i64> {
+    let message = message_ops::new_message(
+        conn,
+        from_agent,
+        content,
+        vec![HANDOFF_TAG.to_string()],
+        priority,
+        handoff.message_id,
+        vec![],
+        vec![to.to_string()],
+    )?;
+    message_queries::insert_message_in(conn, &message)
+}
+
+/// Escalates pending handoffs that are past their `escalate_at`: each is
+/// raised one priority level, once, and a reminder is posted to its
+/// recipient at the new priority.
+fn escalate_due(conn: &Connection) -> BBResult<()> {
+    let now = Utc::now();
+    for handoff in handoff_queries::list_handoffs_to_escalate(conn, now)? {
+        let priority = handoff.priority.escalated();
+        handoff_queries::mark_escalated(conn, handoff.id, priority)?;
+
+        let waited = now.signed_duration_since(handoff.created_at).num_minutes();
+        let content = format!(
+            "Handoff #{} to {} is still pending after {waited} min (escalated to {})",
+            handoff.id,
+            handoff.to_agent,
+            priority.as_str()
+        );
+        post_to_thread(
+            conn,
+            &handoff,
+            &handoff.from_agent,
+            &handoff.to_agent,
+            &content,
+            priority,
+        )?;
+    }
+    Ok(())
+}
+
+/// Hands work from `from_agent` to `to_agent`. The handoff starts pending
+/// and is announced by a message to the recipient; accepting, declining and
+/// escalating it all reply on that message's thread.
+#[allow(clippy::too_many_arguments)]
+pub fn create_handoff(
+    conn: &mut Connection,
+    from_agent: &str,
+    to_agent: &str,
+    summary: &str,
+    refs: Vec<Reference>,
+    artifacts: Vec<String>,
+    priority: Priority,
+    escalate_after: Duration,
+) -> BBResult<Handoff> {
+    validate_agent_id(from_agent)?;
+    validate_agent_id(to_agent).map_err(|e| match e {
+        BBError::InvalidField { reason, .. } => BBError::invalid_field("to", reason),
+        e => e,
+    })?;
+    if from_agent == to_agent {
+        return Err(BBError::invalid_field("to", "cannot hand off to yourself"));
+    }
+    validate_handoff_summary(summary)?;
+    validate_handoff_escalation(escalate_after)?;
+    let limits = limits();
+    if refs.len() > limits.max_refs_per_entity {
+        return Err(BBError::invalid_field(
+            "refs",
+            format!("too many refs (max {})", limits.max_refs_per_entity),
+        ));
+    }
+    if artifacts.len() > limits.max_refs_per_entity {
+        return Err(BBError::invalid_field(
+            "artifacts",
+            format!("too many artifacts (max {})", limits.max_refs_per_entity),
+        ));
+    }
+
+    // Artifacts must already be registered, so the recipient can find them
+    let mut paths: Vec<String> = Vec::new();
+    for path in artifacts {
+        let path = normalize_project_path(&path).map_err(|e| match e {
+            BBError::InvalidField { reason, .. } => BBError::invalid_field("artifacts", reason),
+            e => e,
+        })?;
+        if artifact_queries::get_artifact_by_path(conn, &path)?.is_none() {
+            return Err(BBError::NotFound(format!("artifact {path} not found")));
+        }
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    let now = Utc::now();
+    let mut handoff = Handoff {
+        id: 0,
+        from_agent: from_agent.to_string(),
+        to_agent: to_agent.to_string(),
+        summary: summary.to_string(),
+        refs,
+        artifacts: paths,
+        priority,
+        status: HandoffStatus::Pending,
+        message_id: None,
+        created_at: now,
+        escalate_at: now + escalate_after,
+        escalated: false,
+        responded_at: None,
+        response: None,
+    };
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    handoff.id = handoff_queries::insert_handoff(&tx, &handoff)?;
+
+    let mut content = format!("Handoff #{} to {to_agent}: {summary}", handoff.id);
+    if !handoff.artifacts.is_empty() {
+        content.push_str(&format!("\nArtifacts: {}", handoff.artifacts.join(", ")));
+    }
+    let message = message_ops::new_message(
+        &tx,
+        from_agent,
+        &content,
+        vec![HANDOFF_TAG.to_string()],
+        priority,
+        None,
+        handoff.refs.clone(),
+        vec![to_agent.to_string()],
+    )?;
+    let message_id = message_queries::insert_message_in(&tx, &message)?;
+    handoff_queries::set_handoff_message(&tx, handoff.id, message_id)?;
+    tx.commit()?;
+
+    handoff.message_id = Some(message_id);
+    Ok(handoff)
+}
+
+/// Accepts or declines a pending handoff addressed to `agent_id`, and
+/// replies to the sender on the handoff's thread.
+fn respond(
+    conn: &mut Connection,
+    agent_id: &str,
+    id: i64,
+    status: HandoffStatus,
+    response: Option<&str>,
+) -> BBResult<Handoff> {
+    validate_agent_id(agent_id)?;
+    let field = match status {
+        HandoffStatus::Declined => "reason",
+        _ => "note",
+    };
+    if let Some(response) = response {
+        validate_handoff_response(field, response)?;
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let handoff = handoff_queries::get_handoff(&tx, id)?
+        .ok_or_else(|| BBError::NotFound(format!("handoff {id} not found")))?;
+    if handoff.to_agent != agent_id {
+        return Err(BBError::invalid_field(
+            "id",
+            format!("handoff {id} is addressed to {}", handoff.to_agent),
+        ));
+    }
+    if handoff.status != HandoffStatus::Pending {
+        return Err(BBError::invalid_field(
+            "id",
+            format!("handoff {id} is already {}", handoff.status.as_str()),
+        ));
+    }
+
+    let now = Utc::now();
+    handoff_queries::respond_to_handoff(&tx, id, status, response, now)?;
+
+    let verb = match status {
+        HandoffStatus::Declined => "Declined",
+        _ => "Accepted",
+    };
+    let content = match response {
+        Some(response) => format!("{verb} handoff #{id}: {response}"),
+        None => format!("{verb} handoff #{id}"),
+    };
+    post_to_thread(
+        &tx,
+        &handoff,
+        agent_id,
+        &handoff.from_agent,
+        &content,
+        handoff.priority,
+    )?;
+    tx.commit()?;
+
+    Ok(Handoff {
+        status,
+        responded_at: Some(now),
+        response: response.map(String::from),
+        ..handoff
+    })
+}
+
+pub fn accept_handoff(
+    conn: &mut Connection,
+    agent_id: &str,
+    id: i64,
+    note: Option<&str>,
+) -> BBResult<Handoff> {
+    respond(conn, agent_id, id, HandoffStatus::Accepted, note)
+}
+
+pub fn decline_handoff(
+    conn: &mut Connection,
+    agent_id: &str,
+    id: i64,
+    reason: Option<&str>,
+) -> BBResult<Handoff> {
+    respond(conn, agent_id, id, HandoffStatus::Declined, reason)
+}
+
+/// Lists handoffs, newest first.
+pub fn list_handoffs(
+    conn: &Connection,
+    filter: &HandoffFilter,
+    limit: usize,
+) -> BBResult<Vec<Handoff>> {
+    handoff_queries::list_handoffs(conn, filter, limit)
+}
+
+/// Every pending handoff, newest first.
+pub fn pending_handoffs(conn: &Connection) -> BBResult<Vec<Handoff>> {
+    let filter = HandoffFilter {
+        status: Some(HandoffStatus::Pending),
+        ..Default::default()
+    };
+    list_handoffs(conn, &filter, 100)
+}
+
+/// Escalates overdue handoffs. Runs after each write that adds history and
+/// from the wait loop once a handoff falls due; reads never escalate.
+pub fn escalate_overdue(conn: &mut Connection) -> BBResult<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    escalate_due(&tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Message;
+    use crate::core::operations::artifact;
+    use crate::db::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn thread_of(conn: &mut Connection, message_id: i64) -> Vec<Message> {
+        message_ops::get_message_thread(conn, message_id, None, 0, None)
+            .unwrap()
+            .messages
+            .into_iter()
+            .map(|t| t.message)
+            .collect()
+    }
+
+    fn hand_off(conn: &mut Connection, escalate_after: Duration) -> Handoff {
+        create_handoff(
+            conn,
+            "agent-1",
+            "agent-2",
+            "Finish the parser",
+            vec![],
+            vec![],
+            Priority::Normal,
+            escalate_after,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_create_announces_to_recipient() {
+        let mut conn = setup();
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("parser.rs"), "fn main() {}").unwrap();
+        artifact::register_artifact(
+            &mut conn,
+            "parser.rs",
+            "agent-1",
+            "Parser",
+            None,
+            vec![],
+            dir.path(),
+        )
+        .unwrap();
+
+        let handoff = create_handoff(
+            &mut conn,
+            "agent-1",
+            "agent-2",
+            "Finish the parser",
+            vec![],
+            vec!["./parser.rs".to_string()],
+            Priority::Normal,
+            Duration::minutes(30),
+        )
+        .unwrap();
+        assert_eq!(handoff.artifacts, vec!["parser.rs".to_string()]);
+
+        let message = message_ops::get_message(&mut conn, handoff.message_id.unwrap()).unwrap();
+        assert_eq!(message.recipients, vec!["agent-2".to_string()]);
+        assert_eq!(message.tags, vec![HANDOFF_TAG.to_string()]);
+        assert!(
+            message
+                .content
+                .starts_with(&format!("Handoff #{}", handoff.id))
+        );
+    }
+
+    #[test]
+    fn test_create_rejects_unknown_artifact_and_self() {
+        let mut conn = setup();
+        let err = create_handoff(
+            &mut conn,
+            "agent-1",
+            "agent-2",
+            "Finish the parser",
+            vec![],
+            vec!["missing.rs".to_string()],
+            Priority::Normal,
+            Duration::minutes(30),
+        )
+        .unwrap_err();
+        assert!(matches!(err, BBError::NotFound(_)));
+
+        let err = create_handoff(
+            &mut conn,
+            "agent-1",
+            "agent-1",
+            "Finish the parser",
+            vec![],
+            vec![],
+            Priority::Normal,
+            Duration::minutes(30),
+        )
+        .unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { field, .. } if field == "to"));
+    }
+
+    #[test]
+    fn test_only_recipient_can_respond_once() {
+        let mut conn = setup();
+        let handoff = hand_off(&mut conn, Duration::minutes(30));
+
+        assert!(accept_handoff(&mut conn, "agent-3", handoff.id, None).is_err());
+        let declined =
+            decline_handoff(&mut conn, "agent-2", handoff.id, Some("out of time")).unwrap();
+        assert_eq!(declined.status, HandoffStatus::Declined);
+        assert!(accept_handoff(&mut conn, "agent-2", handoff.id, None).is_err());
+
+        let thread = thread_of(&mut conn, handoff.message_id.unwrap());
+        assert_eq!(thread.len(), 2);
+        assert_eq!(thread[1].from_agent, "agent-2");
+        assert_eq!(thread[1].recipients, vec!["agent-1".to_string()]);
+        assert_eq!(
+            thread[1].content,
+            format!("Declined handoff #{}: out of time", handoff.id)
+        );
+    }
+
+    #[test]
+    fn test_overdue_handoff_escalates_once() {
+        let mut conn = setup();
+        let handoff = hand_off(&mut conn, Duration::minutes(30));
+        let overdue = hand_off(&mut conn, Duration::milliseconds(1));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        // Listing is a plain read
+        let pending = pending_handoffs(&conn).unwrap();
+        assert!(pending.iter().all(|h| !h.escalated));
+
+        escalate_overdue(&mut conn).unwrap();
+        escalate_overdue(&mut conn).unwrap();
+        let pending = pending_handoffs(&conn).unwrap();
+        assert_eq!(pending.len(), 2);
+        let escalated = pending.iter().find(|h| h.id == overdue.id).unwrap();
+        assert!(escalated.escalated);
+        assert_eq!(escalated.priority, Priority::High);
+        let waiting = pending.iter().find(|h| h.id == handoff.id).unwrap();
+        assert_eq!(waiting.priority, Priority::Normal);
+
+        let thread = thread_of(&mut conn, overdue.message_id.unwrap());
+        assert_eq!(thread.len(), 2);
+        assert_eq!(thread[1].priority, Priority::High);
+    }
+}
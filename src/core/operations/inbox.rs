@@ -0,0 +1,168 @@
+use crate::core::errors::BBResult;
+use crate::core::models::message::Message;
+use crate::core::validation::limits::validate_agent_id;
+use crate::db::queries::message as message_queries;
+use crate::db::queries::message::MessageFilter;
+use crate::db::queries::read_cursor as cursor_queries;
+pub use crate::db::queries::read_cursor::UnreadSummary;
+use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InboxPage {
+    /// Unread messages, oldest first.
+    pub messages: Vec<Message>,
+    /// Unread messages left after this page.
+    pub remaining: i64,
+    /// The agent's read cursor after this call.
+    pub cursor: i64,
+}
+
+/// Messages the agent has not read yet. An agent's own posts are never unread.
+fn unread_filter(agent_id: &str, cursor: i64) -> MessageFilter {
+    MessageFilter {
+        after_id: Some(cursor),
+        exclude_from: Some(agent_id.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Returns the agent's oldest unread messages and, unless `peek` is set,
+/// advances its read cursor past them.
+pub fn read_inbox(
+    conn: &mut Connection,
+    agent_id: &str,
+    limit: usize,
+    peek: bool,
+) -> BBResult<InboxPage> {
+    validate_agent_id(agent_id)?;
+
+    let cursor = cursor_queries::get_cursor(conn, agent_id)?;
+    // Snapshot before listing so a message posted mid-call is never skipped
+    let max_id = message_queries::max_message_id(conn)?;
+    let filter = unread_filter(agent_id, cursor);
+    let messages = message_queries::list_messages_oldest_first(conn, &filter, limit)?;
+    let total = message_queries::count_messages(conn, &filter)?;
+    let remaining = total - messages.len() as i64;
+
+    if peek {
+        return Ok(InboxPage {
+            messages,
+            remaining,
+            cursor,
+        });
+    }
+
+    // With nothing left unread, skip past the agent's own posts as well
+    let last_returned = messages.last().map_or(cursor, |m| m.id);
+    let new_cursor = if remaining == 0 {
+        last_returned.max(max_id)
+    } else {
+        last_returned
+    };
+    if new_cursor > cursor {
+        cursor_queries::advance_cursor(conn, agent_id, new_cursor)?;
+    }
+
+    Ok(InboxPage {
+        messages,
+        remaining,
+        cursor: new_cursor.max(cursor),
+    })
+}
+
+pub fn unread_count(conn: &mut Connection, agent_id: &str) -> BBResult<i64> {
+    let cursor = cursor_queries::get_cursor(conn, agent_id)?;
+    message_queries::count_messages(conn, &unread_filter(agent_id, cursor))
+}
+
+pub fn unread_summary(conn: &mut Connection, agent_id: &str) -> BBResult<UnreadSummary> {
+    cursor_queries::unread_summary(conn, agent_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::message::post_message;
+    use crate::db::migrations::run_migrations;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn post(conn: &mut Connection, from: &str, content: &str) -> Message {
+        post_message(
+            conn,
+            from,
+            content,
+            vec![],
+            Priority::Normal,
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_read_inbox_advances_cursor() {
+        let mut conn = setup();
+        post(&mut conn, "agent-1", "first");
+        post(&mut conn, "agent-1", "second");
+
+        let page = read_inbox(&mut conn, "agent-2", 10, false).unwrap();
+        assert_eq!(page.messages.len(), 2);
+        assert_eq!(page.messages[0].content, "first");
+        assert_eq!(page.remaining, 0);
+
+        let page = read_inbox(&mut conn, "agent-2", 10, false).unwrap();
+        assert!(page.messages.is_empty());
+
+        post(&mut conn, "agent-1", "third");
+        let page = read_inbox(&mut conn, "agent-2", 10, false).unwrap();
+        assert_eq!(page.messages.len(), 1);
+        assert_eq!(page.messages[0].content, "third");
+    }
+
+    #[test]
+    fn test_read_inbox_pages_without_skipping() {
+        let mut conn = setup();
+        for i in 0..5 {
+            post(&mut conn, "agent-1", &format!("msg {i}"));
+        }
+
+        let page = read_inbox(&mut conn, "agent-2", 2, false).unwrap();
+        assert_eq!(page.messages[0].content, "msg 0");
+        assert_eq!(page.remaining, 3);
+
+        let page = read_inbox(&mut conn, "agent-2", 2, false).unwrap();
+        assert_eq!(page.messages[0].content, "msg 2");
+        assert_eq!(unread_count(&mut conn, "agent-2").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_read_inbox_peek_does_not_advance() {
+        let mut conn = setup();
+        post(&mut conn, "agent-1", "hello");
+
+        read_inbox(&mut conn, "agent-2", 10, true).unwrap();
+        assert_eq!(unread_count(&mut conn, "agent-2").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_own_messages_are_not_unread() {
+        let mut conn = setup();
+        post(&mut conn, "agent-1", "mine");
+        post(&mut conn, "agent-2", "theirs");
+
+        assert_eq!(unread_count(&mut conn, "agent-1").unwrap(), 1);
+
+        let page = read_inbox(&mut conn, "agent-1", 10, false).unwrap();
+        assert_eq!(page.messages.len(), 1);
+        assert_eq!(page.messages[0].content, "theirs");
+        assert_eq!(page.cursor, 2);
+    }
+}
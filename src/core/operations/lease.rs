@@ -0,0 +1,286 @@
+use crate::core::config::LivenessConfig;
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::agent::Liveness;
+use crate::core::models::lease::{Lease, LeaseMode};
+use crate::core::operations::agent as agent_ops;
+use crate::core::operations::classify_liveness;
+use crate::core::validation::limits::{
+    normalize_project_path, validate_agent_id, validate_lease_path, validate_lease_ttl,
+};
+use crate::db::queries::lease as lease_queries;
+use crate::util::glob::patterns_overlap;
+use chrono::{Duration, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+use std::path::Path;
+
+/// Drops leases that have expired or whose holder has gone offline, and
+/// returns the rest.
+fn live_leases(conn: &Connection, liveness: &LivenessConfig) -> BBResult<Vec<Lease>> {
+    lease_queries::delete_expired_leases(conn, Utc::now())?;
+
+    let mut live = Vec::new();
+    for (lease, last_seen) in lease_queries::list_leases_with_holder_last_seen(conn)? {
+        let offline = last_seen.is_none_or(|seen| {
+            classify_liveness(liveness, &lease.holder, seen) == Liveness::Offline
+        });
+        if offline {
+            lease_queries::delete_leases_by_holder(conn, &lease.holder)?;
+        } else {
+            live.push(lease);
+        }
+    }
+    Ok(live)
+}
+
+/// Takes (or renews) a lease on `path` for `holder`.
+///
+/// Fails with `LeaseConflict` if another agent holds an overlapping lease
+/// in a conflicting mode. Checking and inserting happen in one IMMEDIATE
+/// transaction, so two agents cannot both win the same path.
+pub fn claim_path(
+    conn: &mut Connection,
+    holder: &str,
+    path: &str,
+    mode: LeaseMode,
+    ttl: Duration,
+    project_root: &Path,
+    liveness: &LivenessConfig,
+) -> BBResult<Lease> {
+    validate_agent_id(holder)?;
+    validate_lease_ttl(ttl)?;
+    let path = validate_lease_path(path, project_root)?;
+
+    // Claiming is a sign of life, and keeps the lease from being pruned
+    agent_ops::touch_agent(conn, holder)?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+    if let Some(conflict) = live_leases(&tx, liveness)?.into_iter().find(|l| {
+        l.holder != holder && l.mode.conflicts_with(mode) && patterns_overlap(&l.path, &path)
+    }) {
+        return Err(BBError::LeaseConflict {
+            path: conflict.path,
+            holder: conflict.holder,
+            expires_at: conflict.expires_at,
+        });
+    }
+
+    let now = Utc::now();
+    let mut lease = Lease {
+        id: 0,
+        path,
+        holder: holder.to_string(),
+        mode,
+        acquired_at: now,
+        expires_at: now + ttl,
+    };
+    lease.id = lease_queries::upsert_lease(&tx, &lease)?;
+    tx.commit()?;
+
+    Ok(lease)
+}
+
+pub fn release_path(conn: &mut Connection, holder: &str, path: &str) -> BBResult<()> {
+    validate_agent_id(holder)?;
+    let path = normalize_project_path(path)?;
+
+    if lease_queries::delete_lease(conn, &path, holder)? == 0 {
+        return Err(BBError::NotFound(format!(
+            "no claim on {path} held by {holder}"
+        )));
+    }
+    Ok(())
+}
+
+/// Releases every lease `holder` has, returning how many there were.
+pub fn release_all(conn: &mut Connection, holder: &str) -> BBResult<usize> {
+    validate_agent_id(holder)?;
+    lease_queries::delete_leases_by_holder(conn, holder)
+}
+
+/// Lists live leases, optionally only those overlapping `path`.
+pub fn list_claims(
+    conn: &mut Connection,
+    path: Option<&str>,
+    liveness: &LivenessConfig,
+) -> BBResult<Vec<Lease>> {
+    let path = path.map(normalize_project_path).transpose()?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let leases = live_leases(&tx, liveness)?;
+    tx.commit()?;
+
+    Ok(match path {
+        Some(path) => leases
+            .into_iter()
+            .filter(|l| patterns_overlap(&l.path, &path))
+            .collect(),
+        None => leases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    fn setup() -> (Connection, TempDir) {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        (conn, TempDir::new().unwrap())
+    }
+
+    fn claim(
+        conn: &mut Connection,
+        root: &TempDir,
+        holder: &str,
+        path: &str,
+        mode: LeaseMode,
+    ) -> BBResult<Lease> {
+        claim_path(
+            conn,
+            holder,
+            path,
+            mode,
+            Duration::minutes(30),
+            root.path(),
+            &LivenessConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_exclusive_claim_conflicts() {
+        let (mut conn, root) = setup();
+        claim(
+            &mut conn,
+            &root,
+            "agent-1",
+            "src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+
+        let err = claim(&mut conn, &root, "agent-2", "src/*.rs", LeaseMode::Shared).unwrap_err();
+        match err {
+            BBError::LeaseConflict { path, holder, .. } => {
+                assert_eq!(path, "src/auth.rs");
+                assert_eq!(holder, "agent-1");
+            }
+            other => panic!("expected LeaseConflict, got {other:?}"),
+        }
+
+        // Unrelated paths and the holder's own renewals are fine
+        claim(
+            &mut conn,
+            &root,
+            "agent-2",
+            "tests/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+        claim(
+            &mut conn,
+            &root,
+            "agent-1",
+            "./src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+        assert_eq!(
+            list_claims(&mut conn, None, &LivenessConfig::default())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_shared_claims_coexist() {
+        let (mut conn, root) = setup();
+        claim(&mut conn, &root, "agent-1", "docs", LeaseMode::Shared).unwrap();
+        claim(
+            &mut conn,
+            &root,
+            "agent-2",
+            "docs/api.md",
+            LeaseMode::Shared,
+        )
+        .unwrap();
+
+        assert!(
+            claim(
+                &mut conn,
+                &root,
+                "agent-3",
+                "docs/api.md",
+                LeaseMode::Exclusive
+            )
+            .is_err()
+        );
+        assert_eq!(
+            list_claims(&mut conn, Some("docs/api.md"), &LivenessConfig::default())
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_release_path() {
+        let (mut conn, root) = setup();
+        claim(
+            &mut conn,
+            &root,
+            "agent-1",
+            "src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            release_path(&mut conn, "agent-2", "src/auth.rs"),
+            Err(BBError::NotFound(_))
+        ));
+        release_path(&mut conn, "agent-1", "src/auth.rs").unwrap();
+        claim(
+            &mut conn,
+            &root,
+            "agent-2",
+            "src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_offline_holder_loses_leases() {
+        let (mut conn, root) = setup();
+        claim(
+            &mut conn,
+            &root,
+            "agent-1",
+            "src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+
+        conn.execute(
+            "UPDATE agents SET last_seen = ?1 WHERE id = 'agent-1'",
+            [(Utc::now() - Duration::days(1)).to_rfc3339()],
+        )
+        .unwrap();
+
+        claim(
+            &mut conn,
+            &root,
+            "agent-2",
+            "src/auth.rs",
+            LeaseMode::Exclusive,
+        )
+        .unwrap();
+        let claims = list_claims(&mut conn, None, &LivenessConfig::default()).unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].holder, "agent-2");
+    }
+}
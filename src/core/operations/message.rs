@@ -1,12 +1,70 @@
 use crate::core::errors::{BBError, BBResult};
-use crate::core::models::message::{Message, Priority};
+use crate::core::models::message::{Message, Priority, Thread};
 use crate::core::models::reference::Reference;
-use crate::core::validation::limits::MAX_REFS_PER_ENTITY;
-use crate::core::validation::limits::{validate_message_content, validate_tags};
+use crate::core::operations::{handoff as handoff_ops, retention, tag};
+use crate::core::validation::limits::{
+    limits, validate_agent_id, validate_message_content, validate_tags, validate_wait_timeout,
+};
+use crate::db::connection::data_version;
+use crate::db::queries::handoff as handoff_queries;
 use crate::db::queries::message as message_queries;
-use chrono::{DateTime, Utc};
+use crate::db::queries::message::MessageFilter;
+use chrono::{DateTime, Duration, Utc};
 use rusqlite::Connection;
+use serde::Serialize;
+
+/// How often `wait_for_messages` checks the database for new commits.
+pub const WAIT_POLL_INTERVAL_MS: u64 = 250;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WaitOutcome {
+    /// Matching messages, oldest first.
+    pub messages: Vec<Message>,
+    pub timed_out: bool,
+    /// Highest message ID examined; pass it back as `after_id` to resume.
+    pub last_id: i64,
+}
+
+/// Extracts `@agent-id` mentions from message content, in order of first
+/// appearance. An `@` preceded by a word character (as in an email address)
+/// is not a mention, and trailing punctuation is dropped.
+pub fn parse_mentions(content: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = prev.is_none_or(|p| !(p.is_alphanumeric() || p == '_'));
+        prev = Some(c);
+        if c != '@' || !at_boundary {
+            continue;
+        }
+
+        let start = i + 1;
+        let mut end = start;
+        while let Some(&(j, n)) = chars.peek() {
+            if n.is_alphanumeric() || matches!(n, '-' | '_' | '.') {
+                end = j + n.len_utf8();
+                prev = Some(n);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let name = content[start..end].trim_end_matches(['.', '-']);
+        if !name.is_empty()
+            && validate_agent_id(name).is_ok()
+            && !mentions.iter().any(|m| m == name)
+        {
+            mentions.push(name.to_string());
+        }
+    }
 
+    mentions
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn post_message(
     conn: &mut Connection,
     from_agent: &str,
@@ -15,14 +73,67 @@ pub fn post_message(
     priority: Priority,
     in_reply_to: Option<i64>,
     refs: Vec<Reference>,
+    to: Vec<String>,
+) -> BBResult<Message> {
+    let mut message = new_message(
+        conn,
+        from_agent,
+        content,
+        tags,
+        priority,
+        in_reply_to,
+        refs,
+        to,
+    )?;
+    message.id = message_queries::insert_message(conn, &message)?;
+    retention::after_write(conn, message.created_at);
+    Ok(message)
+}
+
+/// Validates a message and resolves its recipients without storing it. The
+/// returned message has ID 0.
+#[allow(clippy::too_many_arguments)]
+pub fn new_message(
+    conn: &Connection,
+    from_agent: &str,
+    content: &str,
+    tags: Vec<String>,
+    priority: Priority,
+    in_reply_to: Option<i64>,
+    refs: Vec<Reference>,
+    to: Vec<String>,
 ) -> BBResult<Message> {
     validate_message_content(content)?;
     validate_tags(&tags)?;
+    let tags = tag::registry().canonicalize(tags)?;
+
+    let limits = limits();
+    if refs.len() > limits.max_refs_per_entity {
+        return Err(BBError::invalid_field(
+            "refs",
+            format!("too many refs (max {})", limits.max_refs_per_entity),
+        ));
+    }
 
-    if refs.len() > MAX_REFS_PER_ENTITY {
-        return Err(BBError::InvalidInput(format!(
-            "too many refs (max {MAX_REFS_PER_ENTITY})"
-        )));
+    // Explicit recipients first, then anyone @mentioned in the content
+    let mut recipients: Vec<String> = Vec::new();
+    for agent_id in to.into_iter().chain(parse_mentions(content)) {
+        validate_agent_id(&agent_id).map_err(|e| match e {
+            BBError::InvalidField { reason, .. } => BBError::invalid_field("to", reason),
+            e => e,
+        })?;
+        if !recipients.contains(&agent_id) {
+            recipients.push(agent_id);
+        }
+    }
+    if recipients.len() > limits.max_recipients_per_message {
+        return Err(BBError::invalid_field(
+            "to",
+            format!(
+                "too many recipients (max {})",
+                limits.max_recipients_per_message
+            ),
+        ));
     }
 
     // Verify in_reply_to exists if provided
@@ -32,57 +143,137 @@ pub fn post_message(
         return Err(BBError::NotFound(format!("message {reply_to} not found")));
     }
 
-    let message = Message {
+    Ok(Message {
         id: 0,
         from_agent: from_agent.to_string(),
+        recipients,
         content: content.to_string(),
         tags,
         priority,
         in_reply_to,
         refs,
         created_at: Utc::now(),
-    };
-
-    let id = message_queries::insert_message(conn, &message)?;
-
-    // Return the message with the new ID
-    let mut result = message;
-    result.id = id;
-    Ok(result)
+    })
 }
 
-pub fn get_message_thread(conn: &mut Connection, id: i64) -> BBResult<Vec<Message>> {
-    // First get the original message
-    let mut messages = Vec::new();
+pub fn get_message(conn: &mut Connection, id: i64) -> BBResult<Message> {
+    message_queries::get_message(conn, id)?
+        .ok_or_else(|| BBError::NotFound(format!("message {id} not found")))
+}
 
-    if let Some(msg) = message_queries::get_message(conn, id)? {
-        messages.push(msg);
+/// The whole thread `id` belongs to, from its root down through every
+/// reply, one page at a time. `max_depth` limits how far below the root
+/// replies are followed; a `limit` of `None` returns the rest of the tree.
+pub fn get_message_thread(
+    conn: &mut Connection,
+    id: i64,
+    max_depth: Option<usize>,
+    offset: usize,
+    limit: Option<usize>,
+) -> BBResult<Thread> {
+    let root_id = message_queries::find_thread_root(conn, id)?
+        .ok_or_else(|| BBError::NotFound(format!("message {id} not found")))?;
+
+    let messages = message_queries::list_thread(conn, root_id, max_depth, offset, limit)?;
+    let total = message_queries::count_thread(conn, root_id, max_depth)?;
+
+    Ok(Thread {
+        root_id,
+        has_more: offset + messages.len() < total,
+        messages,
+        total,
+    })
+}
 
-        // Get replies (capped at 50 total in the query)
-        let replies = message_queries::get_message_replies(conn, id)?;
-        messages.extend(replies);
+pub fn list_messages(
+    conn: &mut Connection,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<Vec<Message>> {
+    message_queries::list_messages(conn, &with_tag_spellings(filter), limit)
+}
 
-        Ok(messages)
-    } else {
-        Err(BBError::NotFound(format!("message {id} not found")))
+/// `filter` matching any spelling of its tags, aliases included.
+pub(crate) fn with_tag_spellings(filter: &MessageFilter) -> MessageFilter {
+    MessageFilter {
+        tags: tag::registry().spellings(&filter.tags),
+        ..filter.clone()
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn list_messages(
+/// Blocks until a message newer than `after_id` matches `filter`, or `timeout`
+/// elapses.
+///
+/// When `after_id` is `None` only messages posted after the call starts are
+/// considered. New rows are detected via `PRAGMA data_version`, so the filter
+/// query only runs after another connection has committed. Handoffs that fall
+/// due while waiting are escalated here, so their reminders wake waiters.
+pub fn wait_for_messages(
     conn: &mut Connection,
-    since: Option<DateTime<Utc>>,
-    tags: &[String],
-    from_agent: Option<&str>,
-    priority: Option<Priority>,
-    ref_where: Option<&str>,
-    ref_what: Option<&str>,
-    ref_ref: Option<&str>,
+    filter: &MessageFilter,
+    after_id: Option<i64>,
+    timeout: Duration,
     limit: usize,
-) -> BBResult<Vec<Message>> {
-    message_queries::list_messages(
-        conn, since, tags, from_agent, priority, ref_where, ref_what, ref_ref, limit,
-    )
+) -> BBResult<WaitOutcome> {
+    validate_wait_timeout(timeout)?;
+    let filter = &with_tag_spellings(filter);
+
+    let mut high_water = match after_id {
+        Some(id) => id,
+        None => message_queries::max_message_id(conn)?,
+    };
+    let deadline = std::time::Instant::now() + timeout.to_std().unwrap_or_default();
+    let poll = std::time::Duration::from_millis(WAIT_POLL_INTERVAL_MS);
+    let mut seen_version = None;
+    let mut next_escalation = None;
+
+    loop {
+        let version = data_version(conn)?;
+        if seen_version != Some(version) {
+            seen_version = Some(version);
+            next_escalation = handoff_queries::next_escalation_at(conn)?;
+
+            let max_id = message_queries::max_message_id(conn)?;
+            if max_id > high_water {
+                let scan = MessageFilter {
+                    after_id: Some(high_water),
+                    ..filter.clone()
+                };
+                let messages = message_queries::list_messages_oldest_first(conn, &scan, limit)?;
+                if !messages.is_empty() {
+                    // A truncated page must resume after the last returned row
+                    let last_id = if messages.len() >= limit.min(100) {
+                        messages.last().map_or(max_id, |m| m.id)
+                    } else {
+                        max_id
+                    };
+                    return Ok(WaitOutcome {
+                        messages,
+                        timed_out: false,
+                        last_id,
+                    });
+                }
+                high_water = max_id;
+            }
+        }
+
+        if next_escalation.is_some_and(|at| at <= Utc::now()) {
+            handoff_ops::escalate_overdue(conn)?;
+            // Our own commits leave data_version alone, so rescan by hand
+            seen_version = None;
+            continue;
+        }
+
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return Ok(WaitOutcome {
+                messages: Vec::new(),
+                timed_out: true,
+                last_id: high_water,
+            });
+        }
+        std::thread::sleep(poll.min(deadline - now));
+    }
 }
 
 pub fn delete_messages_before(conn: &mut Connection, before: DateTime<Utc>) -> BBResult<usize> {
@@ -101,6 +292,68 @@ mod tests {
         conn
     }
 
+    #[test]
+    fn test_parse_mentions() {
+        assert_eq!(
+            parse_mentions("@agent-1 and @agent_2, see @agent-1."),
+            vec!["agent-1", "agent_2"]
+        );
+        assert_eq!(parse_mentions("(@reviewer) please"), vec!["reviewer"]);
+        assert!(parse_mentions("mail me at dev@example.com").is_empty());
+        assert!(parse_mentions("just an @ sign").is_empty());
+    }
+
+    #[test]
+    fn test_post_message_survives_failed_upkeep() {
+        let mut conn = setup();
+        // Escalation after the write can no longer read its table
+        conn.execute_batch("DROP TABLE handoffs").unwrap();
+
+        let msg = post_message(
+            &mut conn,
+            "agent-1",
+            "stored anyway",
+            vec![],
+            Priority::Normal,
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let stored = message_queries::get_message(&conn, msg.id).unwrap();
+        assert_eq!(stored.map(|m| m.content).as_deref(), Some("stored anyway"));
+    }
+
+    #[test]
+    fn test_post_message_recipients() {
+        let mut conn = setup();
+
+        let msg = post_message(
+            &mut conn,
+            "agent-1",
+            "@agent-3 take a look",
+            vec![],
+            Priority::Normal,
+            None,
+            vec![],
+            vec!["agent-2".to_string(), "agent-3".to_string()],
+        )
+        .unwrap();
+        assert_eq!(msg.recipients, vec!["agent-2", "agent-3"]);
+
+        let result = post_message(
+            &mut conn,
+            "agent-1",
+            "hello",
+            vec![],
+            Priority::Normal,
+            None,
+            vec![],
+            vec![String::new()],
+        );
+        assert!(matches!(result, Err(BBError::InvalidField { field, .. }) if field == "to"));
+    }
+
     #[test]
     fn test_post_message() {
         let mut conn = setup();
@@ -113,6 +366,7 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -134,6 +388,7 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         );
 
         assert!(result.is_err());
@@ -151,6 +406,7 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -162,6 +418,7 @@ mod tests {
             Priority::Normal,
             Some(parent.id),
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -180,6 +437,7 @@ mod tests {
             Priority::Normal,
             Some(9999),
             vec![],
+            vec![],
         );
 
         assert!(matches!(result, Err(BBError::NotFound(_))));
@@ -197,6 +455,7 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -208,13 +467,34 @@ mod tests {
             Priority::Normal,
             Some(parent.id),
             vec![],
+            vec![],
         )
         .unwrap();
 
-        let thread = get_message_thread(&mut conn, parent.id).unwrap();
-        assert_eq!(thread.len(), 2);
-        assert_eq!(thread[0].id, parent.id);
-        assert_eq!(thread[1].id, reply.id);
+        let nested = post_message(
+            &mut conn,
+            "agent-1",
+            "Reply to reply",
+            vec![],
+            Priority::Normal,
+            Some(reply.id),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        // Any message in the thread brings back the whole tree
+        let thread = get_message_thread(&mut conn, nested.id, None, 0, None).unwrap();
+        assert_eq!(thread.root_id, parent.id);
+        assert_eq!(thread.total, 3);
+        assert!(!thread.has_more);
+        let ids: Vec<_> = thread.messages.iter().map(|t| t.message.id).collect();
+        assert_eq!(ids, vec![parent.id, reply.id, nested.id]);
+        assert_eq!(thread.messages[2].depth, 2);
+
+        let page = get_message_thread(&mut conn, parent.id, None, 0, Some(2)).unwrap();
+        assert_eq!(page.messages.len(), 2);
+        assert!(page.has_more);
     }
 
     #[test]
@@ -229,6 +509,7 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
@@ -240,24 +521,235 @@ mod tests {
             Priority::Normal,
             None,
             vec![],
+            vec![],
         )
         .unwrap();
 
-        let results = list_messages(
+        let filter = MessageFilter {
+            tags: vec!["decision".to_string()],
+            ..Default::default()
+        };
+        let results = list_messages(&mut conn, &filter, 10).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "Message 1");
+    }
+
+    #[test]
+    fn test_wait_for_messages_times_out() {
+        let mut conn = setup();
+
+        let outcome = wait_for_messages(
             &mut conn,
+            &MessageFilter::default(),
             None,
-            &["decision".to_string()],
-            None,
+            Duration::seconds(1),
+            10,
+        )
+        .unwrap();
+
+        assert!(outcome.timed_out);
+        assert!(outcome.messages.is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_messages_returns_existing_after_id() {
+        let mut conn = setup();
+
+        let question = post_message(
+            &mut conn,
+            "agent-1",
+            "Which schema?",
+            vec!["question".to_string()],
+            Priority::Normal,
             None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        post_message(
+            &mut conn,
+            "agent-3",
+            "Unrelated",
+            vec![],
+            Priority::Normal,
             None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let answer = post_message(
+            &mut conn,
+            "agent-2",
+            "Use v2",
+            vec![],
+            Priority::Normal,
+            Some(question.id),
+            vec![],
+            vec![],
+        )
+        .unwrap();
+
+        let filter = MessageFilter {
+            in_reply_to: Some(question.id),
+            ..Default::default()
+        };
+        let outcome = wait_for_messages(
+            &mut conn,
+            &filter,
+            Some(question.id),
+            Duration::seconds(1),
+            10,
+        )
+        .unwrap();
+
+        assert!(!outcome.timed_out);
+        assert_eq!(outcome.messages.len(), 1);
+        assert_eq!(outcome.messages[0].id, answer.id);
+        assert_eq!(outcome.last_id, answer.id);
+    }
+
+    #[test]
+    fn test_wait_for_messages_pages_oldest_first() {
+        let mut conn = setup();
+        let mut ids = Vec::new();
+        for i in 0..3 {
+            let message = post_message(
+                &mut conn,
+                "agent-1",
+                &format!("Update {i}"),
+                vec![],
+                Priority::Normal,
+                None,
+                vec![],
+                vec![],
+            )
+            .unwrap();
+            ids.push(message.id);
+        }
+
+        // A page cut short by the limit resumes after its last message, so
+        // the next wait returns the rest instead of skipping them
+        let filter = MessageFilter::default();
+        let first =
+            wait_for_messages(&mut conn, &filter, Some(0), Duration::seconds(1), 2).unwrap();
+        let first_ids: Vec<_> = first.messages.iter().map(|m| m.id).collect();
+        assert_eq!(first_ids, ids[..2]);
+        assert_eq!(first.last_id, ids[1]);
+
+        let rest = wait_for_messages(
+            &mut conn,
+            &filter,
+            Some(first.last_id),
+            Duration::seconds(1),
+            2,
+        )
+        .unwrap();
+        let rest_ids: Vec<_> = rest.messages.iter().map(|m| m.id).collect();
+        assert_eq!(rest_ids, ids[2..]);
+    }
+
+    #[test]
+    fn test_wait_for_messages_escalates_handoffs_that_fall_due() {
+        let mut conn = setup();
+        let handoff = handoff_ops::create_handoff(
+            &mut conn,
+            "agent-1",
+            "agent-2",
+            "Finish the parser",
+            vec![],
+            vec![],
+            Priority::Normal,
+            Duration::milliseconds(200),
+        )
+        .unwrap();
+
+        // Nothing else writes, so the wait itself must post the reminder
+        let filter = MessageFilter {
+            to: Some("agent-2".to_string()),
+            ..Default::default()
+        };
+        let outcome =
+            wait_for_messages(&mut conn, &filter, None, Duration::seconds(5), 10).unwrap();
+        assert!(!outcome.timed_out);
+        assert_eq!(outcome.messages.len(), 1);
+        assert_eq!(outcome.messages[0].in_reply_to, handoff.message_id);
+        assert_eq!(outcome.messages[0].priority, Priority::High);
+    }
+
+    #[test]
+    fn test_wait_for_messages_mention_matches_whole_agent_id() {
+        let mut conn = setup();
+        post_message(
+            &mut conn,
+            "agent-2",
+            "@agent-10 can you take this?",
+            vec![],
+            Priority::Normal,
             None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let mention = post_message(
+            &mut conn,
+            "agent-2",
+            "@agent-1, and you this one",
+            vec![],
+            Priority::Normal,
             None,
-            10,
+            vec![],
+            vec![],
         )
         .unwrap();
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].content, "Message 1");
+        // `to` is what `--mention` filters on: agent-1 is not mentioned by
+        // "@agent-10"
+        let filter = MessageFilter {
+            to: Some("agent-1".to_string()),
+            ..Default::default()
+        };
+        let outcome =
+            wait_for_messages(&mut conn, &filter, Some(0), Duration::seconds(1), 10).unwrap();
+        let ids: Vec<_> = outcome.messages.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![mention.id]);
+    }
+
+    #[test]
+    fn test_wait_for_messages_sees_other_connection() {
+        let temp = tempfile::TempDir::new().unwrap();
+        crate::cli::commands::init::run(temp.path(), None).unwrap();
+        let project_dir = temp.path().to_path_buf();
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            crate::db::connection::with_connection(&project_dir, |conn| {
+                post_message(
+                    conn,
+                    "agent-2",
+                    "Blocked on review",
+                    vec!["blocker".to_string()],
+                    Priority::High,
+                    None,
+                    vec![],
+                    vec![],
+                )
+            })
+            .unwrap();
+        });
+
+        let filter = MessageFilter {
+            priority: Some(Priority::High),
+            ..Default::default()
+        };
+        let outcome = crate::db::connection::with_connection(temp.path(), |conn| {
+            wait_for_messages(conn, &filter, None, Duration::seconds(10), 10)
+        })
+        .unwrap();
+        writer.join().unwrap();
+
+        assert!(!outcome.timed_out);
+        assert_eq!(outcome.messages[0].content, "Blocked on review");
     }
 
     #[test]
@@ -268,6 +760,7 @@ mod tests {
         let old_msg = Message {
             id: 0,
             from_agent: "agent-1".to_string(),
+            recipients: vec![],
             content: "Old message".to_string(),
             tags: vec![],
             priority: Priority::Normal,
@@ -1,6 +1,14 @@
 pub mod agent;
 pub mod artifact;
+pub mod board;
+pub mod handoff;
+pub mod inbox;
+pub mod lease;
 pub mod message;
+pub mod question;
 pub mod reference;
+pub mod retention;
+pub mod search;
+pub mod tag;
 
 pub use agent::classify_liveness;
@@ -0,0 +1,183 @@
+use crate::core::config::QuestionsConfig;
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::question::{Question, QuestionState};
+use crate::core::validation::limits::validate_agent_id;
+use crate::db::queries::message as message_queries;
+use crate::db::queries::question as question_queries;
+use crate::db::queries::question::QuestionFilter;
+use chrono::{Duration, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+
+/// Tag that makes a message a tracked question.
+pub const QUESTION_TAG: &str = "question";
+
+/// Loads question `id` for its asker to resolve, failing if `agent_id` did
+/// not ask it or it is already closed.
+fn question_for_asker(conn: &Connection, agent_id: &str, id: i64) -> BBResult<Question> {
+    let question = match question_queries::get_question(conn, id)? {
+        Some(question) => question,
+        None if message_queries::get_message(conn, id)?.is_some() => {
+            return Err(BBError::invalid_field(
+                "id",
+                format!("message {id} is not tagged {QUESTION_TAG}"),
+            ));
+        }
+        None => return Err(BBError::NotFound(format!("message {id} not found"))),
+    };
+
+    if question.message.from_agent != agent_id {
+        return Err(BBError::invalid_field(
+            "id",
+            format!("question {id} was asked by {}", question.message.from_agent),
+        ));
+    }
+    if question.state == QuestionState::Closed {
+        return Err(BBError::invalid_field(
+            "id",
+            format!("question {id} is closed"),
+        ));
+    }
+    Ok(question)
+}
+
+/// Marks `answer_id`, a direct reply to question `id`, as its accepted
+/// answer. Only the asker may accept an answer; accepting another reply
+/// later replaces the first.
+pub fn accept_answer(
+    conn: &mut Connection,
+    agent_id: &str,
+    id: i64,
+    answer_id: i64,
+) -> BBResult<Question> {
+    validate_agent_id(agent_id)?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    question_for_asker(&tx, agent_id, id)?;
+    let answer = message_queries::get_message(&tx, answer_id)?
+        .ok_or_else(|| BBError::NotFound(format!("message {answer_id} not found")))?;
+    if answer.in_reply_to != Some(id) {
+        return Err(BBError::invalid_field(
+            "accept",
+            format!("message {answer_id} is not a reply to question {id}"),
+        ));
+    }
+
+    question_queries::resolve_question(
+        &tx,
+        id,
+        QuestionState::Answered,
+        Some(answer_id),
+        Utc::now(),
+    )?;
+    let question = question_queries::get_question(&tx, id)?
+        .ok_or_else(|| BBError::NotFound(format!("message {id} not found")))?;
+    tx.commit()?;
+
+    Ok(question)
+}
+
+/// Closes question `id`, answered or not. Only the asker may close it.
+pub fn close_question(conn: &mut Connection, agent_id: &str, id: i64) -> BBResult<Question> {
+    validate_agent_id(agent_id)?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    question_for_asker(&tx, agent_id, id)?;
+    question_queries::resolve_question(&tx, id, QuestionState::Closed, None, Utc::now())?;
+    let question = question_queries::get_question(&tx, id)?
+        .ok_or_else(|| BBError::NotFound(format!("message {id} not found")))?;
+    tx.commit()?;
+
+    Ok(question)
+}
+
+/// Lists questions, oldest first.
+pub fn list_questions(
+    conn: &mut Connection,
+    filter: &QuestionFilter,
+    limit: usize,
+) -> BBResult<Vec<Question>> {
+    question_queries::list_questions(conn, filter, limit.min(100))
+}
+
+/// Number of questions still open after `config.stale_minutes`.
+pub fn stale_question_count(conn: &mut Connection, config: &QuestionsConfig) -> BBResult<i64> {
+    let filter = QuestionFilter {
+        state: Some(QuestionState::Open),
+        before: Some(Utc::now() - Duration::minutes(config.stale_minutes)),
+        ..Default::default()
+    };
+    question_queries::count_questions(conn, &filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::message as message_ops;
+    use crate::db::migrations::run_migrations;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn post(conn: &mut Connection, from: &str, tags: &[&str], in_reply_to: Option<i64>) -> i64 {
+        message_ops::post_message(
+            conn,
+            from,
+            "Which port does the API use?",
+            tags.iter().map(|s| s.to_string()).collect(),
+            Priority::Normal,
+            in_reply_to,
+            vec![],
+            vec![],
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_asker_accepts_a_reply() {
+        let mut conn = setup();
+        let question = post(&mut conn, "agent-1", &[QUESTION_TAG], None);
+        let reply = post(&mut conn, "agent-2", &[], Some(question));
+        let unrelated = post(&mut conn, "agent-2", &[], None);
+
+        let err = accept_answer(&mut conn, "agent-2", question, reply).unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { field, .. } if field == "id"));
+        let err = accept_answer(&mut conn, "agent-1", question, unrelated).unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { field, .. } if field == "accept"));
+        let err = accept_answer(&mut conn, "agent-1", unrelated, reply).unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { field, .. } if field == "id"));
+
+        let answered = accept_answer(&mut conn, "agent-1", question, reply).unwrap();
+        assert_eq!(answered.state, QuestionState::Answered);
+        assert_eq!(answered.answer_id, Some(reply));
+
+        let closed = close_question(&mut conn, "agent-1", question).unwrap();
+        assert_eq!(closed.state, QuestionState::Closed);
+        assert!(close_question(&mut conn, "agent-1", question).is_err());
+    }
+
+    #[test]
+    fn test_stale_question_count() {
+        let mut conn = setup();
+        let old = post(&mut conn, "agent-1", &[QUESTION_TAG], None);
+        post(&mut conn, "agent-1", &[QUESTION_TAG], None);
+        conn.execute(
+            "UPDATE messages SET created_at = ?2 WHERE id = ?1",
+            rusqlite::params![old, (Utc::now() - Duration::hours(2)).to_rfc3339()],
+        )
+        .unwrap();
+
+        let hour = QuestionsConfig::default();
+        let day = QuestionsConfig {
+            stale_minutes: 24 * 60,
+        };
+        assert_eq!(stale_question_count(&mut conn, &hour).unwrap(), 1);
+        assert_eq!(stale_question_count(&mut conn, &day).unwrap(), 0);
+        close_question(&mut conn, "agent-1", old).unwrap();
+        assert_eq!(stale_question_count(&mut conn, &hour).unwrap(), 0);
+    }
+}
@@ -52,6 +52,7 @@ mod tests {
         let msg = Message {
             id: 0,
             from_agent: "agent-1".to_string(),
+            recipients: vec![],
             content: "Test message".to_string(),
             tags: vec![],
             priority: Priority::Normal,
@@ -77,9 +78,16 @@ mod tests {
                 what: "task".to_string(),
                 ref_: serde_json::json!(13),
             }],
-            created_at: chrono::Utc::now(),
+            first_registered_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            content_hash: None,
+            size: None,
+            mtime: None,
+            git: None,
+            depends_on: vec![],
+            derived_from: vec![],
         };
-        crate::db::queries::artifact::upsert_artifact(&mut conn, &artifact).unwrap();
+        crate::db::queries::artifact::upsert_artifact(&mut conn, &artifact, None).unwrap();
 
         let results = find_references(&mut conn, "tt", "task", &json!(13)).unwrap();
 
@@ -0,0 +1,138 @@
+use crate::core::config::{self, RetentionConfig};
+use crate::core::errors::BBResult;
+use crate::core::operations::handoff as handoff_ops;
+use crate::db::queries::agent as agent_queries;
+use crate::db::queries::message as message_queries;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+
+/// What one `enforce` pass deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Pruned {
+    pub messages: usize,
+    pub status_events: usize,
+}
+
+/// Deletes history older than the board's retention policy allows, as of
+/// `now`. Runs after each write that adds history, so the board never holds
+/// much more than the policy keeps.
+pub fn enforce(
+    conn: &mut Connection,
+    policy: &RetentionConfig,
+    now: DateTime<Utc>,
+) -> BBResult<Pruned> {
+    let messages_cutoff = policy.messages_cutoff(now);
+    let status_events_cutoff = policy.status_events_cutoff(now);
+    if messages_cutoff.is_none() && status_events_cutoff.is_none() {
+        return Ok(Pruned::default());
+    }
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut pruned = Pruned::default();
+    if let Some(before) = messages_cutoff {
+        pruned.messages = message_queries::delete_threads_before(&tx, before)?;
+    }
+    if let Some(before) = status_events_cutoff {
+        pruned.status_events = agent_queries::delete_status_events_before(&tx, before)?;
+    }
+    tx.commit()?;
+    Ok(pruned)
+}
+
+/// Upkeep that follows a committed write: prunes history by the board's
+/// policy and escalates overdue handoffs. The write already stands, so a
+/// failure here is logged and left for the next write to retry.
+pub fn after_write(conn: &mut Connection, now: DateTime<Utc>) {
+    if let Err(e) = enforce(conn, &config::current().retention, now) {
+        tracing::warn!("Cannot apply retention policy: {}", e);
+    }
+    if let Err(e) = handoff_ops::escalate_overdue(conn) {
+        tracing::warn!("Cannot escalate overdue handoffs: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::agent as agent_ops;
+    use crate::core::operations::message as message_ops;
+    use crate::db::migrations::run_migrations;
+    use chrono::Duration;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn post(conn: &mut Connection, content: &str, reply_to: Option<i64>) -> i64 {
+        message_ops::post_message(
+            conn,
+            "agent-1",
+            content,
+            vec![],
+            Priority::Normal,
+            reply_to,
+            vec![],
+            vec![],
+        )
+        .unwrap()
+        .id
+    }
+
+    #[test]
+    fn test_no_policy_keeps_everything() {
+        let mut conn = setup();
+        post(&mut conn, "hello", None);
+
+        let later = Utc::now() + Duration::days(3650);
+        let pruned = enforce(&mut conn, &RetentionConfig::default(), later).unwrap();
+        assert_eq!(pruned, Pruned::default());
+    }
+
+    #[test]
+    fn test_threads_are_kept_while_active() {
+        let mut conn = setup();
+        let old = post(&mut conn, "old thread", None);
+        post(&mut conn, "reply", Some(old));
+        let quiet = post(&mut conn, "quiet thread", None);
+        conn.execute(
+            "UPDATE messages SET created_at = ?1 WHERE id IN (?2, ?3)",
+            rusqlite::params![(Utc::now() - Duration::days(10)).to_rfc3339(), old, quiet],
+        )
+        .unwrap();
+
+        let policy = RetentionConfig {
+            messages: Some("7d".to_string()),
+            status_events: None,
+        };
+        let pruned = enforce(&mut conn, &policy, Utc::now()).unwrap();
+
+        // The old thread has a recent reply, so only the quiet one goes
+        assert_eq!(pruned.messages, 1);
+        assert!(message_ops::get_message(&mut conn, old).is_ok());
+        assert!(message_ops::get_message(&mut conn, quiet).is_err());
+    }
+
+    #[test]
+    fn test_latest_status_event_is_kept() {
+        let mut conn = setup();
+        agent_ops::update_agent_status(&mut conn, "agent-1", Some("a"), None, None, None).unwrap();
+        agent_ops::update_agent_status(&mut conn, "agent-1", Some("b"), None, None, None).unwrap();
+
+        let policy = RetentionConfig {
+            messages: None,
+            status_events: Some("1h".to_string()),
+        };
+        let later = Utc::now() + Duration::days(1);
+        let pruned = enforce(&mut conn, &policy, later).unwrap();
+        assert_eq!(pruned.status_events, 1);
+
+        let events = agent_queries::list_status_events(&conn, Some("agent-1"), None).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].current_task, "b");
+    }
+}
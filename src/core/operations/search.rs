@@ -0,0 +1,190 @@
+use crate::core::errors::{BBError, BBResult};
+use crate::core::operations::message as message_ops;
+use crate::core::validation::limits::limits;
+use crate::db::queries::message::MessageFilter;
+use crate::db::queries::search as search_queries;
+use crate::db::queries::search::{ArtifactHit, MessageHit};
+use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub messages: Vec<MessageHit>,
+    pub artifacts: Vec<ArtifactHit>,
+}
+
+/// Turns plain search text into an FTS5 query matching every word in it.
+/// Each word is quoted as a phrase, so punctuation such as the `-` in
+/// `agent-1` is matched rather than parsed; a trailing `*` still makes a
+/// prefix search.
+pub fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) if !stem.is_empty() => (stem, "*"),
+                _ => (word, ""),
+            };
+            format!("\"{}\"{prefix}", word.replace('"', "\"\""))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Searches message content and artifact descriptions for every word in
+/// `query`. With `raw`, `query` is FTS5 syntax instead (`"phrases"`,
+/// `prefix*`, `AND`/`OR`/`NOT`).
+///
+/// Artifacts have no tags, priority, thread or recipients, so they are left
+/// out whenever one of those message-only filters is set.
+pub fn search(
+    conn: &mut Connection,
+    query: &str,
+    raw: bool,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<SearchResults> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(BBError::invalid_field(
+            "query",
+            "search query cannot be empty",
+        ));
+    }
+    let limits = limits();
+    if query.len() > limits.max_search_query_len {
+        return Err(BBError::invalid_field(
+            "query",
+            format!(
+                "search query too long (max {} chars)",
+                limits.max_search_query_len
+            ),
+        ));
+    }
+
+    let query = &if raw {
+        query.to_string()
+    } else {
+        fts_query(query)
+    };
+    let filter = &message_ops::with_tag_spellings(filter);
+    let messages = search_queries::search_messages(conn, query, filter, limit)?;
+
+    let message_only = !filter.tags.is_empty()
+        || filter.priority.is_some()
+        || filter.in_reply_to.is_some()
+        || filter.to.is_some();
+    let artifacts = if message_only {
+        Vec::new()
+    } else {
+        search_queries::search_artifacts(conn, query, filter, limit)?
+    };
+
+    Ok(SearchResults {
+        messages,
+        artifacts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::artifact::register_artifact;
+    use crate::core::operations::message::post_message;
+    use crate::db::migrations::run_migrations;
+    use tempfile::TempDir;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_search_messages_and_artifacts() {
+        let mut conn = setup();
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("schema.sql"), "").unwrap();
+
+        post_message(
+            &mut conn,
+            "agent-1",
+            "Decision: migrations live in one schema file",
+            vec!["decision".to_string()],
+            Priority::Normal,
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        register_artifact(
+            &mut conn,
+            "schema.sql",
+            "agent-2",
+            "Database schema",
+            None,
+            vec![],
+            temp.path(),
+        )
+        .unwrap();
+
+        let results = search(&mut conn, "schema", false, &MessageFilter::default(), 10).unwrap();
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.artifacts.len(), 1);
+
+        let filter = MessageFilter {
+            tags: vec!["decision".to_string()],
+            ..Default::default()
+        };
+        let results = search(&mut conn, "schema", false, &filter, 10).unwrap();
+        assert_eq!(results.messages.len(), 1);
+        assert!(results.artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_search_rejects_empty_query() {
+        let mut conn = setup();
+        assert!(search(&mut conn, "  ", false, &MessageFilter::default(), 10).is_err());
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("auth-service"), "\"auth-service\"");
+        assert_eq!(fts_query(" foo\"  token* "), "\"foo\"\"\" \"token\"*");
+        assert_eq!(fts_query("*"), "\"*\"");
+    }
+
+    #[test]
+    fn test_plain_queries_match_punctuated_words() {
+        let mut conn = setup();
+        post_message(
+            &mut conn,
+            "agent-1",
+            "auth-service is down, ask agent-2 about \"foo\"",
+            vec![],
+            Priority::Normal,
+            None,
+            vec![],
+            vec![],
+        )
+        .unwrap();
+        let filter = MessageFilter::default();
+
+        for query in ["auth-service", "agent-2", "foo\"", "\"foo", "auth-serv*"] {
+            let results = search(&mut conn, query, false, &filter, 10).unwrap();
+            assert_eq!(results.messages.len(), 1, "{query}");
+        }
+        assert!(
+            search(&mut conn, "auth-service agent-3", false, &filter, 10)
+                .unwrap()
+                .messages
+                .is_empty()
+        );
+
+        // Raw queries are FTS5 syntax, and are rejected when malformed
+        let results = search(&mut conn, "auth OR nothing", true, &filter, 10).unwrap();
+        assert_eq!(results.messages.len(), 1);
+        assert!(search(&mut conn, "auth-service", true, &filter, 10).is_err());
+        assert!(search(&mut conn, "foo\"", true, &filter, 10).is_err());
+    }
+}
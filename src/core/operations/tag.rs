@@ -0,0 +1,317 @@
+use crate::core::config::{self, TagsConfig};
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::tag::{Tag, TagSource};
+use crate::core::operations::handoff::HANDOFF_TAG;
+use crate::core::operations::question::QUESTION_TAG;
+use crate::db::queries::message as message_queries;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// The conventional tags from SPEC §4.2, with their descriptions and
+/// aliases. Every board has them unless its config redefines one.
+pub const STANDARD_TAGS: &[(&str, &str, &[&str])] = &[
+    ("decision", "A choice that was made, and why", &["decided"]),
+    (
+        HANDOFF_TAG,
+        "Work passed to another agent (added by `bb handoff`)",
+        &[],
+    ),
+    (
+        "blocker",
+        "Something is stopping progress",
+        &["blocked", "blocking"],
+    ),
+    (
+        QUESTION_TAG,
+        "Needs an answer; tracked until answered or closed",
+        &[],
+    ),
+    ("plan", "What an agent is about to do", &["planning"]),
+    ("review", "Asks for, or gives, a review", &["reviewing"]),
+    ("info", "Worth knowing; no action needed", &["fyi"]),
+];
+
+#[derive(Debug, Clone)]
+struct Definition {
+    description: String,
+    aliases: Vec<String>,
+    source: TagSource,
+}
+
+/// The standard tags plus the board's own, and the aliases of both.
+#[derive(Debug, Clone)]
+pub struct TagRegistry {
+    strict: bool,
+    tags: BTreeMap<String, Definition>,
+    aliases: BTreeMap<String, String>,
+}
+
+static REGISTRY: OnceLock<TagRegistry> = OnceLock::new();
+
+/// The registry for the config installed at startup.
+pub fn registry() -> &'static TagRegistry {
+    REGISTRY.get_or_init(|| {
+        TagRegistry::new(&config::current().tags)
+            .expect("tags are checked when the config is loaded")
+    })
+}
+
+impl TagRegistry {
+    /// Builds the registry for `config`, or says why its tags are invalid.
+    pub fn new(config: &TagsConfig) -> Result<Self, String> {
+        let mut tags: BTreeMap<String, Definition> = STANDARD_TAGS
+            .iter()
+            .map(|(name, description, aliases)| {
+                let definition = Definition {
+                    description: description.to_string(),
+                    aliases: aliases.iter().map(|a| a.to_string()).collect(),
+                    source: TagSource::Standard,
+                };
+                (name.to_string(), definition)
+            })
+            .collect();
+        for (name, definition) in &config.defined {
+            let definition = Definition {
+                description: definition.description.clone(),
+                aliases: definition.aliases.clone(),
+                source: TagSource::Board,
+            };
+            tags.insert(name.clone(), definition);
+        }
+
+        let mut aliases = BTreeMap::new();
+        for (name, definition) in &tags {
+            check_name(name)?;
+            for alias in &definition.aliases {
+                check_name(alias)?;
+                if tags.contains_key(alias) {
+                    return Err(format!("tag alias {alias} is also a tag"));
+                }
+                if let Some(other) = aliases.insert(alias.clone(), name.clone()) {
+                    return Err(format!(
+                        "tag alias {alias} is used by both {other} and {name}"
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            strict: config.strict,
+            tags,
+            aliases,
+        })
+    }
+
+    /// The tag `tag` stands for: itself if it is defined, the tag it is an
+    /// alias of, or `None` if the board does not know it.
+    pub fn resolve(&self, tag: &str) -> Option<&str> {
+        if let Some((name, _)) = self.tags.get_key_value(tag) {
+            return Some(name);
+        }
+        self.aliases.get(tag).map(String::as_str)
+    }
+
+    /// Replaces aliases with the tags they stand for and drops duplicates.
+    /// In strict mode a tag the board does not know is an error.
+    pub fn canonicalize(&self, tags: Vec<String>) -> BBResult<Vec<String>> {
+        let mut canonical: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = match self.resolve(&tag) {
+                Some(name) => name.to_string(),
+                None if self.strict => {
+                    return Err(BBError::invalid_field(
+                        "tags",
+                        format!(
+                            "unknown tag: {tag} (this board only allows defined tags; see `bb tags`)"
+                        ),
+                    ));
+                }
+                None => tag,
+            };
+            if !canonical.contains(&tag) {
+                canonical.push(tag);
+            }
+        }
+        Ok(canonical)
+    }
+
+    /// Every spelling that `tags` could have been stored under, for filters:
+    /// each tag, the tag it stands for, and that tag's aliases. Messages
+    /// posted before an alias was defined still match.
+    pub fn spellings(&self, tags: &[String]) -> Vec<String> {
+        let mut spellings: Vec<String> = Vec::new();
+        let mut add = |tag: &str| {
+            if !spellings.iter().any(|t| t == tag) {
+                spellings.push(tag.to_string());
+            }
+        };
+        for tag in tags {
+            add(tag);
+            if let Some(name) = self.resolve(tag) {
+                add(name);
+                for alias in &self.tags[name].aliases {
+                    add(alias);
+                }
+            }
+        }
+        spellings
+    }
+}
+
+fn check_name(tag: &str) -> Result<(), String> {
+    if tag.is_empty() || tag.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return Err(format!("invalid tag name: {tag:?}"));
+    }
+    Ok(())
+}
+
+/// The board's tags, as listed by `bb tags` and `list_tags`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TagList {
+    /// Whether posting a tag that is not defined is rejected.
+    pub strict: bool,
+    pub tags: Vec<Tag>,
+}
+
+/// Every defined tag and every tag in use, most used first. Uses of an
+/// alias count towards the tag it stands for.
+pub fn list_tags(conn: &Connection, registry: &TagRegistry) -> BBResult<TagList> {
+    let mut tags: BTreeMap<String, Tag> = registry
+        .tags
+        .iter()
+        .map(|(name, definition)| {
+            let tag = Tag {
+                name: name.clone(),
+                description: definition.description.clone(),
+                aliases: definition.aliases.clone(),
+                source: definition.source,
+                count: 0,
+            };
+            (name.clone(), tag)
+        })
+        .collect();
+
+    for (used, count) in message_queries::tag_counts(conn)? {
+        let name = registry.resolve(&used).unwrap_or(&used).to_string();
+        tags.entry(name.clone())
+            .or_insert_with(|| Tag {
+                name,
+                description: String::new(),
+                aliases: Vec::new(),
+                source: TagSource::Undefined,
+                count: 0,
+            })
+            .count += count;
+    }
+
+    let mut tags: Vec<Tag> = tags.into_values().collect();
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    Ok(TagList {
+        strict: registry.strict,
+        tags,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::TagDefinition;
+    use crate::core::models::message::Priority;
+    use crate::core::operations::message as message_ops;
+    use crate::db::migrations::run_migrations;
+
+    fn board_config(strict: bool) -> TagsConfig {
+        let mut defined = BTreeMap::new();
+        defined.insert(
+            "deploy".to_string(),
+            TagDefinition {
+                description: "A release went out".to_string(),
+                aliases: vec!["release".to_string()],
+            },
+        );
+        TagsConfig { strict, defined }
+    }
+
+    #[test]
+    fn test_aliases_resolve_to_their_tag() {
+        let registry = TagRegistry::new(&board_config(false)).unwrap();
+
+        assert_eq!(registry.resolve("blocked"), Some("blocker"));
+        assert_eq!(registry.resolve("release"), Some("deploy"));
+        assert_eq!(registry.resolve("wip"), None);
+        assert_eq!(
+            registry
+                .canonicalize(vec!["blocked".into(), "blocker".into(), "wip".into()])
+                .unwrap(),
+            vec!["blocker", "wip"]
+        );
+        assert_eq!(
+            registry.spellings(&["blocked".to_string()]),
+            vec!["blocked", "blocker", "blocking"]
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_tags() {
+        let registry = TagRegistry::new(&board_config(true)).unwrap();
+
+        assert!(registry.canonicalize(vec!["release".into()]).is_ok());
+        assert!(registry.canonicalize(vec![HANDOFF_TAG.into()]).is_ok());
+        let err = registry.canonicalize(vec!["wip".into()]).unwrap_err();
+        assert!(matches!(err, BBError::InvalidField { field, .. } if field == "tags"));
+    }
+
+    #[test]
+    fn test_conflicting_aliases_are_invalid() {
+        let mut config = board_config(false);
+        config.defined.insert(
+            "stuck".to_string(),
+            TagDefinition {
+                description: String::new(),
+                aliases: vec!["blocked".to_string()],
+            },
+        );
+        assert!(TagRegistry::new(&config).is_err());
+
+        let mut config = board_config(false);
+        config.defined.get_mut("deploy").unwrap().aliases = vec!["plan".to_string()];
+        assert!(TagRegistry::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_list_tags_counts_alias_uses() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        for tags in [vec!["blocker"], vec!["blocked", "wip"], vec!["wip"]] {
+            message_ops::post_message(
+                &mut conn,
+                "agent-1",
+                "hello",
+                tags.into_iter().map(String::from).collect(),
+                Priority::Normal,
+                None,
+                vec![],
+                vec![],
+            )
+            .unwrap();
+        }
+        // Stored before `blocked` was an alias
+        conn.execute(
+            "UPDATE messages SET tags = '[\"blocked\"]' WHERE id = 2",
+            [],
+        )
+        .unwrap();
+
+        let registry = TagRegistry::new(&board_config(false)).unwrap();
+        let tags = list_tags(&conn, &registry).unwrap().tags;
+
+        assert_eq!(tags[0].name, "blocker");
+        assert_eq!(tags[0].count, 2);
+        let wip = tags.iter().find(|t| t.name == "wip").unwrap();
+        assert_eq!((wip.count, wip.source), (1, TagSource::Undefined));
+        let deploy = tags.iter().find(|t| t.name == "deploy").unwrap();
+        assert_eq!((deploy.count, deploy.source), (0, TagSource::Board));
+    }
+}
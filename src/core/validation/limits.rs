@@ -1,80 +1,149 @@
+use crate::core::config;
 use crate::core::errors::{BBError, BBResult};
-use std::path::{Path, PathBuf};
-
-pub const MAX_AGENT_ID_LEN: usize = 64;
-pub const MAX_TASK_LEN: usize = 256;
-pub const MAX_BLOCKERS_LEN: usize = 1024;
-pub const MAX_MESSAGE_CONTENT_LEN: usize = 65536;
-pub const MAX_ARTIFACT_PATH_LEN: usize = 4096;
-pub const MAX_ARTIFACT_DESC_LEN: usize = 1024;
-pub const MAX_VERSION_LEN: usize = 64;
-pub const MAX_TAG_LEN: usize = 32;
-pub const MAX_TAGS_PER_MESSAGE: usize = 10;
-pub const MAX_REFS_PER_ENTITY: usize = 20;
+use crate::util::glob::is_glob;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Size and count limits on everything stored on the board. Set under
+/// `[limits]` in `.bb/config.toml`; the defaults are below.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub max_agent_id_len: usize,
+    pub max_task_len: usize,
+    pub max_blockers_len: usize,
+    pub max_message_content_len: usize,
+    pub max_artifact_path_len: usize,
+    pub max_artifact_desc_len: usize,
+    pub max_version_len: usize,
+    pub max_tag_len: usize,
+    pub max_tags_per_message: usize,
+    pub max_refs_per_entity: usize,
+    pub max_recipients_per_message: usize,
+    pub max_wait_timeout_secs: i64,
+    pub max_search_query_len: usize,
+    pub max_lease_ttl_secs: i64,
+    pub max_handoff_summary_len: usize,
+    pub max_handoff_response_len: usize,
+    pub max_handoff_escalation_secs: i64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_agent_id_len: 64,
+            max_task_len: 256,
+            max_blockers_len: 1024,
+            max_message_content_len: 65536,
+            max_artifact_path_len: 4096,
+            max_artifact_desc_len: 1024,
+            max_version_len: 64,
+            max_tag_len: 32,
+            max_tags_per_message: 10,
+            max_refs_per_entity: 20,
+            max_recipients_per_message: 20,
+            max_wait_timeout_secs: 600,
+            max_search_query_len: 1024,
+            max_lease_ttl_secs: 24 * 60 * 60,
+            max_handoff_summary_len: 4096,
+            max_handoff_response_len: 1024,
+            max_handoff_escalation_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl Limits {
+    /// Every limit must be positive, or nothing could be stored.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let table = toml::Table::try_from(self).map_err(|e| e.to_string())?;
+        for (name, value) in table {
+            if value.as_integer().is_none_or(|n| n <= 0) {
+                return Err(format!("limits.{name} must be positive"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The limits in effect for this process.
+pub fn limits() -> &'static Limits {
+    &config::current().limits
+}
 
 pub fn validate_agent_id(id: &str) -> BBResult<()> {
     if id.is_empty() {
-        return Err(BBError::InvalidInput(
-            "agent ID cannot be empty".to_string(),
+        return Err(BBError::invalid_field(
+            "agent_id",
+            "agent ID cannot be empty",
         ));
     }
-    if id.len() > MAX_AGENT_ID_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "agent ID too long (max {MAX_AGENT_ID_LEN} chars)"
-        )));
+    let max = limits().max_agent_id_len;
+    if id.len() > max {
+        return Err(BBError::invalid_field(
+            "agent_id",
+            format!("agent ID too long (max {max} chars)"),
+        ));
     }
     // Check for control characters
     if id.chars().any(|c| c.is_control()) {
-        return Err(BBError::InvalidInput(
-            "agent ID contains control characters".to_string(),
+        return Err(BBError::invalid_field(
+            "agent_id",
+            "agent ID contains control characters",
         ));
     }
     Ok(())
 }
 
 pub fn validate_task(task: &str) -> BBResult<()> {
-    if task.len() > MAX_TASK_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "task too long (max {MAX_TASK_LEN} chars)"
-        )));
+    let max = limits().max_task_len;
+    if task.len() > max {
+        return Err(BBError::invalid_field(
+            "current_task",
+            format!("task too long (max {max} chars)"),
+        ));
     }
     Ok(())
 }
 
 pub fn validate_blockers(blockers: &str) -> BBResult<()> {
-    if blockers.len() > MAX_BLOCKERS_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "blockers too long (max {MAX_BLOCKERS_LEN} chars)"
-        )));
+    let max = limits().max_blockers_len;
+    if blockers.len() > max {
+        return Err(BBError::invalid_field(
+            "blockers",
+            format!("blockers too long (max {max} chars)"),
+        ));
     }
     Ok(())
 }
 
 pub fn validate_message_content(content: &str) -> BBResult<()> {
     if content.is_empty() {
-        return Err(BBError::InvalidInput(
-            "message content cannot be empty".to_string(),
+        return Err(BBError::invalid_field(
+            "content",
+            "message content cannot be empty",
         ));
     }
-    if content.len() > MAX_MESSAGE_CONTENT_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "message content too long (max {MAX_MESSAGE_CONTENT_LEN} chars)"
-        )));
+    let max = limits().max_message_content_len;
+    if content.len() > max {
+        return Err(BBError::invalid_field(
+            "content",
+            format!("message content too long (max {max} chars)"),
+        ));
     }
     Ok(())
 }
 
-pub fn validate_artifact_path(path: &str, project_root: &Path) -> BBResult<PathBuf> {
-    if path.is_empty() {
-        return Err(BBError::InvalidInput(
-            "artifact path cannot be empty".to_string(),
+/// Lexical checks shared by artifact paths and lease patterns. Returns the
+/// path relative to the project root with `.` segments and duplicate or
+/// trailing slashes removed. The path does not have to exist.
+pub fn normalize_project_path(path: &str) -> BBResult<String> {
+    let max = limits().max_artifact_path_len;
+    if path.len() > max {
+        return Err(BBError::invalid_field(
+            "path",
+            format!("path too long (max {max} chars)"),
         ));
     }
-    if path.len() > MAX_ARTIFACT_PATH_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "artifact path too long (max {MAX_ARTIFACT_PATH_LEN} chars)"
-        )));
-    }
 
     // Reject absolute paths
     if Path::new(path).is_absolute() {
@@ -90,11 +159,34 @@ pub fn validate_artifact_path(path: &str, project_root: &Path) -> BBResult<PathB
         ));
     }
 
+    let normalized = path
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<_>>()
+        .join("/");
+    if normalized.is_empty() {
+        return Err(BBError::invalid_field("path", "path cannot be empty"));
+    }
+
+    Ok(normalized)
+}
+
+/// Validates an artifact path: a file, a directory, or a glob such as
+/// `src/auth/**`. A file or directory must exist inside the project; for a
+/// glob, the directory before the first wildcard must. Returns the path
+/// normalized as by `normalize_project_path`.
+pub fn validate_artifact_path(path: &str, project_root: &Path) -> BBResult<String> {
+    let normalized = normalize_project_path(path)?;
+
     // Validate path doesn't escape project directory
-    let full_path = project_root.join(path);
+    let existing: Vec<&str> = normalized
+        .split('/')
+        .take_while(|segment| !is_glob(segment))
+        .collect();
+    let full_path = project_root.join(existing.join("/"));
     let canonical = full_path
         .canonicalize()
-        .map_err(|_| BBError::InvalidInput(format!("invalid path: {path}")))?;
+        .map_err(|_| BBError::invalid_field("path", format!("invalid path: {path}")))?;
 
     let project_canonical = project_root
         .canonicalize()
@@ -106,53 +198,154 @@ pub fn validate_artifact_path(path: &str, project_root: &Path) -> BBResult<PathB
         ));
     }
 
-    Ok(full_path)
+    Ok(normalized)
+}
+
+/// Validates a path or glob to claim. Unlike artifacts the path may not exist
+/// yet; when it does, it must resolve inside the project.
+pub fn validate_lease_path(path: &str, project_root: &Path) -> BBResult<String> {
+    let normalized = normalize_project_path(path)?;
+
+    if !is_glob(&normalized) && project_root.join(&normalized).exists() {
+        validate_artifact_path(&normalized, project_root)?;
+    }
+
+    Ok(normalized)
+}
+
+pub fn validate_lease_ttl(ttl: chrono::Duration) -> BBResult<()> {
+    if ttl <= chrono::Duration::zero() {
+        return Err(BBError::invalid_field(
+            "ttl",
+            "lease duration must be positive",
+        ));
+    }
+    let max = limits().max_lease_ttl_secs;
+    if ttl > chrono::Duration::seconds(max) {
+        return Err(BBError::invalid_field(
+            "ttl",
+            format!("lease duration too long (max {max} seconds)"),
+        ));
+    }
+    Ok(())
 }
 
 pub fn validate_artifact_description(desc: &str) -> BBResult<()> {
-    if desc.len() > MAX_ARTIFACT_DESC_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "artifact description too long (max {MAX_ARTIFACT_DESC_LEN} chars)"
-        )));
+    let max = limits().max_artifact_desc_len;
+    if desc.len() > max {
+        return Err(BBError::invalid_field(
+            "description",
+            format!("artifact description too long (max {max} chars)"),
+        ));
     }
     Ok(())
 }
 
 pub fn validate_version(version: &str) -> BBResult<()> {
-    if version.len() > MAX_VERSION_LEN {
-        return Err(BBError::InvalidInput(format!(
-            "version too long (max {MAX_VERSION_LEN} chars)"
-        )));
+    let max = limits().max_version_len;
+    if version.len() > max {
+        return Err(BBError::invalid_field(
+            "version",
+            format!("version too long (max {max} chars)"),
+        ));
     }
     Ok(())
 }
 
 pub fn validate_tags(tags: &[String]) -> BBResult<()> {
-    if tags.len() > MAX_TAGS_PER_MESSAGE {
-        return Err(BBError::InvalidInput(format!(
-            "too many tags (max {MAX_TAGS_PER_MESSAGE})"
-        )));
+    let limits = limits();
+    if tags.len() > limits.max_tags_per_message {
+        return Err(BBError::invalid_field(
+            "tags",
+            format!("too many tags (max {})", limits.max_tags_per_message),
+        ));
     }
 
     for tag in tags {
         if tag.is_empty() {
-            return Err(BBError::InvalidInput("tag cannot be empty".to_string()));
+            return Err(BBError::invalid_field("tags", "tag cannot be empty"));
         }
-        if tag.len() > MAX_TAG_LEN {
-            return Err(BBError::InvalidInput(format!(
-                "tag too long (max {MAX_TAG_LEN} chars): {tag}"
-            )));
+        if tag.len() > limits.max_tag_len {
+            return Err(BBError::invalid_field(
+                "tags",
+                format!("tag too long (max {} chars): {tag}", limits.max_tag_len),
+            ));
         }
         if tag.chars().any(|c| c.is_control() || c.is_whitespace()) {
-            return Err(BBError::InvalidInput(format!(
-                "tag contains invalid characters: {tag}"
-            )));
+            return Err(BBError::invalid_field(
+                "tags",
+                format!("tag contains invalid characters: {tag}"),
+            ));
         }
     }
 
     Ok(())
 }
 
+pub fn validate_wait_timeout(timeout: chrono::Duration) -> BBResult<()> {
+    if timeout.num_seconds() <= 0 {
+        return Err(BBError::invalid_field(
+            "timeout",
+            "wait timeout must be positive",
+        ));
+    }
+    let max = limits().max_wait_timeout_secs;
+    if timeout.num_seconds() > max {
+        return Err(BBError::invalid_field(
+            "timeout",
+            format!("wait timeout too long (max {max}s)"),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_handoff_summary(summary: &str) -> BBResult<()> {
+    if summary.trim().is_empty() {
+        return Err(BBError::invalid_field(
+            "summary",
+            "handoff summary cannot be empty",
+        ));
+    }
+    let max = limits().max_handoff_summary_len;
+    if summary.len() > max {
+        return Err(BBError::invalid_field(
+            "summary",
+            format!("handoff summary too long (max {max} chars)"),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the note given on accepting (`field` "note") or the reason given
+/// on declining (`field` "reason").
+pub fn validate_handoff_response(field: &str, response: &str) -> BBResult<()> {
+    let max = limits().max_handoff_response_len;
+    if response.len() > max {
+        return Err(BBError::invalid_field(
+            field,
+            format!("{field} too long (max {max} chars)"),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_handoff_escalation(after: chrono::Duration) -> BBResult<()> {
+    if after <= chrono::Duration::zero() {
+        return Err(BBError::invalid_field(
+            "escalate_after",
+            "escalation delay must be positive",
+        ));
+    }
+    let max = limits().max_handoff_escalation_secs;
+    if after > chrono::Duration::seconds(max) {
+        return Err(BBError::invalid_field(
+            "escalate_after",
+            format!("escalation delay too long (max {max} seconds)"),
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,7 +364,7 @@ mod tests {
 
     #[test]
     fn test_validate_agent_id_too_long() {
-        let long_id = "a".repeat(MAX_AGENT_ID_LEN + 1);
+        let long_id = "a".repeat(limits().max_agent_id_len + 1);
         assert!(validate_agent_id(&long_id).is_err());
     }
 
@@ -182,7 +375,7 @@ mod tests {
 
     #[test]
     fn test_validate_task_too_long() {
-        let long_task = "a".repeat(MAX_TASK_LEN + 1);
+        let long_task = "a".repeat(limits().max_task_len + 1);
         assert!(validate_task(&long_task).is_err());
     }
 
@@ -193,7 +386,7 @@ mod tests {
 
     #[test]
     fn test_validate_blockers_too_long() {
-        let long_blockers = "a".repeat(MAX_BLOCKERS_LEN + 1);
+        let long_blockers = "a".repeat(limits().max_blockers_len + 1);
         assert!(validate_blockers(&long_blockers).is_err());
     }
 
@@ -204,7 +397,7 @@ mod tests {
 
     #[test]
     fn test_validate_message_content_too_long() {
-        let long_content = "a".repeat(MAX_MESSAGE_CONTENT_LEN + 1);
+        let long_content = "a".repeat(limits().max_message_content_len + 1);
         assert!(validate_message_content(&long_content).is_err());
     }
 
@@ -235,15 +428,53 @@ mod tests {
         assert!(validate_artifact_path("src/main.rs", temp.path()).is_ok());
     }
 
+    #[test]
+    fn test_validate_artifact_path_directories_and_globs() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src/auth")).unwrap();
+
+        assert_eq!(
+            validate_artifact_path("./src/auth/", temp.path()).unwrap(),
+            "src/auth"
+        );
+        assert_eq!(
+            validate_artifact_path("src/auth/**", temp.path()).unwrap(),
+            "src/auth/**"
+        );
+        assert!(validate_artifact_path("**/*.rs", temp.path()).is_ok());
+        assert!(validate_artifact_path("src/billing/**", temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_normalize_project_path() {
+        assert_eq!(normalize_project_path("./src//auth/").unwrap(), "src/auth");
+        assert!(normalize_project_path("./").is_err());
+        assert!(normalize_project_path("/etc").is_err());
+    }
+
+    #[test]
+    fn test_validate_lease_path_allows_missing_and_globs() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(
+            validate_lease_path("src/new_file.rs", temp.path()).unwrap(),
+            "src/new_file.rs"
+        );
+        assert_eq!(
+            validate_lease_path("src/**/*.rs", temp.path()).unwrap(),
+            "src/**/*.rs"
+        );
+        assert!(validate_lease_path("../outside", temp.path()).is_err());
+    }
+
     #[test]
     fn test_validate_version_too_long() {
-        let long_version = "v".repeat(MAX_VERSION_LEN + 1);
+        let long_version = "v".repeat(limits().max_version_len + 1);
         assert!(validate_version(&long_version).is_err());
     }
 
     #[test]
     fn test_validate_tags_too_many() {
-        let tags: Vec<String> = (0..MAX_TAGS_PER_MESSAGE + 1)
+        let tags: Vec<String> = (0..limits().max_tags_per_message + 1)
             .map(|i| format!("tag{i}"))
             .collect();
         assert!(validate_tags(&tags).is_err());
@@ -259,6 +490,42 @@ mod tests {
         assert!(validate_tags(&["hello world".to_string()]).is_err());
     }
 
+    #[test]
+    fn test_validate_wait_timeout() {
+        assert!(validate_wait_timeout(chrono::Duration::seconds(30)).is_ok());
+        assert!(validate_wait_timeout(chrono::Duration::seconds(0)).is_err());
+        assert!(
+            validate_wait_timeout(chrono::Duration::seconds(
+                limits().max_wait_timeout_secs + 1
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_lease_ttl() {
+        assert!(validate_lease_ttl(chrono::Duration::minutes(30)).is_ok());
+        assert!(validate_lease_ttl(chrono::Duration::zero()).is_err());
+        assert!(
+            validate_lease_ttl(chrono::Duration::seconds(limits().max_lease_ttl_secs + 1)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_handoff_fields() {
+        assert!(validate_handoff_summary("Finish the parser").is_ok());
+        assert!(validate_handoff_summary("  ").is_err());
+        assert!(validate_handoff_escalation(chrono::Duration::minutes(30)).is_ok());
+        assert!(validate_handoff_escalation(chrono::Duration::zero()).is_err());
+        match validate_handoff_response(
+            "reason",
+            &"x".repeat(limits().max_handoff_response_len + 1),
+        ) {
+            Err(BBError::InvalidField { field, .. }) => assert_eq!(field, "reason"),
+            other => panic!("expected InvalidField, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_validate_tags_valid() {
         assert!(validate_tags(&["tag1".to_string(), "tag2".to_string()]).is_ok());
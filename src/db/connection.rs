@@ -1,18 +1,49 @@
 use crate::core::errors::{BBError, BBResult};
+use crate::db::migrations::run_migrations;
 use rusqlite::Connection;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+/// Idle connections a `ConnectionPool` keeps open; busier moments open
+/// extra ones that are closed when handed back.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// Opens the project database, applies any pending migrations and runs `f`.
+///
+/// Fails with `SchemaTooNew` if the database was written by a newer `bb`.
 pub fn with_connection<F, T>(project_dir: &Path, f: F) -> BBResult<T>
 where
     F: FnOnce(&mut Connection) -> BBResult<T>,
 {
+    with_unmigrated_connection(project_dir, |conn| {
+        run_migrations(conn)?;
+        f(conn)
+    })
+}
+
+/// Like `with_connection`, but leaves the schema as it is. Only `bb migrate`
+/// should need this.
+pub fn with_unmigrated_connection<F, T>(project_dir: &Path, f: F) -> BBResult<T>
+where
+    F: FnOnce(&mut Connection) -> BBResult<T>,
+{
+    let mut conn = open(project_dir)?;
+
+    let result = f(&mut conn)?;
+
+    conn.execute_batch("PRAGMA wal_checkpoint(PASSIVE)")?;
+
+    Ok(result)
+}
+
+fn open(project_dir: &Path) -> BBResult<Connection> {
     let bb_dir = project_dir.join(".bb");
     if !bb_dir.exists() {
         return Err(BBError::NotInitialized);
     }
 
     let db_path = bb_dir.join("blackboard.db");
-    let mut conn = Connection::open(&db_path)?;
+    let conn = Connection::open(&db_path)?;
 
     conn.execute_batch(
         "PRAGMA journal_mode = WAL;
@@ -20,16 +51,90 @@ where
          PRAGMA foreign_keys = ON;",
     )?;
 
-    let result = f(&mut conn)?;
+    Ok(conn)
+}
 
-    conn.execute_batch("PRAGMA wal_checkpoint(PASSIVE)")?;
+/// Connections to one project's database, kept open for the life of a
+/// server instead of being opened for every call.
+///
+/// Migrations run once, with the first connection. Each call borrows an idle
+/// connection, or opens another when they are all busy, so a long wait never
+/// blocks other calls and still sees their commits through `data_version`.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    inner: Arc<PoolInner>,
+}
 
-    Ok(result)
+struct PoolInner {
+    project_dir: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+    migrated: Mutex<bool>,
+}
+
+impl ConnectionPool {
+    /// Creates an empty pool; nothing is opened until the first call.
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                project_dir: project_dir.to_path_buf(),
+                idle: Mutex::new(Vec::new()),
+                migrated: Mutex::new(false),
+            }),
+        }
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.inner.project_dir
+    }
+
+    /// Runs `f` on a pooled connection, migrating the schema first if no
+    /// call has yet. Fails like `with_connection` when the board is missing
+    /// or too new.
+    pub fn with<F, T>(&self, f: F) -> BBResult<T>
+    where
+        F: FnOnce(&mut Connection) -> BBResult<T>,
+    {
+        let idle = self.inner.idle.lock().unwrap().pop();
+        let mut conn = match idle {
+            Some(conn) => conn,
+            None => self.open()?,
+        };
+
+        // An open transaction rolls back when `f` drops it, so the
+        // connection is reusable whether or not `f` succeeded
+        let result = f(&mut conn);
+
+        let mut idle = self.inner.idle.lock().unwrap();
+        if idle.len() < MAX_IDLE_CONNECTIONS {
+            idle.push(conn);
+        }
+        result
+    }
+
+    fn open(&self) -> BBResult<Connection> {
+        let conn = open(&self.inner.project_dir)?;
+        let mut migrated = self.inner.migrated.lock().unwrap();
+        if !*migrated {
+            run_migrations(&conn)?;
+            *migrated = true;
+        }
+        Ok(conn)
+    }
+}
+
+/// Returns SQLite's `data_version` for this connection.
+///
+/// The value changes whenever another connection commits to the database,
+/// which makes it a cheap way to detect new writes without re-running queries.
+pub fn data_version(conn: &Connection) -> BBResult<i64> {
+    let version = conn.query_row("PRAGMA data_version", [], |row| row.get(0))?;
+    Ok(version)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::migrations::{LATEST_VERSION, current_version};
     use std::fs;
     use tempfile::TempDir;
 
@@ -67,4 +172,66 @@ mod tests {
         })
         .unwrap();
     }
+
+    #[test]
+    fn test_data_version_changes_on_external_commit() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".bb")).unwrap();
+
+        with_connection(temp.path(), |conn| {
+            conn.execute_batch("CREATE TABLE t (x INTEGER)")?;
+            let before = data_version(conn)?;
+            assert_eq!(before, data_version(conn)?);
+
+            let other = Connection::open(temp.path().join(".bb/blackboard.db"))?;
+            other.execute("INSERT INTO t (x) VALUES (1)", [])?;
+
+            assert_ne!(before, data_version(conn)?);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pool_migrates_once_and_reuses_connections() {
+        let temp = TempDir::new().unwrap();
+        let pool = ConnectionPool::new(temp.path());
+        assert!(matches!(
+            pool.with(|_| Ok(())),
+            Err(BBError::NotInitialized)
+        ));
+
+        fs::create_dir(temp.path().join(".bb")).unwrap();
+        pool.with(|conn| {
+            assert_eq!(current_version(conn)?, LATEST_VERSION);
+            // Temporary tables live only as long as their connection
+            conn.execute_batch("CREATE TEMP TABLE marker (x INTEGER)")?;
+            Ok(())
+        })
+        .unwrap();
+        pool.with(|conn| {
+            conn.execute_batch("SELECT x FROM temp.marker")?;
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pool_lends_a_second_connection_while_one_is_busy() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".bb")).unwrap();
+        let pool = ConnectionPool::new(temp.path());
+
+        pool.with(|waiting| {
+            let before = data_version(waiting)?;
+            pool.with(|other| {
+                other.execute_batch("CREATE TABLE t (x INTEGER)")?;
+                Ok(())
+            })?;
+            assert_ne!(before, data_version(waiting)?);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(pool.inner.idle.lock().unwrap().len(), 2);
+    }
 }
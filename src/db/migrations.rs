@@ -1,11 +1,159 @@
-use crate::core::errors::BBResult;
-use rusqlite::Connection;
+use crate::core::errors::{BBError, BBResult};
+use rusqlite::{Connection, Transaction, TransactionBehavior};
 
-pub const SCHEMA_SQL: &str = include_str!("../../migrations/001_initial.sql");
+/// A schema change embedded in the binary. Migrations are applied in version
+/// order, each in its own transaction, and recorded in `schema_version`.
+///
+/// A migration is never edited once released: databases that have applied
+/// it will not run it again, so later changes go in a new migration.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("../../migrations/001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "read_cursors",
+        sql: include_str!("../../migrations/002_read_cursors.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "message_recipients",
+        sql: include_str!("../../migrations/003_message_recipients.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "full_text_search",
+        sql: include_str!("../../migrations/004_full_text_search.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "leases",
+        sql: include_str!("../../migrations/005_leases.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "handoffs",
+        sql: include_str!("../../migrations/006_handoffs.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "questions",
+        sql: include_str!("../../migrations/007_questions.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "agent_status_events",
+        sql: include_str!("../../migrations/008_agent_status_events.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "artifact_content",
+        sql: include_str!("../../migrations/009_artifact_content.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "artifact_revisions",
+        sql: include_str!("../../migrations/010_artifact_revisions.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "artifact_git",
+        sql: include_str!("../../migrations/011_artifact_git.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "artifact_links",
+        sql: include_str!("../../migrations/012_artifact_links.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "artifact_removals",
+        sql: include_str!("../../migrations/013_artifact_removals.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "revision_links",
+        sql: include_str!("../../migrations/014_revision_links.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "revision_renames",
+        sql: include_str!("../../migrations/015_revision_renames.sql"),
+    },
+];
+
+/// The newest schema version this binary knows how to use.
+pub const LATEST_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// Returns the database's schema version, or 0 for an empty database.
+pub fn current_version(conn: &Connection) -> BBResult<i64> {
+    let has_table: bool = conn.query_row(
+        "SELECT EXISTS (
+             SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_table {
+        return Ok(0);
+    }
 
-pub fn run_migrations(conn: &Connection) -> BBResult<()> {
-    conn.execute_batch(SCHEMA_SQL)?;
-    Ok(())
+    let version = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version)
+}
+
+/// Migrations not yet applied to this database, oldest first. Fails if the
+/// database was written by a newer `bb`.
+pub fn pending_migrations(conn: &Connection) -> BBResult<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    if current > LATEST_VERSION {
+        return Err(BBError::SchemaTooNew {
+            found: current,
+            supported: LATEST_VERSION,
+        });
+    }
+
+    Ok(MIGRATIONS.iter().filter(|m| m.version > current).collect())
+}
+
+/// Applies every pending migration and returns the versions applied.
+pub fn run_migrations(conn: &Connection) -> BBResult<Vec<i64>> {
+    let mut applied = Vec::new();
+
+    for migration in pending_migrations(conn)? {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+
+        // Another process may have migrated while we waited for the write lock
+        if current_version(&tx)? >= migration.version {
+            continue;
+        }
+
+        tx.execute_batch(migration.sql)?;
+        // 001_initial.sql records its own version, as it did before there
+        // were migrations
+        tx.execute(
+            "INSERT INTO schema_version (version)
+             SELECT ?1 WHERE NOT EXISTS (SELECT 1 FROM schema_version WHERE version = ?1)",
+            [migration.version],
+        )?;
+        tx.commit()?;
+
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
 }
 
 #[cfg(test)]
@@ -13,47 +161,173 @@ mod tests {
     use super::*;
     use rusqlite::Connection;
 
+    const V1_FIXTURE: &str = include_str!("../../tests/fixtures/v1_database.sql");
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+            [name],
+            |row| row.get::<_, i64>(0),
+        )
+        .unwrap()
+            == 1
+    }
+
     #[test]
     fn test_run_migrations() {
         let conn = Connection::open_in_memory().unwrap();
 
         // Run migrations
+        let applied = run_migrations(&conn).unwrap();
+        assert_eq!(applied, (1..=LATEST_VERSION).collect::<Vec<_>>());
+        assert_eq!(current_version(&conn).unwrap(), LATEST_VERSION);
+
+        // Check that the core tables exist
+        assert!(table_exists(&conn, "agents"));
+        assert!(table_exists(&conn, "messages"));
+        assert!(table_exists(&conn, "artifacts"));
+    }
+
+    #[test]
+    fn test_run_migrations_is_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
         run_migrations(&conn).unwrap();
 
-        // Check that schema_version was created
-        let version: i64 = conn
-            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+        assert!(run_migrations(&conn).unwrap().is_empty());
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(version, 1);
+        assert_eq!(rows, LATEST_VERSION);
+    }
 
-        // Check that agents table exists
-        let count: i64 = conn
+    #[test]
+    fn test_initial_migration_is_unchanged_since_release() {
+        // 001 was run as the whole schema before migrations existed; every
+        // database created by then has it, so it must stay as released
+        let initial = MIGRATIONS[0].sql;
+        assert!(initial.starts_with("PRAGMA journal_mode = WAL;"));
+        assert!(initial.contains("INSERT OR IGNORE INTO schema_version (version) VALUES (1);"));
+        assert!(!initial.contains("read_cursors"));
+
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        let rows: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='agents'",
+                "SELECT COUNT(*) FROM schema_version WHERE version = 1",
                 [],
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn test_migration_versions_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, i as i64 + 1, "{}", migration.name);
+        }
+    }
+
+    #[test]
+    fn test_upgrade_v1_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V1_FIXTURE).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert_eq!(
+            pending_migrations(&conn).unwrap().len() as i64,
+            LATEST_VERSION - 1
+        );
 
-        // Check that messages table exists
-        let count: i64 = conn
+        let applied = run_migrations(&conn).unwrap();
+        assert_eq!(applied, (2..=LATEST_VERSION).collect::<Vec<_>>());
+        assert_eq!(current_version(&conn).unwrap(), LATEST_VERSION);
+
+        assert!(table_exists(&conn, "read_cursors"));
+        assert!(table_exists(&conn, "message_recipients"));
+
+        // Rows written before the upgrade are searchable
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM messages_fts WHERE messages_fts MATCH 'JWT'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+        let hits: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='messages'",
+                "SELECT COUNT(*) FROM artifacts_fts WHERE artifacts_fts MATCH 'session'",
                 [],
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!(hits, 1);
 
-        // Check that artifacts table exists
-        let count: i64 = conn
+        // Rebuilding artifact_revisions keeps the revisions it had
+        let revisions: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='artifacts'",
+                "SELECT COUNT(*) FROM artifact_revisions WHERE path = 'src/auth.rs' AND removed = 0",
                 [],
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!(revisions, 1);
+    }
+
+    #[test]
+    fn test_latest_revisions_get_current_links() {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in MIGRATIONS.iter().filter(|m| m.version < 14) {
+            conn.execute_batch(migration.sql).unwrap();
+            conn.execute(
+                "INSERT OR IGNORE INTO schema_version (version) VALUES (?1)",
+                [migration.version],
+            )
+            .unwrap();
+        }
+        conn.execute_batch(
+            "INSERT INTO artifacts (id, path, produced_by, created_at) VALUES
+                 (1, 'schema.sql', 'agent-1', '2025-01-01T10:00:00+00:00'),
+                 (2, 'gen/api.rs', 'agent-1', '2025-01-01T10:00:00+00:00');
+             INSERT INTO artifact_revisions (artifact_id, path, produced_by, registered_at) VALUES
+                 (2, 'gen/api.rs', 'agent-1', '2025-01-01T10:00:00+00:00'),
+                 (2, 'gen/api.rs', 'agent-2', '2025-01-01T11:00:00+00:00');
+             INSERT INTO artifact_links (artifact_id, upstream_id, link)
+                 VALUES (2, 1, 'derived_from');",
+        )
+        .unwrap();
+
+        assert_eq!(run_migrations(&conn).unwrap(), vec![14, 15]);
+        let links: Vec<(String, String)> = conn
+            .prepare("SELECT depends_on, derived_from FROM artifact_revisions ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            links,
+            [
+                ("[]".to_string(), "[]".to_string()),
+                ("[]".to_string(), r#"["schema.sql"]"#.to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version) VALUES (?1)",
+            [LATEST_VERSION + 1],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            run_migrations(&conn),
+            Err(BBError::SchemaTooNew { found, supported })
+                if found == LATEST_VERSION + 1 && supported == LATEST_VERSION
+        ));
     }
 }
@@ -1,9 +1,9 @@
 use crate::core::errors::BBResult;
-use crate::core::models::agent::{Agent, AgentStatus};
+use crate::core::models::agent::{Agent, AgentStatus, StatusEvent};
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, params};
 
-pub fn upsert_agent(conn: &mut Connection, agent: &Agent) -> BBResult<()> {
+pub fn upsert_agent(conn: &Connection, agent: &Agent) -> BBResult<()> {
     conn.execute(
         "INSERT INTO agents (id, current_task, progress, status, blockers, last_seen, updated_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
@@ -27,7 +27,7 @@ pub fn upsert_agent(conn: &mut Connection, agent: &Agent) -> BBResult<()> {
     Ok(())
 }
 
-pub fn get_agent(conn: &mut Connection, id: &str) -> BBResult<Option<Agent>> {
+pub fn get_agent(conn: &Connection, id: &str) -> BBResult<Option<Agent>> {
     let mut stmt = conn.prepare(
         "SELECT id, current_task, progress, status, blockers, last_seen, updated_at
          FROM agents WHERE id = ?1",
@@ -42,7 +42,7 @@ pub fn get_agent(conn: &mut Connection, id: &str) -> BBResult<Option<Agent>> {
     }
 }
 
-pub fn get_all_agents(conn: &mut Connection) -> BBResult<Vec<Agent>> {
+pub fn get_all_agents(conn: &Connection) -> BBResult<Vec<Agent>> {
     let mut stmt = conn.prepare(
         "SELECT id, current_task, progress, status, blockers, last_seen, updated_at
          FROM agents ORDER BY last_seen DESC",
@@ -55,22 +55,103 @@ pub fn get_all_agents(conn: &mut Connection) -> BBResult<Vec<Agent>> {
     Ok(agents)
 }
 
-pub fn delete_offline_agents(conn: &mut Connection) -> BBResult<usize> {
-    let count = conn.execute("DELETE FROM agents WHERE status = 'offline'", [])?;
+/// Opaque value that changes whenever an agent is added or removed, or
+/// updates its status. Touching `last_seen` does not change it.
+pub fn agents_fingerprint(conn: &Connection) -> BBResult<String> {
+    let fingerprint = conn.query_row(
+        "SELECT COUNT(*) || ':' || COALESCE(MAX(updated_at), '') FROM agents",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(fingerprint)
+}
+
+pub fn delete_agent(conn: &Connection, id: &str) -> BBResult<usize> {
+    let count = conn.execute("DELETE FROM agents WHERE id = ?1", params![id])?;
     Ok(count)
 }
 
-pub fn update_offline_status(conn: &mut Connection, stale_minutes: i64) -> BBResult<usize> {
+pub fn insert_status_event(conn: &Connection, event: &StatusEvent) -> BBResult<i64> {
+    conn.execute(
+        "INSERT INTO agent_status_events
+             (agent_id, status, current_task, progress, blockers, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            event.agent_id,
+            event.status.as_str(),
+            event.current_task,
+            event.progress,
+            event.blockers,
+            event.created_at.to_rfc3339()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Deletes status events older than `before`, keeping each agent's latest
+/// so its current state is still on record.
+pub fn delete_status_events_before(conn: &Connection, before: DateTime<Utc>) -> BBResult<usize> {
     let count = conn.execute(
-        "UPDATE agents 
-         SET status = 'offline' 
-         WHERE status != 'offline' 
-           AND datetime(last_seen) < datetime('now', ?1 || ' minutes')",
-        params![format!("-{}", stale_minutes)],
+        "DELETE FROM agent_status_events
+         WHERE created_at < ?1
+           AND id NOT IN (SELECT MAX(id) FROM agent_status_events GROUP BY agent_id)",
+        params![before.to_rfc3339()],
     )?;
     Ok(count)
 }
 
+/// Status events grouped by agent, oldest first. With `since`, each agent's
+/// last event before it is included too, as the state the agent was in when
+/// the window opened.
+pub fn list_status_events(
+    conn: &Connection,
+    agent_id: Option<&str>,
+    since: Option<DateTime<Utc>>,
+) -> BBResult<Vec<StatusEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT e.id, e.agent_id, e.status, e.current_task, e.progress, e.blockers, e.created_at
+         FROM agent_status_events e
+         WHERE (?1 IS NULL OR e.agent_id = ?1)
+           AND (?2 IS NULL OR e.created_at >= ?2 OR e.id = (
+               SELECT p.id FROM agent_status_events p
+               WHERE p.agent_id = e.agent_id AND p.created_at < ?2
+               ORDER BY p.created_at DESC, p.id DESC
+               LIMIT 1))
+         ORDER BY e.agent_id, e.created_at, e.id",
+    )?;
+
+    let events = stmt
+        .query_map(
+            params![agent_id, since.map(|t| t.to_rfc3339())],
+            row_to_status_event,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(events)
+}
+
+fn row_to_status_event(row: &rusqlite::Row) -> Result<StatusEvent, rusqlite::Error> {
+    let created_at_str: String = row.get(6)?;
+
+    Ok(StatusEvent {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        status: AgentStatus::parse(&row.get::<_, String>(2)?),
+        current_task: row.get(3)?,
+        progress: row.get(4)?,
+        blockers: row.get(5)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at_str)
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    6,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })?
+            .with_timezone(&Utc),
+    })
+}
+
 fn row_to_agent(row: &rusqlite::Row) -> Result<Agent, rusqlite::Error> {
     let last_seen_str: String = row.get(5)?;
     let updated_at_str: String = row.get(6)?;
@@ -127,11 +208,11 @@ mod tests {
 
     #[test]
     fn test_upsert_agent_insert() {
-        let (mut conn, agent) = setup();
+        let (conn, agent) = setup();
 
-        upsert_agent(&mut conn, &agent).unwrap();
+        upsert_agent(&conn, &agent).unwrap();
 
-        let retrieved = get_agent(&mut conn, "test-agent").unwrap().unwrap();
+        let retrieved = get_agent(&conn, "test-agent").unwrap().unwrap();
         assert_eq!(retrieved.id, "test-agent");
         assert_eq!(retrieved.current_task, "testing");
         assert_eq!(retrieved.progress, 50);
@@ -139,32 +220,32 @@ mod tests {
 
     #[test]
     fn test_get_agent_not_found() {
-        let (mut conn, _) = setup();
+        let (conn, _) = setup();
 
-        let result = get_agent(&mut conn, "nonexistent").unwrap();
+        let result = get_agent(&conn, "nonexistent").unwrap();
         assert!(result.is_none());
     }
 
     #[test]
     fn test_upsert_agent_update() {
-        let (mut conn, mut agent) = setup();
+        let (conn, mut agent) = setup();
 
-        upsert_agent(&mut conn, &agent).unwrap();
+        upsert_agent(&conn, &agent).unwrap();
 
         agent.current_task = "updated task".to_string();
         agent.progress = 75;
-        upsert_agent(&mut conn, &agent).unwrap();
+        upsert_agent(&conn, &agent).unwrap();
 
-        let retrieved = get_agent(&mut conn, "test-agent").unwrap().unwrap();
+        let retrieved = get_agent(&conn, "test-agent").unwrap().unwrap();
         assert_eq!(retrieved.current_task, "updated task");
         assert_eq!(retrieved.progress, 75);
     }
 
     #[test]
     fn test_get_all_agents() {
-        let (mut conn, agent1) = setup();
+        let (conn, agent1) = setup();
 
-        upsert_agent(&mut conn, &agent1).unwrap();
+        upsert_agent(&conn, &agent1).unwrap();
 
         let agent2 = Agent {
             id: "test-agent-2".to_string(),
@@ -175,9 +256,40 @@ mod tests {
             last_seen: Utc::now(),
             updated_at: Utc::now(),
         };
-        upsert_agent(&mut conn, &agent2).unwrap();
+        upsert_agent(&conn, &agent2).unwrap();
 
-        let agents = get_all_agents(&mut conn).unwrap();
+        let agents = get_all_agents(&conn).unwrap();
         assert_eq!(agents.len(), 2);
     }
+
+    #[test]
+    fn test_status_events_since() {
+        let (conn, agent) = setup();
+        let now = Utc::now();
+
+        for (mins_ago, status) in [
+            (90, AgentStatus::Coding),
+            (60, AgentStatus::Blocked),
+            (30, AgentStatus::Coding),
+        ] {
+            let event = StatusEvent {
+                status,
+                created_at: now - chrono::Duration::minutes(mins_ago),
+                ..StatusEvent::of(&agent)
+            };
+            insert_status_event(&conn, &event).unwrap();
+        }
+
+        assert_eq!(
+            list_status_events(&conn, Some("test-agent"), None)
+                .unwrap()
+                .len(),
+            3
+        );
+        // The event in effect 45 minutes ago opens the window
+        let events =
+            list_status_events(&conn, None, Some(now - chrono::Duration::minutes(45))).unwrap();
+        let statuses: Vec<_> = events.iter().map(|e| e.status).collect();
+        assert_eq!(statuses, vec![AgentStatus::Blocked, AgentStatus::Coding]);
+    }
 }
@@ -1,40 +1,154 @@
 use crate::core::errors::BBResult;
-use crate::core::models::artifact::Artifact;
+use crate::core::models::artifact::{
+    Artifact, ArtifactEdge, ArtifactLink, ArtifactRevision, GitVersion,
+};
 use crate::core::models::reference::Reference;
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
 use serde_json::Value as JsonValue;
 
-pub fn upsert_artifact(conn: &mut Connection, artifact: &Artifact) -> BBResult<i64> {
+pub(crate) const ARTIFACT_COLUMNS: &str = "a.id, a.path, a.produced_by, a.description, a.version, \
+     a.refs, a.first_registered_at, a.updated_at, a.content_hash, a.size, a.mtime, \
+     a.git_head, a.git_dirty, a.git_last_commit, \
+     (SELECT json_group_array(u.path) FROM artifact_links l JOIN artifacts u ON u.id = l.upstream_id \
+      WHERE l.artifact_id = a.id AND l.link = 'depends_on'), \
+     (SELECT json_group_array(u.path) FROM artifact_links l JOIN artifacts u ON u.id = l.upstream_id \
+      WHERE l.artifact_id = a.id AND l.link = 'derived_from')";
+
+const REVISION_COLUMNS: &str = "id, artifact_id, path, produced_by, description, version, refs, \
+     content_hash, size, mtime, registered_at, git_head, git_dirty, git_last_commit, removed, \
+     depends_on, derived_from, renamed_from";
+
+/// A subquery for the paths that the artifact with id `artifact_id`, an SQL
+/// expression, links to as `link`, as a JSON array.
+fn linked_paths(artifact_id: &str, link: ArtifactLink) -> String {
+    format!(
+        "(SELECT json_group_array(u.path) FROM artifact_links l \
+          JOIN artifacts u ON u.id = l.upstream_id \
+          WHERE l.artifact_id = {artifact_id} AND l.link = '{}')",
+        link.as_str()
+    )
+}
+
+/// Registers `artifact` under its path, replacing the latest registration
+/// and recording it as a new revision. `first_registered_at` is only set
+/// the first time; `updated_at` is the time of this registration. `links`,
+/// given as upstream artifact ids, replace the artifact's links; `None`
+/// keeps them. The revision records the links the artifact ends up with.
+pub fn upsert_artifact(
+    conn: &mut Connection,
+    artifact: &Artifact,
+    links: Option<&[(i64, ArtifactLink)]>,
+) -> BBResult<i64> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let id = upsert_artifact_in(&tx, artifact, links)?;
+    tx.commit()?;
+
+    Ok(id)
+}
+
+/// Like `upsert_artifact`, inside the caller's transaction, which should be
+/// IMMEDIATE so the artifact, its links and its revision are written
+/// together.
+pub fn upsert_artifact_in(
+    conn: &Connection,
+    artifact: &Artifact,
+    links: Option<&[(i64, ArtifactLink)]>,
+) -> BBResult<i64> {
     let refs_json = serde_json::to_string(&artifact.refs)?;
+    let registered_at = artifact.updated_at.to_rfc3339();
+    let mtime = artifact.mtime.map(|t| t.to_rfc3339());
+    let git = artifact.git.as_ref();
+    let (git_head, git_dirty, git_last_commit) = (
+        git.map(|g| &g.head),
+        git.map(|g| g.dirty),
+        git.and_then(|g| g.last_commit.as_ref()),
+    );
 
-    conn.execute(
-        "INSERT INTO artifacts (path, produced_by, description, version, refs, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+    let id: i64 = conn.query_row(
+        "INSERT INTO artifacts
+             (path, produced_by, description, version, refs, created_at,
+              first_registered_at, updated_at, content_hash, size, mtime,
+              git_head, git_dirty, git_last_commit)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?6, ?8, ?9, ?10, ?11, ?12, ?13)
          ON CONFLICT(path) DO UPDATE SET
              produced_by = excluded.produced_by,
              description = excluded.description,
              version = excluded.version,
              refs = excluded.refs,
-             created_at = excluded.created_at",
+             created_at = excluded.created_at,
+             updated_at = excluded.updated_at,
+             content_hash = excluded.content_hash,
+             size = excluded.size,
+             mtime = excluded.mtime,
+             git_head = excluded.git_head,
+             git_dirty = excluded.git_dirty,
+             git_last_commit = excluded.git_last_commit
+         RETURNING id",
         params![
             artifact.path,
             artifact.produced_by,
             artifact.description,
             artifact.version,
             refs_json,
-            artifact.created_at.to_rfc3339()
+            registered_at,
+            artifact.first_registered_at.to_rfc3339(),
+            artifact.content_hash,
+            artifact.size,
+            mtime,
+            git_head,
+            git_dirty,
+            git_last_commit
+        ],
+        |row| row.get(0),
+    )?;
+    if let Some(links) = links {
+        conn.execute(
+            "DELETE FROM artifact_links WHERE artifact_id = ?1",
+            params![id],
+        )?;
+        for (upstream_id, link) in links {
+            conn.execute(
+                "INSERT OR IGNORE INTO artifact_links (artifact_id, upstream_id, link)
+                 VALUES (?1, ?2, ?3)",
+                params![id, upstream_id, link.as_str()],
+            )?;
+        }
+    }
+    conn.execute(
+        &format!(
+            "INSERT INTO artifact_revisions
+                 (artifact_id, path, produced_by, description, version, refs,
+                  content_hash, size, mtime, registered_at, git_head, git_dirty, git_last_commit,
+                  depends_on, derived_from)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, {}, {})",
+            linked_paths("?1", ArtifactLink::DependsOn),
+            linked_paths("?1", ArtifactLink::DerivedFrom)
+        ),
+        params![
+            id,
+            artifact.path,
+            artifact.produced_by,
+            artifact.description,
+            artifact.version,
+            refs_json,
+            artifact.content_hash,
+            artifact.size,
+            mtime,
+            registered_at,
+            git_head,
+            git_dirty,
+            git_last_commit
         ],
     )?;
 
-    Ok(conn.last_insert_rowid())
+    Ok(id)
 }
 
-pub fn get_artifact_by_path(conn: &mut Connection, path: &str) -> BBResult<Option<Artifact>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, path, produced_by, description, version, refs, created_at
-         FROM artifacts WHERE path = ?1",
-    )?;
+pub fn get_artifact_by_path(conn: &Connection, path: &str) -> BBResult<Option<Artifact>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ARTIFACT_COLUMNS} FROM artifacts a WHERE a.path = ?1"
+    ))?;
 
     let mut rows = stmt.query(params![path])?;
 
@@ -45,9 +159,12 @@ pub fn get_artifact_by_path(conn: &mut Connection, path: &str) -> BBResult<Optio
     }
 }
 
+/// Lists artifacts, newest first. `under` keeps the artifact at that path
+/// and everything below it, including globs such as `src/auth/**`.
 pub fn list_artifacts(
     conn: &mut Connection,
     produced_by: Option<&str>,
+    under: Option<&str>,
     ref_where: Option<&str>,
     ref_what: Option<&str>,
     ref_ref: Option<&str>,
@@ -55,10 +172,7 @@ pub fn list_artifacts(
 ) -> BBResult<Vec<Artifact>> {
     let limit = limit.min(100);
 
-    let mut sql = String::from(
-        "SELECT DISTINCT a.id, a.path, a.produced_by, a.description, a.version, a.refs, a.created_at
-         FROM artifacts a WHERE 1=1"
-    );
+    let mut sql = format!("SELECT DISTINCT {ARTIFACT_COLUMNS} FROM artifacts a WHERE 1=1");
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
     if let Some(producer) = produced_by {
@@ -66,6 +180,12 @@ pub fn list_artifacts(
         params.push(Box::new(producer.to_string()));
     }
 
+    if let Some(prefix) = under {
+        sql.push_str(" AND (a.path = ? OR a.path LIKE ? ESCAPE '\\')");
+        params.push(Box::new(prefix.to_string()));
+        params.push(Box::new(format!("{}/%", escape_like(prefix))));
+    }
+
     // Reference filtering
     if let (Some(where_), Some(what), Some(ref_val)) = (ref_where, ref_what, ref_ref) {
         sql.push_str(
@@ -85,7 +205,7 @@ pub fn list_artifacts(
         }
     }
 
-    sql.push_str(" ORDER BY a.created_at DESC LIMIT ?");
+    sql.push_str(" ORDER BY a.updated_at DESC LIMIT ?");
     params.push(Box::new(limit as i64));
 
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
@@ -98,6 +218,22 @@ pub fn list_artifacts(
     Ok(artifacts)
 }
 
+/// Directory and glob artifacts that could cover `path`: directories it is
+/// under, and every glob. Callers match the globs themselves.
+pub fn list_covering_artifacts(conn: &mut Connection, path: &str) -> BBResult<Vec<Artifact>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ARTIFACT_COLUMNS}
+         FROM artifacts a
+         WHERE substr(?1, 1, length(a.path) + 1) = a.path || '/'
+            OR a.path GLOB '*[*?]*'
+         ORDER BY length(a.path) DESC"
+    ))?;
+    let artifacts = stmt
+        .query_map(params![path], row_to_artifact)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(artifacts)
+}
+
 pub fn find_artifacts_by_ref(
     conn: &mut Connection,
     where_: &str,
@@ -111,8 +247,8 @@ pub fn find_artifacts_by_ref(
         _ => ref_.to_string(),
     };
 
-    let mut stmt = conn.prepare(
-        "SELECT a.id, a.path, a.produced_by, a.description, a.version, a.refs, a.created_at
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ARTIFACT_COLUMNS}
          FROM artifacts a
          WHERE EXISTS (
              SELECT 1 FROM json_each(a.refs)
@@ -120,8 +256,8 @@ pub fn find_artifacts_by_ref(
                AND json_extract(value, '$.what') = ?2
                AND CAST(json_extract(value, '$.ref') AS TEXT) = CAST(?3 AS TEXT)
          )
-         ORDER BY a.created_at DESC",
-    )?;
+         ORDER BY a.updated_at DESC"
+    ))?;
 
     let artifacts = stmt
         .query_map(params![where_, what, ref_param], row_to_artifact)?
@@ -130,18 +266,176 @@ pub fn find_artifacts_by_ref(
     Ok(artifacts)
 }
 
+/// Paths and registration times of artifacts registered or updated after
+/// `since`, oldest first. Timestamps are compared as RFC 3339 strings.
+pub fn list_artifact_updates_since(
+    conn: &mut Connection,
+    since: Option<&str>,
+) -> BBResult<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT path, updated_at FROM artifacts
+         WHERE ?1 IS NULL OR updated_at > ?1
+         ORDER BY updated_at ASC",
+    )?;
+    let updates = stmt
+        .query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(updates)
+}
+
+/// Every registration of artifact `artifact_id`, newest first.
+pub fn list_artifact_revisions(
+    conn: &mut Connection,
+    artifact_id: i64,
+) -> BBResult<Vec<ArtifactRevision>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {REVISION_COLUMNS} FROM artifact_revisions
+         WHERE artifact_id = ?1
+         ORDER BY id DESC"
+    ))?;
+    let revisions = stmt
+        .query_map(params![artifact_id], row_to_revision)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(revisions)
+}
+
+/// The id of the artifact most recently removed from `path`, if any.
+pub fn removed_artifact_id(conn: &mut Connection, path: &str) -> BBResult<Option<i64>> {
+    let id = conn
+        .query_row(
+            "SELECT artifact_id FROM artifact_revisions
+             WHERE path = ?1 AND removed = 1
+             ORDER BY id DESC LIMIT 1",
+            params![path],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id)
+}
+
+/// Deletes the artifact at `path`. Its revisions are kept, and a last one,
+/// marked removed, records that `removed_by` removed it at `removed_at`.
+/// Artifacts linked to it lose those links, and each gets a revision by
+/// `removed_by` recording the links it has left. Returns the number of
+/// artifacts deleted.
+pub fn delete_artifact(
+    conn: &mut Connection,
+    path: &str,
+    removed_by: &str,
+    removed_at: DateTime<Utc>,
+) -> BBResult<usize> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let downstream: Vec<String> = tx
+        .prepare(
+            "SELECT DISTINCT d.path FROM artifact_links l
+             JOIN artifacts d ON d.id = l.artifact_id
+             JOIN artifacts u ON u.id = l.upstream_id
+             WHERE u.path = ?1 AND d.path != ?1
+             ORDER BY d.path",
+        )?
+        .query_map(params![path], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    insert_current_revision(&tx, path, removed_by, removed_at, true, None)?;
+    // Links to the artifact go with it (ON DELETE CASCADE)
+    let count = tx.execute("DELETE FROM artifacts WHERE path = ?1", params![path])?;
+    for downstream_path in &downstream {
+        insert_current_revision(&tx, downstream_path, removed_by, removed_at, false, None)?;
+    }
+    tx.commit()?;
+    Ok(count)
+}
+
+/// Moves the artifact at `old` to `new`, keeping its id, refs and revisions,
+/// and records that `renamed_by` moved it at `renamed_at` as a new revision.
+/// Returns the number of artifacts moved. Run it in a transaction, so the
+/// move and its revision land together.
+pub fn rename_artifact(
+    conn: &Connection,
+    old: &str,
+    new: &str,
+    renamed_by: &str,
+    renamed_at: DateTime<Utc>,
+) -> BBResult<usize> {
+    let count = conn.execute(
+        "UPDATE artifacts SET path = ?2 WHERE path = ?1",
+        params![old, new],
+    )?;
+    insert_current_revision(conn, new, renamed_by, renamed_at, false, Some(old))?;
+    Ok(count)
+}
+
+/// Records the artifact at `path`, as it is now, as a revision made by `by`
+/// at `at`. `removed` and `renamed_from` say what the revision records.
+fn insert_current_revision(
+    conn: &Connection,
+    path: &str,
+    by: &str,
+    at: DateTime<Utc>,
+    removed: bool,
+    renamed_from: Option<&str>,
+) -> BBResult<usize> {
+    let count = conn.execute(
+        &format!(
+            "INSERT INTO artifact_revisions
+                 (artifact_id, path, produced_by, description, version, refs,
+                  content_hash, size, mtime, registered_at, git_head, git_dirty, git_last_commit,
+                  removed, depends_on, derived_from, renamed_from)
+             SELECT a.id, a.path, ?2, a.description, a.version, a.refs,
+                    a.content_hash, a.size, a.mtime, ?3, a.git_head, a.git_dirty,
+                    a.git_last_commit, ?4, {}, {}, ?5
+             FROM artifacts a WHERE a.path = ?1",
+            linked_paths("a.id", ArtifactLink::DependsOn),
+            linked_paths("a.id", ArtifactLink::DerivedFrom)
+        ),
+        params![path, by, at.to_rfc3339(), removed, renamed_from],
+    )?;
+    Ok(count)
+}
+
+/// Every link between artifacts, by path.
+pub fn list_artifact_links(conn: &mut Connection) -> BBResult<Vec<ArtifactEdge>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.path, u.path, l.link
+         FROM artifact_links l
+         JOIN artifacts d ON d.id = l.artifact_id
+         JOIN artifacts u ON u.id = l.upstream_id
+         ORDER BY d.path, u.path, l.link",
+    )?;
+    let edges = stmt
+        .query_map([], |row| {
+            let link: String = row.get(2)?;
+            Ok(ArtifactEdge {
+                from: row.get(0)?,
+                to: row.get(1)?,
+                link: link.parse().map_err(|e: String| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        2,
+                        rusqlite::types::Type::Text,
+                        e.into(),
+                    )
+                })?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(edges)
+}
+
+/// Deletes every artifact and every revision.
 pub fn clear_artifacts(conn: &mut Connection) -> BBResult<usize> {
-    let count = conn.execute("DELETE FROM artifacts", [])?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let count = tx.execute("DELETE FROM artifacts", [])?;
+    tx.execute("DELETE FROM artifact_revisions", [])?;
+    tx.commit()?;
     Ok(count)
 }
 
-fn row_to_artifact(row: &rusqlite::Row) -> Result<Artifact, rusqlite::Error> {
+/// Maps a row that starts with `ARTIFACT_COLUMNS`.
+pub(crate) fn row_to_artifact(row: &rusqlite::Row) -> Result<Artifact, rusqlite::Error> {
     let refs_json: String = row.get(5)?;
-    let created_at_str: String = row.get(6)?;
-
-    let refs: Vec<Reference> = serde_json::from_str(&refs_json).map_err(|e| {
-        rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
-    })?;
+    let first_registered_at: String = row.get(6)?;
+    let updated_at: String = row.get(7)?;
+    let mtime: Option<String> = row.get(10)?;
 
     Ok(Artifact {
         id: row.get(0)?,
@@ -149,19 +443,87 @@ fn row_to_artifact(row: &rusqlite::Row) -> Result<Artifact, rusqlite::Error> {
         produced_by: row.get(2)?,
         description: row.get(3)?,
         version: row.get(4)?,
-        refs,
-        created_at: DateTime::parse_from_rfc3339(&created_at_str)
-            .map_err(|e| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    6,
-                    rusqlite::types::Type::Text,
-                    Box::new(e),
-                )
-            })?
-            .with_timezone(&Utc),
+        refs: parse_refs(&refs_json, 5)?,
+        first_registered_at: parse_timestamp(&first_registered_at, 6)?,
+        updated_at: parse_timestamp(&updated_at, 7)?,
+        content_hash: row.get(8)?,
+        size: row.get(9)?,
+        mtime: mtime.map(|t| parse_timestamp(&t, 10)).transpose()?,
+        git: git_version(row, 11)?,
+        depends_on: parse_paths(row, 14)?,
+        derived_from: parse_paths(row, 15)?,
     })
 }
 
+fn row_to_revision(row: &rusqlite::Row) -> Result<ArtifactRevision, rusqlite::Error> {
+    let refs_json: String = row.get(6)?;
+    let mtime: Option<String> = row.get(9)?;
+    let registered_at: String = row.get(10)?;
+
+    Ok(ArtifactRevision {
+        id: row.get(0)?,
+        artifact_id: row.get(1)?,
+        path: row.get(2)?,
+        produced_by: row.get(3)?,
+        description: row.get(4)?,
+        version: row.get(5)?,
+        refs: parse_refs(&refs_json, 6)?,
+        content_hash: row.get(7)?,
+        size: row.get(8)?,
+        mtime: mtime.map(|t| parse_timestamp(&t, 9)).transpose()?,
+        git: git_version(row, 11)?,
+        registered_at: parse_timestamp(&registered_at, 10)?,
+        removed: row.get(14)?,
+        depends_on: parse_paths(row, 15)?,
+        derived_from: parse_paths(row, 16)?,
+        renamed_from: row.get(17)?,
+    })
+}
+
+/// Maps the `git_head, git_dirty, git_last_commit` columns starting at `idx`.
+fn git_version(row: &rusqlite::Row, idx: usize) -> Result<Option<GitVersion>, rusqlite::Error> {
+    let head: Option<String> = row.get(idx)?;
+    Ok(match head {
+        Some(head) => Some(GitVersion {
+            head,
+            dirty: row.get::<_, Option<bool>>(idx + 1)?.unwrap_or(false),
+            last_commit: row.get(idx + 2)?,
+        }),
+        None => None,
+    })
+}
+
+/// Escapes `%`, `_` and `\` for a LIKE pattern with `ESCAPE '\'`.
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Maps a JSON array of paths, sorted.
+fn parse_paths(row: &rusqlite::Row, idx: usize) -> Result<Vec<String>, rusqlite::Error> {
+    let json: String = row.get(idx)?;
+    let mut paths: Vec<String> = serde_json::from_str(&json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn parse_refs(s: &str, idx: usize) -> Result<Vec<Reference>, rusqlite::Error> {
+    serde_json::from_str(s).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn parse_timestamp(s: &str, idx: usize) -> Result<DateTime<Utc>, rusqlite::Error> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -183,7 +545,14 @@ mod tests {
             description: "test artifact".to_string(),
             version: Some("v1.0".to_string()),
             refs: vec![],
-            created_at: Utc::now(),
+            first_registered_at: Utc::now(),
+            updated_at: Utc::now(),
+            content_hash: None,
+            size: None,
+            mtime: None,
+            git: None,
+            depends_on: vec![],
+            derived_from: vec![],
         }
     }
 
@@ -192,12 +561,10 @@ mod tests {
         let mut conn = setup();
         let artifact = create_test_artifact("src/main.rs");
 
-        let id = upsert_artifact(&mut conn, &artifact).unwrap();
+        let id = upsert_artifact(&mut conn, &artifact, None).unwrap();
         assert!(id > 0);
 
-        let retrieved = get_artifact_by_path(&mut conn, "src/main.rs")
-            .unwrap()
-            .unwrap();
+        let retrieved = get_artifact_by_path(&conn, "src/main.rs").unwrap().unwrap();
         assert_eq!(retrieved.path, "src/main.rs");
         assert_eq!(retrieved.produced_by, "agent-1");
     }
@@ -207,32 +574,52 @@ mod tests {
         let mut conn = setup();
         let artifact = create_test_artifact("src/main.rs");
 
-        upsert_artifact(&mut conn, &artifact).unwrap();
+        upsert_artifact(&mut conn, &artifact, None).unwrap();
 
         let mut updated = artifact.clone();
         updated.description = "updated description".to_string();
         updated.produced_by = "agent-2".to_string();
-        upsert_artifact(&mut conn, &updated).unwrap();
+        upsert_artifact(&mut conn, &updated, None).unwrap();
 
-        let retrieved = get_artifact_by_path(&mut conn, "src/main.rs")
-            .unwrap()
-            .unwrap();
+        let retrieved = get_artifact_by_path(&conn, "src/main.rs").unwrap().unwrap();
         assert_eq!(retrieved.description, "updated description");
         assert_eq!(retrieved.produced_by, "agent-2");
     }
 
+    #[test]
+    fn test_upsert_records_revisions() {
+        let mut conn = setup();
+        let first = create_test_artifact("src/main.rs");
+        let id = upsert_artifact(&mut conn, &first, None).unwrap();
+
+        let mut second = first.clone();
+        second.produced_by = "agent-2".to_string();
+        second.first_registered_at = first.updated_at + chrono::Duration::minutes(5);
+        second.updated_at = second.first_registered_at;
+        assert_eq!(upsert_artifact(&mut conn, &second, None).unwrap(), id);
+
+        let latest = get_artifact_by_path(&conn, "src/main.rs").unwrap().unwrap();
+        assert_eq!(latest.first_registered_at, first.first_registered_at);
+        assert_eq!(latest.updated_at, second.updated_at);
+
+        let revisions = list_artifact_revisions(&mut conn, id).unwrap();
+        let producers: Vec<_> = revisions.iter().map(|r| r.produced_by.as_str()).collect();
+        assert_eq!(producers, vec!["agent-2", "agent-1"]);
+    }
+
     #[test]
     fn test_list_artifacts_by_producer() {
         let mut conn = setup();
 
         let artifact1 = create_test_artifact("src/main.rs");
-        upsert_artifact(&mut conn, &artifact1).unwrap();
+        upsert_artifact(&mut conn, &artifact1, None).unwrap();
 
         let mut artifact2 = create_test_artifact("src/lib.rs");
         artifact2.produced_by = "agent-2".to_string();
-        upsert_artifact(&mut conn, &artifact2).unwrap();
+        upsert_artifact(&mut conn, &artifact2, None).unwrap();
 
-        let results = list_artifacts(&mut conn, Some("agent-1"), None, None, None, 10).unwrap();
+        let results =
+            list_artifacts(&mut conn, Some("agent-1"), None, None, None, None, 10).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].path, "src/main.rs");
     }
@@ -247,24 +634,78 @@ mod tests {
             what: "task".to_string(),
             ref_: serde_json::json!(13),
         }];
-        upsert_artifact(&mut conn, &artifact).unwrap();
+        upsert_artifact(&mut conn, &artifact, None).unwrap();
 
         let results =
             find_artifacts_by_ref(&mut conn, "tt", "task", &serde_json::json!(13)).unwrap();
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn test_artifact_links() {
+        let mut conn = setup();
+        let schema = upsert_artifact(&mut conn, &create_test_artifact("schema.sql"), None).unwrap();
+        let config =
+            upsert_artifact(&mut conn, &create_test_artifact("config.toml"), None).unwrap();
+        let api_artifact = create_test_artifact("gen/api.rs");
+        let api = upsert_artifact(
+            &mut conn,
+            &api_artifact,
+            Some(&[
+                (schema, ArtifactLink::DerivedFrom),
+                (config, ArtifactLink::DependsOn),
+            ]),
+        )
+        .unwrap();
+        let artifact = get_artifact_by_path(&conn, "gen/api.rs").unwrap().unwrap();
+        assert_eq!(artifact.derived_from, vec!["schema.sql"]);
+        assert_eq!(artifact.depends_on, vec!["config.toml"]);
+        assert_eq!(list_artifact_links(&mut conn).unwrap().len(), 2);
+
+        // No links keeps them; given links replace them. Each revision
+        // records the links it was registered with.
+        upsert_artifact(&mut conn, &api_artifact, None).unwrap();
+        upsert_artifact(
+            &mut conn,
+            &api_artifact,
+            Some(&[(schema, ArtifactLink::DependsOn)]),
+        )
+        .unwrap();
+        let revisions = list_artifact_revisions(&mut conn, api).unwrap();
+        let depends_on: Vec<_> = revisions.iter().map(|r| r.depends_on.clone()).collect();
+        assert_eq!(
+            depends_on,
+            [vec!["schema.sql"], vec!["config.toml"], vec!["config.toml"]]
+        );
+        assert!(revisions[0].derived_from.is_empty());
+        assert_eq!(revisions[2].derived_from, vec!["schema.sql"]);
+
+        // Removing an upstream drops its links, not the revisions' record of
+        // them, and records the loss as a revision by whoever removed it
+        delete_artifact(&mut conn, "schema.sql", "cleaner", Utc::now()).unwrap();
+        let artifact = get_artifact_by_path(&conn, "gen/api.rs").unwrap().unwrap();
+        assert!(artifact.depends_on.is_empty());
+        assert!(artifact.derived_from.is_empty());
+        assert!(list_artifact_links(&mut conn).unwrap().is_empty());
+        let revisions = list_artifact_revisions(&mut conn, api).unwrap();
+        assert_eq!(revisions.len(), 4);
+        assert_eq!(revisions[0].produced_by, "cleaner");
+        assert!(!revisions[0].removed);
+        assert!(revisions[0].depends_on.is_empty());
+        assert_eq!(revisions[1].depends_on, vec!["schema.sql"]);
+    }
+
     #[test]
     fn test_clear_artifacts() {
         let mut conn = setup();
 
         let artifact = create_test_artifact("src/main.rs");
-        upsert_artifact(&mut conn, &artifact).unwrap();
+        upsert_artifact(&mut conn, &artifact, None).unwrap();
 
         let cleared = clear_artifacts(&mut conn).unwrap();
         assert_eq!(cleared, 1);
 
-        let retrieved = get_artifact_by_path(&mut conn, "src/main.rs").unwrap();
+        let retrieved = get_artifact_by_path(&conn, "src/main.rs").unwrap();
         assert!(retrieved.is_none());
     }
 }
@@ -0,0 +1,264 @@
+use crate::core::errors::BBResult;
+use crate::core::models::handoff::{Handoff, HandoffStatus};
+use crate::core::models::message::Priority;
+use crate::core::models::reference::Reference;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+
+// Handoff queries take `&Connection` so they can share an IMMEDIATE
+// transaction with the message that announces each transition.
+
+const HANDOFF_COLUMNS: &str = "id, from_agent, to_agent, summary, refs, artifacts, priority, \
+     status, message_id, created_at, escalate_at, escalated, responded_at, response";
+
+/// Filters for `list_handoffs`; set fields are combined with AND.
+#[derive(Debug, Clone, Default)]
+pub struct HandoffFilter {
+    pub to: Option<String>,
+    pub from: Option<String>,
+    pub status: Option<HandoffStatus>,
+}
+
+pub fn insert_handoff(conn: &Connection, handoff: &Handoff) -> BBResult<i64> {
+    let id = conn.query_row(
+        "INSERT INTO handoffs (from_agent, to_agent, summary, refs, artifacts, priority, status,
+                               message_id, created_at, escalate_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         RETURNING id",
+        params![
+            handoff.from_agent,
+            handoff.to_agent,
+            handoff.summary,
+            serde_json::to_string(&handoff.refs)?,
+            serde_json::to_string(&handoff.artifacts)?,
+            handoff.priority.as_str(),
+            handoff.status.as_str(),
+            handoff.message_id,
+            handoff.created_at.to_rfc3339(),
+            handoff.escalate_at.to_rfc3339()
+        ],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+pub fn get_handoff(conn: &Connection, id: i64) -> BBResult<Option<Handoff>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {HANDOFF_COLUMNS} FROM handoffs WHERE id = ?1"
+    ))?;
+    let mut rows = stmt.query(params![id])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(row_to_handoff(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Handoffs matching `filter`, newest first.
+pub fn list_handoffs(
+    conn: &Connection,
+    filter: &HandoffFilter,
+    limit: usize,
+) -> BBResult<Vec<Handoff>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {HANDOFF_COLUMNS} FROM handoffs
+         WHERE (?1 IS NULL OR to_agent = ?1)
+           AND (?2 IS NULL OR from_agent = ?2)
+           AND (?3 IS NULL OR status = ?3)
+         ORDER BY id DESC
+         LIMIT ?4"
+    ))?;
+
+    let handoffs = stmt
+        .query_map(
+            params![
+                filter.to,
+                filter.from,
+                filter.status.map(|s| s.as_str()),
+                limit as i64
+            ],
+            row_to_handoff,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(handoffs)
+}
+
+/// Pending handoffs due for escalation at `now`, oldest first.
+pub fn list_handoffs_to_escalate(conn: &Connection, now: DateTime<Utc>) -> BBResult<Vec<Handoff>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {HANDOFF_COLUMNS} FROM handoffs
+         WHERE status = 'pending' AND escalated = 0 AND escalate_at <= ?1
+         ORDER BY id"
+    ))?;
+
+    let handoffs = stmt
+        .query_map(params![now.to_rfc3339()], row_to_handoff)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(handoffs)
+}
+
+/// When the next pending handoff falls due for escalation, if any does.
+pub fn next_escalation_at(conn: &Connection) -> BBResult<Option<DateTime<Utc>>> {
+    let at: Option<String> = conn.query_row(
+        "SELECT MIN(escalate_at) FROM handoffs WHERE status = 'pending' AND escalated = 0",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(at.map(|at| parse_timestamp(&at, 0)).transpose()?)
+}
+
+pub fn set_handoff_message(conn: &Connection, id: i64, message_id: i64) -> BBResult<usize> {
+    let count = conn.execute(
+        "UPDATE handoffs SET message_id = ?2 WHERE id = ?1",
+        params![id, message_id],
+    )?;
+    Ok(count)
+}
+
+pub fn mark_escalated(conn: &Connection, id: i64, priority: Priority) -> BBResult<usize> {
+    let count = conn.execute(
+        "UPDATE handoffs SET escalated = 1, priority = ?2 WHERE id = ?1",
+        params![id, priority.as_str()],
+    )?;
+    Ok(count)
+}
+
+/// Moves a pending handoff to `status`. Returns 0 if it was not pending.
+pub fn respond_to_handoff(
+    conn: &Connection,
+    id: i64,
+    status: HandoffStatus,
+    response: Option<&str>,
+    at: DateTime<Utc>,
+) -> BBResult<usize> {
+    let count = conn.execute(
+        "UPDATE handoffs SET status = ?2, response = ?3, responded_at = ?4
+         WHERE id = ?1 AND status = 'pending'",
+        params![id, status.as_str(), response, at.to_rfc3339()],
+    )?;
+    Ok(count)
+}
+
+fn parse_timestamp(s: &str, idx: usize) -> Result<DateTime<Utc>, rusqlite::Error> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(s: &str, idx: usize) -> Result<T, rusqlite::Error> {
+    serde_json::from_str(s).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn row_to_handoff(row: &rusqlite::Row) -> Result<Handoff, rusqlite::Error> {
+    let refs: Vec<Reference> = parse_json(&row.get::<_, String>(4)?, 4)?;
+    let responded_at: Option<String> = row.get(12)?;
+
+    Ok(Handoff {
+        id: row.get(0)?,
+        from_agent: row.get(1)?,
+        to_agent: row.get(2)?,
+        summary: row.get(3)?,
+        refs,
+        artifacts: parse_json(&row.get::<_, String>(5)?, 5)?,
+        priority: Priority::parse(&row.get::<_, String>(6)?),
+        status: HandoffStatus::parse(&row.get::<_, String>(7)?),
+        message_id: row.get(8)?,
+        created_at: parse_timestamp(&row.get::<_, String>(9)?, 9)?,
+        escalate_at: parse_timestamp(&row.get::<_, String>(10)?, 10)?,
+        escalated: row.get(11)?,
+        responded_at: responded_at.map(|s| parse_timestamp(&s, 12)).transpose()?,
+        response: row.get(13)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use chrono::Duration;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn handoff(to: &str, escalate_in: Duration) -> Handoff {
+        let now = Utc::now();
+        Handoff {
+            id: 0,
+            from_agent: "agent-1".to_string(),
+            to_agent: to.to_string(),
+            summary: "Finish the parser".to_string(),
+            refs: vec![],
+            artifacts: vec!["src/parser.rs".to_string()],
+            priority: Priority::Normal,
+            status: HandoffStatus::Pending,
+            message_id: None,
+            created_at: now,
+            escalate_at: now + escalate_in,
+            escalated: false,
+            responded_at: None,
+            response: None,
+        }
+    }
+
+    #[test]
+    fn test_insert_and_respond() {
+        let conn = setup();
+        let id = insert_handoff(&conn, &handoff("agent-2", Duration::minutes(30))).unwrap();
+
+        let stored = get_handoff(&conn, id).unwrap().unwrap();
+        assert_eq!(stored.artifacts, vec!["src/parser.rs".to_string()]);
+        assert_eq!(stored.status, HandoffStatus::Pending);
+
+        let now = Utc::now();
+        assert_eq!(
+            respond_to_handoff(&conn, id, HandoffStatus::Declined, Some("busy"), now).unwrap(),
+            1
+        );
+        // Only a pending handoff can be answered
+        assert_eq!(
+            respond_to_handoff(&conn, id, HandoffStatus::Accepted, None, now).unwrap(),
+            0
+        );
+
+        let stored = get_handoff(&conn, id).unwrap().unwrap();
+        assert_eq!(stored.status, HandoffStatus::Declined);
+        assert_eq!(stored.response.as_deref(), Some("busy"));
+        assert!(stored.responded_at.is_some());
+    }
+
+    #[test]
+    fn test_list_handoffs_filters() {
+        let conn = setup();
+        insert_handoff(&conn, &handoff("agent-2", Duration::minutes(30))).unwrap();
+        let overdue = insert_handoff(&conn, &handoff("agent-3", Duration::minutes(-1))).unwrap();
+
+        let to_agent_2 = HandoffFilter {
+            to: Some("agent-2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list_handoffs(&conn, &to_agent_2, 10).unwrap().len(), 1);
+
+        let due = list_handoffs_to_escalate(&conn, Utc::now()).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, overdue);
+
+        mark_escalated(&conn, overdue, Priority::High).unwrap();
+        assert!(
+            list_handoffs_to_escalate(&conn, Utc::now())
+                .unwrap()
+                .is_empty()
+        );
+        let stored = get_handoff(&conn, overdue).unwrap().unwrap();
+        assert!(stored.escalated);
+        assert_eq!(stored.priority, Priority::High);
+    }
+}
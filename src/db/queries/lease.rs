@@ -0,0 +1,143 @@
+use crate::core::errors::BBResult;
+use crate::core::models::lease::{Lease, LeaseMode};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+
+// Lease queries take `&Connection` so they can run inside the IMMEDIATE
+// transaction that makes claim-check-insert atomic.
+
+/// Inserts a lease, or renews the holder's existing lease on the same path.
+pub fn upsert_lease(conn: &Connection, lease: &Lease) -> BBResult<i64> {
+    let id = conn.query_row(
+        "INSERT INTO leases (path, holder, mode, acquired_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(path, holder) DO UPDATE SET
+             mode = excluded.mode,
+             acquired_at = excluded.acquired_at,
+             expires_at = excluded.expires_at
+         RETURNING id",
+        params![
+            lease.path,
+            lease.holder,
+            lease.mode.as_str(),
+            lease.acquired_at.to_rfc3339(),
+            lease.expires_at.to_rfc3339()
+        ],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+/// Every lease, with its holder's `last_seen` (`None` for unknown agents).
+pub fn list_leases_with_holder_last_seen(
+    conn: &Connection,
+) -> BBResult<Vec<(Lease, Option<DateTime<Utc>>)>> {
+    let mut stmt = conn.prepare(
+        "SELECT l.id, l.path, l.holder, l.mode, l.acquired_at, l.expires_at, a.last_seen
+         FROM leases l LEFT JOIN agents a ON a.id = l.holder
+         ORDER BY l.path, l.holder",
+    )?;
+
+    let leases = stmt
+        .query_map([], |row| {
+            let last_seen: Option<String> = row.get(6)?;
+            let last_seen = last_seen.map(|s| parse_timestamp(&s, 6)).transpose()?;
+            Ok((row_to_lease(row)?, last_seen))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(leases)
+}
+
+pub fn delete_expired_leases(conn: &Connection, now: DateTime<Utc>) -> BBResult<usize> {
+    let count = conn.execute(
+        "DELETE FROM leases WHERE expires_at <= ?1",
+        params![now.to_rfc3339()],
+    )?;
+    Ok(count)
+}
+
+pub fn delete_lease(conn: &Connection, path: &str, holder: &str) -> BBResult<usize> {
+    let count = conn.execute(
+        "DELETE FROM leases WHERE path = ?1 AND holder = ?2",
+        params![path, holder],
+    )?;
+    Ok(count)
+}
+
+pub fn delete_leases_by_holder(conn: &Connection, holder: &str) -> BBResult<usize> {
+    let count = conn.execute("DELETE FROM leases WHERE holder = ?1", params![holder])?;
+    Ok(count)
+}
+
+fn parse_timestamp(s: &str, idx: usize) -> Result<DateTime<Utc>, rusqlite::Error> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+fn row_to_lease(row: &rusqlite::Row) -> Result<Lease, rusqlite::Error> {
+    Ok(Lease {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        holder: row.get(2)?,
+        mode: LeaseMode::parse(&row.get::<_, String>(3)?),
+        acquired_at: parse_timestamp(&row.get::<_, String>(4)?, 4)?,
+        expires_at: parse_timestamp(&row.get::<_, String>(5)?, 5)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+    use chrono::Duration;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn lease(path: &str, holder: &str, ttl: Duration) -> Lease {
+        let now = Utc::now();
+        Lease {
+            id: 0,
+            path: path.to_string(),
+            holder: holder.to_string(),
+            mode: LeaseMode::Exclusive,
+            acquired_at: now,
+            expires_at: now + ttl,
+        }
+    }
+
+    #[test]
+    fn test_upsert_lease_renews_existing() {
+        let conn = setup();
+
+        let first =
+            upsert_lease(&conn, &lease("src/a.rs", "agent-1", Duration::minutes(5))).unwrap();
+        let mut renewed = lease("src/a.rs", "agent-1", Duration::minutes(30));
+        renewed.mode = LeaseMode::Shared;
+        let second = upsert_lease(&conn, &renewed).unwrap();
+        assert_eq!(first, second);
+
+        let leases = list_leases_with_holder_last_seen(&conn).unwrap();
+        assert_eq!(leases.len(), 1);
+        assert_eq!(leases[0].0.mode, LeaseMode::Shared);
+        assert!(leases[0].1.is_none());
+    }
+
+    #[test]
+    fn test_delete_expired_leases() {
+        let conn = setup();
+        upsert_lease(&conn, &lease("src/a.rs", "agent-1", Duration::minutes(-1))).unwrap();
+        upsert_lease(&conn, &lease("src/b.rs", "agent-1", Duration::minutes(10))).unwrap();
+
+        assert_eq!(delete_expired_leases(&conn, Utc::now()).unwrap(), 1);
+        assert_eq!(delete_lease(&conn, "src/b.rs", "agent-2").unwrap(), 0);
+        assert_eq!(delete_leases_by_holder(&conn, "agent-1").unwrap(), 1);
+    }
+}
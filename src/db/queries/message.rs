@@ -1,11 +1,30 @@
 use crate::core::errors::BBResult;
-use crate::core::models::message::{Message, Priority};
+use crate::core::models::message::{Message, Priority, ThreadMessage};
 use crate::core::models::reference::Reference;
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
 use serde_json::Value as JsonValue;
 
+/// Columns read by `row_to_message`, over `messages m`. Recipients come back
+/// as a JSON array in insertion order.
+pub(crate) const MESSAGE_COLUMNS: &str =
+    "m.id, m.from_agent, m.content, m.tags, m.priority, m.in_reply_to, m.refs, m.created_at,
+    (SELECT json_group_array(agent_id) FROM
+        (SELECT agent_id FROM message_recipients WHERE message_id = m.id ORDER BY rowid))";
+
 pub fn insert_message(conn: &mut Connection, message: &Message) -> BBResult<i64> {
+    // Take the write lock up front: preparing the insert reads the FTS index,
+    // and upgrading that read to a write fails at once under contention
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let id = insert_message_in(&tx, message)?;
+    tx.commit()?;
+
+    Ok(id)
+}
+
+/// Inserts a message and its recipients inside the caller's transaction,
+/// which should be IMMEDIATE for the reason given in `insert_message`.
+pub fn insert_message_in(conn: &Connection, message: &Message) -> BBResult<i64> {
     let tags_json = serde_json::to_string(&message.tags)?;
     let refs_json = serde_json::to_string(&message.refs)?;
 
@@ -22,15 +41,22 @@ pub fn insert_message(conn: &mut Connection, message: &Message) -> BBResult<i64>
             message.created_at.to_rfc3339()
         ],
     )?;
+    let id = conn.last_insert_rowid();
 
-    Ok(conn.last_insert_rowid())
+    for agent_id in &message.recipients {
+        conn.execute(
+            "INSERT OR IGNORE INTO message_recipients (message_id, agent_id) VALUES (?1, ?2)",
+            params![id, agent_id],
+        )?;
+    }
+
+    Ok(id)
 }
 
-pub fn get_message(conn: &mut Connection, id: i64) -> BBResult<Option<Message>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, from_agent, content, tags, priority, in_reply_to, refs, created_at
-         FROM messages WHERE id = ?1",
-    )?;
+pub fn get_message(conn: &Connection, id: i64) -> BBResult<Option<Message>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM messages m WHERE m.id = ?1"
+    ))?;
 
     let mut rows = stmt.query(params![id])?;
 
@@ -41,91 +67,219 @@ pub fn get_message(conn: &mut Connection, id: i64) -> BBResult<Option<Message>>
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Filters shared by every message listing query.
+///
+/// All set fields are combined with AND; `tags` uses OR semantics within
+/// itself (SPEC 8.4).
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub after_id: Option<i64>,
+    pub tags: Vec<String>,
+    pub from_agent: Option<String>,
+    pub exclude_from: Option<String>,
+    pub priority: Option<Priority>,
+    pub in_reply_to: Option<i64>,
+    /// Only messages addressed to this agent.
+    pub to: Option<String>,
+    pub ref_where: Option<String>,
+    pub ref_what: Option<String>,
+    pub ref_ref: Option<String>,
+}
+
 pub fn list_messages(
     conn: &mut Connection,
-    since: Option<DateTime<Utc>>,
-    tags: &[String],
-    from_agent: Option<&str>,
-    priority: Option<Priority>,
-    ref_where: Option<&str>,
-    ref_what: Option<&str>,
-    ref_ref: Option<&str>,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<Vec<Message>> {
+    select_messages(conn, filter, "m.created_at DESC, m.id DESC", limit)
+}
+
+/// Like `list_messages`, but returns the oldest matches first so callers can
+/// page forward through new messages without skipping any.
+pub fn list_messages_oldest_first(
+    conn: &mut Connection,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<Vec<Message>> {
+    select_messages(conn, filter, "m.id ASC", limit)
+}
+
+fn select_messages(
+    conn: &mut Connection,
+    filter: &MessageFilter,
+    order_by: &str,
     limit: usize,
 ) -> BBResult<Vec<Message>> {
     let limit = limit.min(100);
 
-    let mut sql = String::from(
-        "SELECT DISTINCT m.id, m.from_agent, m.content, m.tags, m.priority, m.in_reply_to, m.refs, m.created_at
-         FROM messages m WHERE 1=1"
-    );
+    let mut sql = format!("SELECT DISTINCT {MESSAGE_COLUMNS} FROM messages m WHERE 1=1");
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-    if let Some(since) = since {
-        sql.push_str(" AND m.created_at >= ?");
+    push_filter_clauses(filter, &mut sql, &mut params);
+
+    sql.push_str(&format!(" ORDER BY {order_by} LIMIT ?"));
+    params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conn.prepare(&sql)?;
+    let messages = stmt
+        .query_map(&param_refs[..], row_to_message)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(messages)
+}
+
+/// Appends the WHERE clauses for `filter` to a query over `messages m`.
+pub(crate) fn push_filter_clauses(
+    filter: &MessageFilter,
+    sql: &mut String,
+    params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) {
+    push_filter_clauses_on(filter, &MESSAGE_FILTER_COLUMNS, sql, params);
+}
+
+/// The columns that `since`, `from_agent` and the reference filter apply to.
+/// The other filters only apply to `messages m`.
+pub(crate) struct FilterColumns {
+    pub time: &'static str,
+    pub agent: &'static str,
+    pub refs: &'static str,
+}
+
+const MESSAGE_FILTER_COLUMNS: FilterColumns = FilterColumns {
+    time: "m.created_at",
+    agent: "m.from_agent",
+    refs: "m.refs",
+};
+
+/// Like `push_filter_clauses`, over another table's `columns`.
+pub(crate) fn push_filter_clauses_on(
+    filter: &MessageFilter,
+    columns: &FilterColumns,
+    sql: &mut String,
+    params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) {
+    if let Some(since) = filter.since {
+        sql.push_str(&format!(" AND {} >= ?", columns.time));
         params.push(Box::new(since.to_rfc3339()));
     }
 
-    if let Some(from) = from_agent {
-        sql.push_str(" AND m.from_agent = ?");
-        params.push(Box::new(from.to_string()));
+    if let Some(after_id) = filter.after_id {
+        sql.push_str(" AND m.id > ?");
+        params.push(Box::new(after_id));
+    }
+
+    if let Some(from) = &filter.from_agent {
+        sql.push_str(&format!(" AND {} = ?", columns.agent));
+        params.push(Box::new(from.clone()));
+    }
+
+    if let Some(agent) = &filter.exclude_from {
+        sql.push_str(" AND m.from_agent != ?");
+        params.push(Box::new(agent.clone()));
     }
 
-    if let Some(p) = priority {
+    if let Some(p) = filter.priority {
         sql.push_str(
             " AND (CASE m.priority 
-            WHEN 'critical' THEN 4 
-            WHEN 'high' THEN 3 
-            WHEN 'normal' THEN 2 
-            WHEN 'low' THEN 1 
+            WHEN 'critical' THEN 3 
+            WHEN 'high' THEN 2 
+            WHEN 'normal' THEN 1 
+            WHEN 'low' THEN 0 
             END) >= ?",
         );
         params.push(Box::new(p.level() as i64));
     }
 
-    if !tags.is_empty() {
+    if let Some(reply_to) = filter.in_reply_to {
+        sql.push_str(" AND m.in_reply_to = ?");
+        params.push(Box::new(reply_to));
+    }
+
+    if let Some(agent) = &filter.to {
+        sql.push_str(
+            " AND EXISTS (
+            SELECT 1 FROM message_recipients r
+            WHERE r.message_id = m.id AND r.agent_id = ?)",
+        );
+        params.push(Box::new(agent.clone()));
+    }
+
+    if !filter.tags.is_empty() {
         sql.push_str(
             " AND EXISTS (
             SELECT 1 FROM json_each(m.tags) 
             WHERE value IN (",
         );
-        sql.push_str(&tags.iter().map(|_| "?").collect::<Vec<_>>().join(", "));
+        sql.push_str(
+            &filter
+                .tags
+                .iter()
+                .map(|_| "?")
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
         sql.push_str("))");
-        for tag in tags {
+        for tag in &filter.tags {
             params.push(Box::new(tag.clone()));
         }
     }
 
     // Reference filtering
-    if let (Some(where_), Some(what), Some(ref_val)) = (ref_where, ref_what, ref_ref) {
-        sql.push_str(
+    if let (Some(where_), Some(what), Some(ref_val)) =
+        (&filter.ref_where, &filter.ref_what, &filter.ref_ref)
+    {
+        sql.push_str(&format!(
             " AND EXISTS (
-            SELECT 1 FROM json_each(m.refs) 
+            SELECT 1 FROM json_each({}) 
             WHERE json_extract(value, '$.where') = ? 
               AND json_extract(value, '$.what') = ?
               AND json_extract(value, '$.ref') = ?)",
-        );
-        params.push(Box::new(where_.to_string()));
-        params.push(Box::new(what.to_string()));
+            columns.refs
+        ));
+        params.push(Box::new(where_.clone()));
+        params.push(Box::new(what.clone()));
         // Try to parse as number, otherwise use as string
         if let Ok(num) = ref_val.parse::<i64>() {
             params.push(Box::new(num));
         } else {
-            params.push(Box::new(ref_val.to_string()));
+            params.push(Box::new(ref_val.clone()));
         }
     }
+}
 
-    sql.push_str(" ORDER BY m.created_at DESC LIMIT ?");
-    params.push(Box::new(limit as i64));
+pub fn count_messages(conn: &mut Connection, filter: &MessageFilter) -> BBResult<i64> {
+    let mut sql = String::from("SELECT COUNT(*) FROM messages m WHERE 1=1");
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    push_filter_clauses(filter, &mut sql, &mut params);
 
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    let count = conn.query_row(&sql, &param_refs[..], |row| row.get(0))?;
+    Ok(count)
+}
 
-    let mut stmt = conn.prepare(&sql)?;
-    let messages = stmt
-        .query_map(&param_refs[..], row_to_message)?
-        .collect::<Result<Vec<_>, _>>()?;
+/// Highest message ID on the board, or 0 when there are no messages.
+pub fn max_message_id(conn: &mut Connection) -> BBResult<i64> {
+    let id = conn.query_row("SELECT COALESCE(MAX(id), 0) FROM messages", [], |row| {
+        row.get(0)
+    })?;
+    Ok(id)
+}
 
-    Ok(messages)
+/// IDs and `in_reply_to` of messages newer than `after_id`, oldest first.
+pub fn list_message_ids_after(
+    conn: &mut Connection,
+    after_id: i64,
+) -> BBResult<Vec<(i64, Option<i64>)>> {
+    let mut stmt =
+        conn.prepare("SELECT id, in_reply_to FROM messages WHERE id > ?1 ORDER BY id ASC")?;
+    let ids = stmt
+        .query_map(params![after_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
 }
 
 pub fn find_messages_by_ref(
@@ -141,8 +295,8 @@ pub fn find_messages_by_ref(
         _ => ref_.to_string(),
     };
 
-    let mut stmt = conn.prepare(
-        "SELECT m.id, m.from_agent, m.content, m.tags, m.priority, m.in_reply_to, m.refs, m.created_at
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS}
          FROM messages m
          WHERE EXISTS (
              SELECT 1 FROM json_each(m.refs)
@@ -151,7 +305,7 @@ pub fn find_messages_by_ref(
                AND CAST(json_extract(value, '$.ref') AS TEXT) = CAST(?3 AS TEXT)
          )
          ORDER BY m.created_at DESC"
-    )?;
+    ))?;
 
     let messages = stmt
         .query_map(params![where_, what, ref_param], row_to_message)?
@@ -160,34 +314,139 @@ pub fn find_messages_by_ref(
     Ok(messages)
 }
 
-pub fn get_message_replies(conn: &mut Connection, message_id: i64) -> BBResult<Vec<Message>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, from_agent, content, tags, priority, in_reply_to, refs, created_at
-         FROM messages
-         WHERE in_reply_to = ?1
-         ORDER BY created_at ASC
-         LIMIT 50",
-    )?;
+/// ID of the root of the thread containing `message_id`, following
+/// `in_reply_to` upwards, or `None` if the message does not exist.
+pub fn find_thread_root(conn: &Connection, message_id: i64) -> BBResult<Option<i64>> {
+    let root = conn
+        .query_row(
+            "WITH RECURSIVE ancestors(id, in_reply_to, height) AS (
+                 SELECT id, in_reply_to, 0 FROM messages WHERE id = ?1
+                 UNION
+                 SELECT m.id, m.in_reply_to, a.height + 1
+                 FROM messages m JOIN ancestors a ON m.id = a.in_reply_to
+             )
+             SELECT id FROM ancestors ORDER BY height DESC LIMIT 1",
+            params![message_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(root)
+}
+
+/// The reply tree under `root_id`, with each message's depth and a sort key
+/// that puts every message right after its parent, siblings oldest first.
+/// Binds the root as ?1 and the maximum depth (or NULL) as ?2.
+const THREAD_TREE: &str = "WITH RECURSIVE tree(id, depth, path) AS (
+         SELECT id, 0, printf('%020d', id) FROM messages WHERE id = ?1
+         UNION ALL
+         SELECT m.id, t.depth + 1, t.path || '/' || printf('%020d', m.id)
+         FROM messages m JOIN tree t ON m.in_reply_to = t.id
+         WHERE ?2 IS NULL OR t.depth < ?2
+     )";
+
+/// A page of the thread rooted at `root_id`, in depth-first order. Replies
+/// deeper than `max_depth` are left out; `limit` of `None` returns the rest
+/// of the tree.
+pub fn list_thread(
+    conn: &Connection,
+    root_id: i64,
+    max_depth: Option<usize>,
+    offset: usize,
+    limit: Option<usize>,
+) -> BBResult<Vec<ThreadMessage>> {
+    let mut stmt = conn.prepare(&format!(
+        "{THREAD_TREE}
+         SELECT {MESSAGE_COLUMNS}, t.depth
+         FROM tree t JOIN messages m ON m.id = t.id
+         ORDER BY t.path
+         LIMIT ?3 OFFSET ?4"
+    ))?;
 
     let messages = stmt
-        .query_map(params![message_id], row_to_message)?
+        .query_map(
+            params![
+                root_id,
+                max_depth.map(|d| d as i64),
+                limit.map_or(-1, |l| l as i64),
+                offset as i64
+            ],
+            |row| {
+                Ok(ThreadMessage {
+                    message: row_to_message(row)?,
+                    depth: row.get::<_, i64>(9)? as usize,
+                })
+            },
+        )?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(messages)
 }
 
+/// Number of messages `list_thread` would return without paging.
+pub fn count_thread(conn: &Connection, root_id: i64, max_depth: Option<usize>) -> BBResult<usize> {
+    let count: i64 = conn.query_row(
+        &format!("{THREAD_TREE} SELECT COUNT(*) FROM tree"),
+        params![root_id, max_depth.map(|d| d as i64)],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
 pub fn delete_messages_before(conn: &mut Connection, before: DateTime<Utc>) -> BBResult<usize> {
-    let count = conn.execute(
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    tx.execute(
+        "DELETE FROM message_recipients WHERE message_id IN
+             (SELECT id FROM messages WHERE created_at < ?1)",
+        params![before.to_rfc3339()],
+    )?;
+    let count = tx.execute(
         "DELETE FROM messages WHERE created_at < ?1",
         params![before.to_rfc3339()],
     )?;
+    tx.commit()?;
+    Ok(count)
+}
+
+/// How many messages carry each tag, as stored.
+pub fn tag_counts(conn: &Connection) -> BBResult<Vec<(String, i64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.value, COUNT(DISTINCT m.id)
+         FROM messages m, json_each(m.tags) t
+         GROUP BY t.value",
+    )?;
+    let counts = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(counts)
+}
+
+/// Deletes every thread whose newest message is older than `before`, root
+/// and replies together, so no reply is left pointing at a deleted message.
+pub fn delete_threads_before(conn: &Connection, before: DateTime<Utc>) -> BBResult<usize> {
+    let count = conn.execute(
+        "WITH RECURSIVE thread(id, root) AS (
+             SELECT id, id FROM messages
+             WHERE in_reply_to IS NULL OR in_reply_to NOT IN (SELECT id FROM messages)
+             UNION ALL
+             SELECT m.id, t.root FROM messages m JOIN thread t ON m.in_reply_to = t.id
+         )
+         DELETE FROM messages WHERE id IN (
+             SELECT id FROM thread WHERE root IN (
+                 SELECT t.root FROM thread t JOIN messages m ON m.id = t.id
+                 GROUP BY t.root
+                 HAVING MAX(m.created_at) < ?1
+             )
+         )",
+        params![before.to_rfc3339()],
+    )?;
     Ok(count)
 }
 
-fn row_to_message(row: &rusqlite::Row) -> Result<Message, rusqlite::Error> {
+pub(crate) fn row_to_message(row: &rusqlite::Row) -> Result<Message, rusqlite::Error> {
     let tags_json: String = row.get(3)?;
     let refs_json: String = row.get(6)?;
     let created_at_str: String = row.get(7)?;
+    let recipients_json: String = row.get(8)?;
 
     let tags: Vec<String> = serde_json::from_str(&tags_json).map_err(|e| {
         rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
@@ -197,9 +456,14 @@ fn row_to_message(row: &rusqlite::Row) -> Result<Message, rusqlite::Error> {
         rusqlite::Error::FromSqlConversionFailure(6, rusqlite::types::Type::Text, Box::new(e))
     })?;
 
+    let recipients: Vec<String> = serde_json::from_str(&recipients_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(8, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
     Ok(Message {
         id: row.get(0)?,
         from_agent: row.get(1)?,
+        recipients,
         content: row.get(2)?,
         tags,
         priority: Priority::parse(&row.get::<_, String>(4)?),
@@ -234,6 +498,7 @@ mod tests {
         Message {
             id: 0,
             from_agent: id.to_string(),
+            recipients: vec![],
             content: "test content".to_string(),
             tags: vec!["test".to_string()],
             priority: Priority::Normal,
@@ -251,7 +516,7 @@ mod tests {
         let id = insert_message(&mut conn, &message).unwrap();
         assert!(id > 0);
 
-        let retrieved = get_message(&mut conn, id).unwrap().unwrap();
+        let retrieved = get_message(&conn, id).unwrap().unwrap();
         assert_eq!(retrieved.from_agent, "agent-1");
         assert_eq!(retrieved.content, "test content");
     }
@@ -268,22 +533,112 @@ mod tests {
         msg2.tags = vec!["question".to_string()];
         insert_message(&mut conn, &msg2).unwrap();
 
-        let results = list_messages(
-            &mut conn,
-            None,
-            &["decision".to_string()],
-            None,
-            None,
-            None,
-            None,
-            None,
-            10,
-        )
-        .unwrap();
+        let filter = MessageFilter {
+            tags: vec!["decision".to_string()],
+            ..Default::default()
+        };
+        let results = list_messages(&mut conn, &filter, 10).unwrap();
         assert_eq!(results.len(), 1);
         assert!(results[0].tags.contains(&"decision".to_string()));
     }
 
+    #[test]
+    fn test_list_messages_min_priority() {
+        let mut conn = setup();
+
+        for priority in [
+            Priority::Low,
+            Priority::Normal,
+            Priority::High,
+            Priority::Critical,
+        ] {
+            let mut msg = create_test_message("agent-1");
+            msg.priority = priority;
+            insert_message(&mut conn, &msg).unwrap();
+        }
+
+        let filter = MessageFilter {
+            priority: Some(Priority::High),
+            ..Default::default()
+        };
+        let results = list_messages(&mut conn, &filter, 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|m| m.priority >= Priority::High));
+    }
+
+    #[test]
+    fn test_list_messages_after_id_and_reply_to() {
+        let mut conn = setup();
+
+        let parent = insert_message(&mut conn, &create_test_message("agent-1")).unwrap();
+        let mut reply = create_test_message("agent-2");
+        reply.in_reply_to = Some(parent);
+        let reply_id = insert_message(&mut conn, &reply).unwrap();
+        insert_message(&mut conn, &create_test_message("agent-3")).unwrap();
+
+        let filter = MessageFilter {
+            after_id: Some(parent),
+            in_reply_to: Some(parent),
+            ..Default::default()
+        };
+        let results = list_messages(&mut conn, &filter, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, reply_id);
+        assert_eq!(max_message_id(&mut conn).unwrap(), reply_id + 1);
+    }
+
+    #[test]
+    fn test_thread_tree() {
+        let mut conn = setup();
+
+        // 1 <- 2 <- 4, 1 <- 3; 5 stands alone
+        let mut ids = Vec::new();
+        for parent in [None, Some(0), Some(0), Some(1), None] {
+            let mut msg = create_test_message("agent-1");
+            msg.in_reply_to = parent.map(|i: usize| ids[i]);
+            ids.push(insert_message(&mut conn, &msg).unwrap());
+        }
+
+        assert_eq!(find_thread_root(&conn, ids[3]).unwrap(), Some(ids[0]));
+        assert_eq!(find_thread_root(&conn, ids[4]).unwrap(), Some(ids[4]));
+        assert_eq!(find_thread_root(&conn, 999).unwrap(), None);
+
+        let tree = list_thread(&conn, ids[0], None, 0, None).unwrap();
+        let order: Vec<_> = tree.iter().map(|t| (t.message.id, t.depth)).collect();
+        assert_eq!(
+            order,
+            vec![(ids[0], 0), (ids[1], 1), (ids[3], 2), (ids[2], 1)]
+        );
+        assert_eq!(count_thread(&conn, ids[0], None).unwrap(), 4);
+
+        assert_eq!(count_thread(&conn, ids[0], Some(1)).unwrap(), 3);
+        let page = list_thread(&conn, ids[0], None, 1, Some(2)).unwrap();
+        let page: Vec<_> = page.iter().map(|t| t.message.id).collect();
+        assert_eq!(page, vec![ids[1], ids[3]]);
+    }
+
+    #[test]
+    fn test_list_messages_to_recipient() {
+        let mut conn = setup();
+
+        let mut msg = create_test_message("agent-1");
+        msg.recipients = vec!["agent-3".to_string(), "agent-2".to_string()];
+        let id = insert_message(&mut conn, &msg).unwrap();
+        insert_message(&mut conn, &create_test_message("agent-1")).unwrap();
+
+        let filter = MessageFilter {
+            to: Some("agent-2".to_string()),
+            ..Default::default()
+        };
+        let results = list_messages(&mut conn, &filter, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, id);
+        assert_eq!(results[0].recipients, vec!["agent-3", "agent-2"]);
+
+        let other = get_message(&conn, id + 1).unwrap().unwrap();
+        assert!(other.recipients.is_empty());
+    }
+
     #[test]
     fn test_find_messages_by_ref() {
         let mut conn = setup();
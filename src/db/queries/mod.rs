@@ -1,3 +1,8 @@
 pub mod agent;
 pub mod artifact;
+pub mod handoff;
+pub mod lease;
 pub mod message;
+pub mod question;
+pub mod read_cursor;
+pub mod search;
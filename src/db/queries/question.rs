@@ -0,0 +1,213 @@
+use crate::core::errors::BBResult;
+use crate::core::models::question::{Question, QuestionState};
+use crate::db::queries::message::{MESSAGE_COLUMNS, row_to_message};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+
+// Rows are added by the `questions_track` trigger whenever a message tagged
+// `question` is inserted, so there is no insert here.
+
+/// `MESSAGE_COLUMNS` followed by the question's own columns, over
+/// `questions q JOIN messages m`.
+fn question_columns() -> String {
+    format!(
+        "{MESSAGE_COLUMNS}, q.state, q.answer_id, q.resolved_at,
+        (SELECT COUNT(*) FROM messages r WHERE r.in_reply_to = m.id)"
+    )
+}
+
+/// Filters for `list_questions`; set fields are combined with AND.
+#[derive(Debug, Clone, Default)]
+pub struct QuestionFilter {
+    pub state: Option<QuestionState>,
+    /// Only questions addressed to this agent.
+    pub to: Option<String>,
+    pub from: Option<String>,
+    /// Only questions asked before this time.
+    pub before: Option<DateTime<Utc>>,
+}
+
+const FILTER_CLAUSES: &str = "(?1 IS NULL OR q.state = ?1)
+    AND (?2 IS NULL OR EXISTS (
+        SELECT 1 FROM message_recipients r WHERE r.message_id = m.id AND r.agent_id = ?2))
+    AND (?3 IS NULL OR m.from_agent = ?3)
+    AND (?4 IS NULL OR m.created_at < ?4)";
+
+pub fn get_question(conn: &Connection, message_id: i64) -> BBResult<Option<Question>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM questions q JOIN messages m ON m.id = q.message_id
+         WHERE q.message_id = ?1",
+        question_columns()
+    ))?;
+    let mut rows = stmt.query(params![message_id])?;
+
+    match rows.next()? {
+        Some(row) => Ok(Some(row_to_question(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Questions matching `filter`, oldest first.
+pub fn list_questions(
+    conn: &Connection,
+    filter: &QuestionFilter,
+    limit: usize,
+) -> BBResult<Vec<Question>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM questions q JOIN messages m ON m.id = q.message_id
+         WHERE {FILTER_CLAUSES}
+         ORDER BY m.created_at ASC, m.id ASC
+         LIMIT ?5",
+        question_columns()
+    ))?;
+
+    let questions = stmt
+        .query_map(
+            params![
+                filter.state.map(|s| s.as_str()),
+                filter.to,
+                filter.from,
+                filter.before.map(|t| t.to_rfc3339()),
+                limit as i64
+            ],
+            row_to_question,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(questions)
+}
+
+pub fn count_questions(conn: &Connection, filter: &QuestionFilter) -> BBResult<i64> {
+    let count = conn.query_row(
+        &format!(
+            "SELECT COUNT(*) FROM questions q JOIN messages m ON m.id = q.message_id
+             WHERE {FILTER_CLAUSES}"
+        ),
+        params![
+            filter.state.map(|s| s.as_str()),
+            filter.to,
+            filter.from,
+            filter.before.map(|t| t.to_rfc3339())
+        ],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Marks a question answered or closed. An accepted answer replaces any
+/// earlier one; closing keeps it. Returns 0 if the question was closed.
+pub fn resolve_question(
+    conn: &Connection,
+    message_id: i64,
+    state: QuestionState,
+    answer_id: Option<i64>,
+    at: DateTime<Utc>,
+) -> BBResult<usize> {
+    let count = conn.execute(
+        "UPDATE questions
+         SET state = ?2, answer_id = COALESCE(?3, answer_id), resolved_at = ?4
+         WHERE message_id = ?1 AND state != 'closed'",
+        params![message_id, state.as_str(), answer_id, at.to_rfc3339()],
+    )?;
+    Ok(count)
+}
+
+fn row_to_question(row: &rusqlite::Row) -> Result<Question, rusqlite::Error> {
+    let resolved_at: Option<String> = row.get(11)?;
+
+    Ok(Question {
+        message: row_to_message(row)?,
+        state: QuestionState::parse(&row.get::<_, String>(9)?),
+        answer_id: row.get(10)?,
+        resolved_at: resolved_at
+            .map(|s| {
+                DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            11,
+                            rusqlite::types::Type::Text,
+                            Box::new(e),
+                        )
+                    })
+            })
+            .transpose()?,
+        replies: row.get(12)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::{Message, Priority};
+    use crate::db::migrations::run_migrations;
+    use crate::db::queries::message::insert_message;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn post(conn: &mut Connection, tags: &[&str], to: &[&str], in_reply_to: Option<i64>) -> i64 {
+        let message = Message {
+            id: 0,
+            from_agent: "agent-1".to_string(),
+            recipients: to.iter().map(|s| s.to_string()).collect(),
+            content: "Which port does the API use?".to_string(),
+            tags: tags.iter().map(|s| s.to_string()).collect(),
+            priority: Priority::Normal,
+            in_reply_to,
+            refs: vec![],
+            created_at: Utc::now(),
+        };
+        insert_message(conn, &message).unwrap()
+    }
+
+    #[test]
+    fn test_question_tag_is_tracked() {
+        let mut conn = setup();
+        let question = post(&mut conn, &["question"], &["agent-2"], None);
+        post(&mut conn, &["info"], &["agent-2"], None);
+        post(&mut conn, &[], &[], Some(question));
+
+        let questions = list_questions(&conn, &QuestionFilter::default(), 10).unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].message.id, question);
+        assert_eq!(questions[0].state, QuestionState::Open);
+        assert_eq!(questions[0].replies, 1);
+
+        let to_agent_3 = QuestionFilter {
+            to: Some("agent-3".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(count_questions(&conn, &to_agent_3).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_resolve_question() {
+        let mut conn = setup();
+        let question = post(&mut conn, &["question"], &[], None);
+        let reply = post(&mut conn, &[], &[], Some(question));
+        let now = Utc::now();
+
+        assert_eq!(
+            resolve_question(&conn, question, QuestionState::Answered, Some(reply), now).unwrap(),
+            1
+        );
+        assert_eq!(
+            resolve_question(&conn, question, QuestionState::Closed, None, now).unwrap(),
+            1
+        );
+        // A closed question stays closed
+        assert_eq!(
+            resolve_question(&conn, question, QuestionState::Answered, Some(reply), now).unwrap(),
+            0
+        );
+
+        let stored = get_question(&conn, question).unwrap().unwrap();
+        assert_eq!(stored.state, QuestionState::Closed);
+        assert_eq!(stored.answer_id, Some(reply));
+        assert!(stored.resolved_at.is_some());
+    }
+}
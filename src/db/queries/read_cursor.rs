@@ -0,0 +1,127 @@
+use crate::core::errors::BBResult;
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+
+/// Unread counts for one agent, as of its read cursor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UnreadSummary {
+    pub unread: i64,
+    /// Unread messages addressed to the agent.
+    pub addressed: i64,
+    pub critical: i64,
+}
+
+/// Returns the last message ID `agent_id` has read, or 0 if it never has.
+pub fn get_cursor(conn: &mut Connection, agent_id: &str) -> BBResult<i64> {
+    let cursor = conn
+        .query_row(
+            "SELECT last_message_id FROM read_cursors WHERE agent_id = ?1",
+            params![agent_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(cursor.unwrap_or(0))
+}
+
+/// Moves the cursor forward to `last_message_id`. Cursors never move backwards.
+pub fn advance_cursor(conn: &mut Connection, agent_id: &str, last_message_id: i64) -> BBResult<()> {
+    conn.execute(
+        "INSERT INTO read_cursors (agent_id, last_message_id, updated_at)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(agent_id) DO UPDATE SET
+             last_message_id = MAX(last_message_id, excluded.last_message_id),
+             updated_at = excluded.updated_at",
+        params![agent_id, last_message_id, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Counts unread, addressed and critical messages in a single query.
+pub fn unread_summary(conn: &mut Connection, agent_id: &str) -> BBResult<UnreadSummary> {
+    let summary = conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(EXISTS (
+                    SELECT 1 FROM message_recipients r
+                    WHERE r.message_id = m.id AND r.agent_id = ?1)), 0),
+                COALESCE(SUM(m.priority = 'critical'), 0)
+         FROM messages m
+         WHERE m.id > COALESCE(
+                 (SELECT last_message_id FROM read_cursors WHERE agent_id = ?1), 0)
+           AND m.from_agent != ?1",
+        params![agent_id],
+        |row| {
+            Ok(UnreadSummary {
+                unread: row.get(0)?,
+                addressed: row.get(1)?,
+                critical: row.get(2)?,
+            })
+        },
+    )?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::run_migrations;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_get_cursor_defaults_to_zero() {
+        let mut conn = setup();
+        assert_eq!(get_cursor(&mut conn, "agent-1").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_advance_cursor_never_moves_backwards() {
+        let mut conn = setup();
+
+        advance_cursor(&mut conn, "agent-1", 10).unwrap();
+        assert_eq!(get_cursor(&mut conn, "agent-1").unwrap(), 10);
+
+        advance_cursor(&mut conn, "agent-1", 4).unwrap();
+        assert_eq!(get_cursor(&mut conn, "agent-1").unwrap(), 10);
+
+        advance_cursor(&mut conn, "agent-1", 12).unwrap();
+        assert_eq!(get_cursor(&mut conn, "agent-1").unwrap(), 12);
+    }
+
+    #[test]
+    fn test_unread_summary_counts_after_cursor() {
+        let mut conn = setup();
+        for (from, content, priority) in [
+            ("agent-2", "old news", "normal"),
+            ("agent-2", "please review", "normal"),
+            ("agent-2", "build is broken", "critical"),
+            ("agent-1", "note to self", "critical"),
+        ] {
+            conn.execute(
+                "INSERT INTO messages (from_agent, content, priority) VALUES (?1, ?2, ?3)",
+                params![from, content, priority],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO message_recipients (message_id, agent_id) VALUES (2, 'agent-1'), (4, 'agent-1')",
+            [],
+        )
+        .unwrap();
+        advance_cursor(&mut conn, "agent-1", 1).unwrap();
+
+        let summary = unread_summary(&mut conn, "agent-1").unwrap();
+        assert_eq!(
+            summary,
+            UnreadSummary {
+                unread: 2,
+                addressed: 1,
+                critical: 1,
+            }
+        );
+    }
+}
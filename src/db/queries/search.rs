@@ -0,0 +1,299 @@
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::artifact::Artifact;
+use crate::core::models::message::Message;
+use crate::db::queries::artifact::{ARTIFACT_COLUMNS, row_to_artifact};
+use crate::db::queries::message::{
+    FilterColumns, MESSAGE_COLUMNS, MessageFilter, push_filter_clauses, push_filter_clauses_on,
+    row_to_message,
+};
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Markers wrapped around matched terms in snippets.
+pub const SNIPPET_OPEN: &str = "**";
+pub const SNIPPET_CLOSE: &str = "**";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageHit {
+    #[serde(flatten)]
+    pub message: Message,
+    pub snippet: String,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactHit {
+    #[serde(flatten)]
+    pub artifact: Artifact,
+    pub snippet: String,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+/// Full-text search over message content, best matches first.
+pub fn search_messages(
+    conn: &mut Connection,
+    query: &str,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<Vec<MessageHit>> {
+    let limit = limit.min(100);
+
+    let mut sql = format!(
+        "SELECT {MESSAGE_COLUMNS},
+                snippet(messages_fts, 0, ?, ?, '…', 12) AS snippet,
+                bm25(messages_fts) AS rank
+         FROM messages_fts JOIN messages m ON m.id = messages_fts.rowid
+         WHERE messages_fts MATCH ?"
+    );
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![
+        Box::new(SNIPPET_OPEN),
+        Box::new(SNIPPET_CLOSE),
+        Box::new(query.to_string()),
+    ];
+
+    push_filter_clauses(filter, &mut sql, &mut params);
+
+    sql.push_str(" ORDER BY rank, m.id DESC LIMIT ?");
+    params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+    let hits = conn
+        .prepare(&sql)
+        .and_then(|mut stmt| {
+            stmt.query_map(&param_refs[..], |row| {
+                Ok(MessageHit {
+                    message: row_to_message(row)?,
+                    snippet: row.get("snippet")?,
+                    rank: row.get("rank")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(map_match_error)?;
+
+    Ok(hits)
+}
+
+const ARTIFACT_FILTER_COLUMNS: FilterColumns = FilterColumns {
+    time: "a.updated_at",
+    agent: "a.produced_by",
+    refs: "a.refs",
+};
+
+/// Full-text search over artifact descriptions, best matches first.
+///
+/// Only the filters that apply to artifacts are honoured: `from_agent`
+/// matches the producer, plus `since` and the reference filter.
+pub fn search_artifacts(
+    conn: &mut Connection,
+    query: &str,
+    filter: &MessageFilter,
+    limit: usize,
+) -> BBResult<Vec<ArtifactHit>> {
+    let limit = limit.min(100);
+
+    let mut sql = format!(
+        "SELECT {ARTIFACT_COLUMNS},
+                snippet(artifacts_fts, 0, ?, ?, '…', 12) AS snippet,
+                bm25(artifacts_fts) AS rank
+         FROM artifacts_fts JOIN artifacts a ON a.id = artifacts_fts.rowid
+         WHERE artifacts_fts MATCH ?"
+    );
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![
+        Box::new(SNIPPET_OPEN),
+        Box::new(SNIPPET_CLOSE),
+        Box::new(query.to_string()),
+    ];
+
+    let artifact_filter = MessageFilter {
+        since: filter.since,
+        from_agent: filter.from_agent.clone(),
+        ref_where: filter.ref_where.clone(),
+        ref_what: filter.ref_what.clone(),
+        ref_ref: filter.ref_ref.clone(),
+        ..Default::default()
+    };
+    push_filter_clauses_on(
+        &artifact_filter,
+        &ARTIFACT_FILTER_COLUMNS,
+        &mut sql,
+        &mut params,
+    );
+
+    sql.push_str(" ORDER BY rank, a.id DESC LIMIT ?");
+    params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+    let hits = conn
+        .prepare(&sql)
+        .and_then(|mut stmt| {
+            stmt.query_map(&param_refs[..], |row| {
+                Ok(ArtifactHit {
+                    artifact: row_to_artifact(row)?,
+                    snippet: row.get("snippet")?,
+                    rank: row.get("rank")?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(map_match_error)?;
+
+    Ok(hits)
+}
+
+/// FTS5 reports malformed queries as plain `SQLITE_ERROR`s ("unterminated
+/// string", "fts5: syntax error ..."). The surrounding SQL is fixed, so treat
+/// those as invalid input rather than database failures.
+fn map_match_error(e: rusqlite::Error) -> BBError {
+    match &e {
+        rusqlite::Error::SqliteFailure(err, Some(msg)) if err.extended_code == 1 => {
+            BBError::InvalidInput(format!("invalid search query: {msg}"))
+        }
+        _ => BBError::SqliteError(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use crate::db::migrations::run_migrations;
+    use rusqlite::params;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, from: &str, content: &str, priority: &str) {
+        conn.execute(
+            "INSERT INTO messages (from_agent, content, priority, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![from, content, priority, chrono::Utc::now().to_rfc3339()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_search_messages_ranks_and_highlights() {
+        let mut conn = setup();
+        insert(
+            &conn,
+            "agent-1",
+            "We decided to use sqlite for storage",
+            "normal",
+        );
+        insert(&conn, "agent-2", "Lunch plans", "normal");
+        insert(&conn, "agent-2", "sqlite sqlite sqlite everywhere", "high");
+
+        let hits = search_messages(&mut conn, "sqlite", &MessageFilter::default(), 10).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].message.id, 3);
+        assert!(hits[1].snippet.contains("**sqlite**"));
+
+        let filter = MessageFilter {
+            priority: Some(Priority::High),
+            ..Default::default()
+        };
+        let hits = search_messages(&mut conn, "sqlite", &filter, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message.from_agent, "agent-2");
+    }
+
+    #[test]
+    fn test_search_index_follows_deletes() {
+        let mut conn = setup();
+        insert(&conn, "agent-1", "obsolete decision", "normal");
+        conn.execute("DELETE FROM messages", []).unwrap();
+
+        let hits = search_messages(&mut conn, "obsolete", &MessageFilter::default(), 10).unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_search_artifacts_follows_upsert() {
+        let mut conn = setup();
+        for description in ["Token refresh logic", "Session cookie handling"] {
+            conn.execute(
+                "INSERT INTO artifacts
+                     (path, produced_by, description, created_at, first_registered_at, updated_at)
+                 VALUES ('src/auth.rs', 'agent-1', ?1, ?2, ?2, ?2)
+                 ON CONFLICT(path) DO UPDATE SET description = excluded.description",
+                params![description, chrono::Utc::now().to_rfc3339()],
+            )
+            .unwrap();
+        }
+
+        let filter = MessageFilter::default();
+        assert!(
+            search_artifacts(&mut conn, "token", &filter, 10)
+                .unwrap()
+                .is_empty()
+        );
+        let hits = search_artifacts(&mut conn, "cookie", &filter, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].artifact.path, "src/auth.rs");
+    }
+
+    #[test]
+    fn test_search_artifacts_filters() {
+        let mut conn = setup();
+        let now = chrono::Utc::now().to_rfc3339();
+        for (path, producer, refs) in [
+            (
+                "a.rs",
+                "agent-1",
+                r#"[{"where":"tt","what":"task","ref":13}]"#,
+            ),
+            ("b.rs", "agent-2", "[]"),
+        ] {
+            conn.execute(
+                "INSERT INTO artifacts
+                     (path, produced_by, description, refs, created_at, first_registered_at, updated_at)
+                 VALUES (?1, ?2, 'Parser', ?3, ?4, ?4, ?4)",
+                params![path, producer, refs, now],
+            )
+            .unwrap();
+        }
+
+        let mut paths = |filter: &MessageFilter| -> Vec<String> {
+            search_artifacts(&mut conn, "parser", filter, 10)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.artifact.path)
+                .collect()
+        };
+        assert_eq!(paths(&MessageFilter::default()).len(), 2);
+        let by_producer = MessageFilter {
+            from_agent: Some("agent-2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(paths(&by_producer), vec!["b.rs"]);
+        let by_ref = MessageFilter {
+            ref_where: Some("tt".to_string()),
+            ref_what: Some("task".to_string()),
+            ref_ref: Some("13".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(paths(&by_ref), vec!["a.rs"]);
+        let later = MessageFilter {
+            since: Some(chrono::Utc::now() + chrono::Duration::minutes(1)),
+            ..Default::default()
+        };
+        assert!(paths(&later).is_empty());
+    }
+
+    #[test]
+    fn test_search_rejects_malformed_query() {
+        let mut conn = setup();
+        insert(&conn, "agent-1", "hello", "normal");
+
+        let result = search_messages(&mut conn, "\"unbalanced", &MessageFilter::default(), 10);
+        assert!(matches!(result, Err(BBError::InvalidInput(_))));
+    }
+}
@@ -5,7 +5,8 @@ use rusqlite::Connection;
 use std::path::Path;
 
 pub fn init_schema(conn: &Connection) -> BBResult<()> {
-    run_migrations(conn)
+    run_migrations(conn)?;
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -31,16 +31,34 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
         OutputFormat::Human
     };
 
+    // Commands that create or edit the config read it themselves, and
+    // commands that repair or remove a board run even if its config is broken
     match cli.command {
-        Commands::Init => {
+        Commands::Init { .. } | Commands::Install { .. } | Commands::Config { .. } => {}
+        Commands::Migrate { .. } | Commands::Destroy { .. } => {
+            let project_dir = get_project_dir(cli.dir.clone())?;
+            core::config::install(core::config::Config::load_or_default(&project_dir));
+        }
+        _ => {
+            let project_dir = get_project_dir(cli.dir.clone())?;
+            core::config::install(core::config::Config::load(&project_dir)?);
+        }
+    }
+
+    match cli.command {
+        Commands::Init { template } => {
             let project_dir = get_project_dir(cli.dir)?;
-            cli::commands::init::run(&project_dir)
+            cli::commands::init::run(&project_dir, template.as_deref())
         }
         Commands::Install {
             tool,
             global,
             local,
         } => cli::commands::install::run(tool, global, local),
+        Commands::Migrate { status, dry_run } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::migrate::run(&project_dir, status, dry_run)
+        }
         Commands::Destroy { confirm } => {
             let project_dir = get_project_dir(cli.dir)?;
             cli::commands::destroy::run(&project_dir, confirm)
@@ -74,6 +92,25 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
                 }
             }
         }
+        Commands::Heartbeat => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::status::heartbeat(&project_dir, &agent_id)
+        }
+        Commands::Timeline {
+            agent,
+            since,
+            limit,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::status::timeline(
+                &project_dir,
+                agent.as_deref(),
+                since.as_deref(),
+                limit,
+                format,
+            )
+        }
         Commands::Log {
             since,
             tags,
@@ -82,9 +119,16 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
             ref_where,
             ref_what,
             ref_ref,
+            to,
+            to_me,
             limit,
         } => {
             let project_dir = get_project_dir(cli.dir)?;
+            let to = if to_me {
+                Some(get_agent_id(cli.as_))
+            } else {
+                to
+            };
             cli::commands::message::log(
                 &project_dir,
                 since.as_deref(),
@@ -94,6 +138,7 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
                 ref_where.as_deref(),
                 ref_what.as_deref(),
                 ref_ref.as_deref(),
+                to.as_deref(),
                 limit,
                 format,
             )
@@ -104,6 +149,7 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
             priority,
             reply_to,
             refs,
+            to,
         } => {
             let project_dir = get_project_dir(cli.dir)?;
             let agent_id = get_agent_id(cli.as_);
@@ -115,29 +161,154 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
                 priority,
                 reply_to,
                 refs,
+                to,
+            )
+        }
+        Commands::Wait {
+            reply_to,
+            tags,
+            mention,
+            from,
+            priority,
+            after,
+            timeout,
+            limit,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::message::wait(
+                &project_dir,
+                reply_to,
+                tags,
+                mention.as_deref(),
+                from.as_deref(),
+                priority,
+                after,
+                &timeout,
+                limit,
+                format,
+            )
+        }
+        Commands::Inbox { limit, peek } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::message::inbox(&project_dir, &agent_id, limit, peek, format)
+        }
+        Commands::Mentions { since, limit } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::message::mentions(
+                &project_dir,
+                &agent_id,
+                since.as_deref(),
+                limit,
+                format,
             )
         }
-        Commands::Message { id } => {
+        Commands::Message { id, tree, depth } => {
             let project_dir = get_project_dir(cli.dir)?;
-            cli::commands::message::show_message(&project_dir, id, format)
+            cli::commands::message::show_message(&project_dir, id, tree, depth, format)
         }
         Commands::Artifacts {
             by,
+            under,
             ref_where,
             ref_what,
             ref_ref,
+            check,
+            since_commit,
             limit,
         } => {
             let project_dir = get_project_dir(cli.dir)?;
-            cli::commands::artifact::list(
-                &project_dir,
-                by.as_deref(),
-                ref_where.as_deref(),
-                ref_what.as_deref(),
-                ref_ref.as_deref(),
-                limit,
-                format,
-            )
+            if let Some(commit) = since_commit {
+                cli::commands::artifact::changed_since(&project_dir, &commit, by.as_deref(), format)
+            } else if check {
+                cli::commands::artifact::check(
+                    &project_dir,
+                    by.as_deref(),
+                    under.as_deref(),
+                    ref_where.as_deref(),
+                    ref_what.as_deref(),
+                    ref_ref.as_deref(),
+                    limit,
+                    format,
+                )
+            } else {
+                cli::commands::artifact::list(
+                    &project_dir,
+                    by.as_deref(),
+                    under.as_deref(),
+                    ref_where.as_deref(),
+                    ref_what.as_deref(),
+                    ref_ref.as_deref(),
+                    limit,
+                    format,
+                )
+            }
+        }
+        Commands::Artifact { command } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            match command {
+                cli::ArtifactCommands::Add {
+                    path,
+                    description,
+                    version,
+                    refs,
+                    from_commit,
+                    depends_on,
+                    derived_from,
+                    clear_links,
+                } => match (from_commit, path, description) {
+                    (Some(commit), _, _) => cli::commands::artifact::add_from_commit(
+                        &project_dir,
+                        &commit,
+                        &get_agent_id(cli.as_),
+                        version.as_deref(),
+                        refs,
+                        format,
+                    ),
+                    (None, Some(path), Some(description)) => cli::commands::artifact::add(
+                        &project_dir,
+                        &path,
+                        &get_agent_id(cli.as_),
+                        &description,
+                        version.as_deref(),
+                        refs,
+                        &depends_on,
+                        &derived_from,
+                        clear_links,
+                    ),
+                    _ => unreachable!("clap requires a path and description"),
+                },
+                cli::ArtifactCommands::Show { path } => {
+                    cli::commands::artifact::show(&project_dir, &path, format)
+                }
+                cli::ArtifactCommands::Remove { path } => {
+                    let agent_id = get_agent_id(cli.as_);
+                    cli::commands::artifact::remove(&project_dir, &agent_id, &path)
+                }
+                cli::ArtifactCommands::Rename { old, new } => {
+                    let agent_id = get_agent_id(cli.as_);
+                    cli::commands::artifact::rename(&project_dir, &agent_id, &old, &new)
+                }
+                cli::ArtifactCommands::History { path } => {
+                    cli::commands::artifact::history(&project_dir, &path, format)
+                }
+                cli::ArtifactCommands::Graph {
+                    path,
+                    direction,
+                    depth,
+                    dot,
+                    mermaid,
+                } => cli::commands::artifact::graph(
+                    &project_dir,
+                    &path,
+                    direction,
+                    depth,
+                    dot,
+                    mermaid,
+                    format,
+                ),
+            }
         }
         Commands::ArtifactAdd {
             path,
@@ -154,12 +325,136 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
                 &description,
                 version.as_deref(),
                 refs,
+                &[],
+                &[],
+                false,
             )
         }
         Commands::ArtifactShow { path } => {
             let project_dir = get_project_dir(cli.dir)?;
             cli::commands::artifact::show(&project_dir, &path, format)
         }
+        Commands::Search {
+            query,
+            raw,
+            since,
+            tags,
+            from,
+            priority,
+            ref_where,
+            ref_what,
+            ref_ref,
+            limit,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::search::run(
+                &project_dir,
+                &query,
+                raw,
+                since.as_deref(),
+                tags,
+                from.as_deref(),
+                priority,
+                ref_where.as_deref(),
+                ref_what.as_deref(),
+                ref_ref.as_deref(),
+                limit,
+                format,
+            )
+        }
+        Commands::Claim { path, shared, ttl } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::lease::claim(&project_dir, &agent_id, &path, shared, &ttl, format)
+        }
+        Commands::Release { path, all: _ } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::lease::release(&project_dir, &agent_id, path.as_deref())
+        }
+        Commands::Claims { path } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::lease::list(&project_dir, path.as_deref(), format)
+        }
+        Commands::Handoff {
+            to,
+            summary,
+            refs,
+            artifacts,
+            priority,
+            escalate_after,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::handoff::handoff(
+                &project_dir,
+                &agent_id,
+                &to,
+                &summary,
+                refs,
+                artifacts,
+                priority,
+                &escalate_after,
+                format,
+            )
+        }
+        Commands::Accept { id, note } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::handoff::accept(&project_dir, &agent_id, id, note.as_deref(), format)
+        }
+        Commands::Decline { id, reason } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::handoff::decline(&project_dir, &agent_id, id, reason.as_deref(), format)
+        }
+        Commands::Handoffs {
+            to,
+            from,
+            status,
+            limit,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::handoff::list(
+                &project_dir,
+                to.as_deref(),
+                from.as_deref(),
+                status,
+                limit,
+                format,
+            )
+        }
+        Commands::Answer { id, accept, .. } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let agent_id = get_agent_id(cli.as_);
+            cli::commands::question::answer(&project_dir, &agent_id, id, accept, format)
+        }
+        Commands::Questions {
+            open,
+            state,
+            to,
+            from,
+            limit,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let state = if open {
+                Some(crate::core::models::question::QuestionState::Open)
+            } else {
+                state
+            };
+            cli::commands::question::list(
+                &project_dir,
+                state,
+                to.as_deref(),
+                from.as_deref(),
+                limit,
+                format,
+            )
+        }
+        Commands::Tags => {
+            let project_dir = get_project_dir(cli.dir)?;
+            cli::commands::tag::list(&project_dir, format)
+        }
         Commands::Refs { reference } => {
             let project_dir = get_project_dir(cli.dir)?;
             cli::commands::ref_::find(&project_dir, &reference, format)
@@ -179,6 +474,18 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
                 confirm,
             )
         }
+        Commands::Config { command } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            match command {
+                cli::ConfigCommands::Get { key } => {
+                    cli::commands::config::get(&project_dir, &key, format)
+                }
+                cli::ConfigCommands::Set { key, value } => {
+                    cli::commands::config::set(&project_dir, &key, &value)
+                }
+                cli::ConfigCommands::List => cli::commands::config::list(&project_dir, format),
+            }
+        }
         Commands::Export => {
             let project_dir = get_project_dir(cli.dir)?;
             cli::commands::export::export(&project_dir)
@@ -191,5 +498,17 @@ async fn run(cli: Cli) -> core::errors::BBResult<()> {
             let project_dir = get_project_dir(cli.dir)?;
             mcp::run_mcp_server(agent, std::env::var("BB_AGENT_ID").ok(), &project_dir).await
         }
+        Commands::Serve {
+            host,
+            port,
+            #[cfg(unix)]
+            socket,
+        } => {
+            let project_dir = get_project_dir(cli.dir)?;
+            let listen = mcp::http::Listen::Tcp((host, port).into());
+            #[cfg(unix)]
+            let listen = socket.map_or(listen, mcp::http::Listen::Unix);
+            mcp::run_http_server(&project_dir, listen).await
+        }
     }
 }
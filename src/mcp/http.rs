@@ -0,0 +1,235 @@
+use axum::extract::Request;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
+use std::future::IntoFuture;
+use std::net::{IpAddr, SocketAddr};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::core::errors::{BBError, BBResult};
+use crate::db::connection::ConnectionPool;
+use crate::mcp::identity::IdentityResolver;
+use crate::mcp::server::BlackboardMcpServer;
+
+/// Path the MCP endpoint is served under.
+pub const MCP_PATH: &str = "/mcp";
+
+/// Request header that fixes a session's identity at `initialize`.
+pub const AGENT_HEADER: &str = "x-bb-agent";
+
+/// Where `bb serve` listens.
+#[derive(Debug, Clone)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// Serves MCP over Streamable HTTP until interrupted. Every client session
+/// gets its own server and identity, but they share one process and one
+/// pool of database connections.
+pub async fn run_http_server(project_dir: &Path, listen: Listen) -> BBResult<()> {
+    // Open and migrate the database up front, so a broken board fails here
+    // rather than on every call
+    let db = ConnectionPool::new(project_dir);
+    db.with(|_| Ok(()))?;
+
+    let service = StreamableHttpService::new(
+        move || {
+            Ok(BlackboardMcpServer::new(
+                IdentityResolver::new(None, None),
+                db.clone(),
+            ))
+        },
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig::default(),
+    );
+    let router = axum::Router::new()
+        .nest_service(MCP_PATH, service)
+        .layer(axum::middleware::from_fn(require_local));
+
+    match listen {
+        Listen::Tcp(addr) => {
+            if !addr.ip().is_loopback() {
+                return Err(BBError::invalid_field(
+                    "addr",
+                    format!("refusing to listen on non-loopback address {addr}"),
+                ));
+            }
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            // Announce the bound address, which matters when the port was 0
+            eprintln!("Listening on http://{}{MCP_PATH}", listener.local_addr()?);
+            tokio::select! {
+                served = axum::serve(listener, router).into_future() => served?,
+                _ = shutdown_signal() => {}
+            }
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            remove_stale_socket(&path)?;
+            let listener = tokio::net::UnixListener::bind(&path)?;
+            // Anyone who can connect can act as any agent, so keep it to us
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            eprintln!("Listening on unix:{}", path.display());
+            let served = tokio::select! {
+                served = axum::serve(listener, router).into_future() => served,
+                _ = shutdown_signal() => Ok(()),
+            };
+            let _ = std::fs::remove_file(&path);
+            served?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects requests that a web page could have made through DNS rebinding:
+/// a `Host` other than this machine, or an `Origin` that is not a local page.
+/// Clients that are not browsers usually send no `Origin`, which is allowed.
+async fn require_local(request: Request, next: Next) -> Response {
+    if is_local_request(request.headers()) {
+        next.run(request).await
+    } else {
+        (StatusCode::FORBIDDEN, "only local clients may connect").into_response()
+    }
+}
+
+fn is_local_request(headers: &HeaderMap) -> bool {
+    let host_ok = headers
+        .get(header::HOST)
+        .is_none_or(|host| host.to_str().is_ok_and(is_loopback_host));
+    let origin_ok = headers.get(header::ORIGIN).is_none_or(|origin| {
+        origin.to_str().is_ok_and(|origin| {
+            origin
+                .strip_prefix("http://")
+                .or_else(|| origin.strip_prefix("https://"))
+                .is_some_and(is_loopback_host)
+        })
+    });
+    host_ok && origin_ok
+}
+
+/// Whether `host`, with an optional port, names this machine: `localhost`
+/// or a loopback address.
+fn is_loopback_host(host: &str) -> bool {
+    let name = if let Some(rest) = host.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((name, port)) if port.is_empty() || port.starts_with(':') => name,
+            _ => return false,
+        }
+    } else {
+        match host.rsplit_once(':') {
+            Some((name, _port)) => name,
+            None => host,
+        }
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Removes a socket left behind by a server that is no longer running.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> BBResult<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(BBError::invalid_field(
+            "socket",
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(BBError::invalid_field(
+            "socket",
+            format!("another server is listening on {}", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM. Open SSE streams never end on
+/// their own, so the server stops without waiting for them.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(host: Option<&'static str>, origin: Option<&'static str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(host) = host {
+            headers.insert(header::HOST, HeaderValue::from_static(host));
+        }
+        if let Some(origin) = origin {
+            headers.insert(header::ORIGIN, HeaderValue::from_static(origin));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_only_local_hosts_and_origins_are_allowed() {
+        for host in [
+            "localhost",
+            "localhost:8080",
+            "127.0.0.1:9",
+            "[::1]:80",
+            "[::1]",
+        ] {
+            assert!(is_local_request(&headers(Some(host), None)), "{host}");
+        }
+        assert!(is_local_request(&headers(None, None)));
+        assert!(is_local_request(&headers(
+            Some("localhost"),
+            Some("http://127.0.0.1:3000")
+        )));
+
+        for host in [
+            "evil.example",
+            "evil.example:8080",
+            "localhost.evil.example",
+            "[::1]x",
+        ] {
+            assert!(!is_local_request(&headers(Some(host), None)), "{host}");
+        }
+        for origin in [
+            "https://evil.example",
+            "null",
+            "file://",
+            "http://localhost.evil.example",
+        ] {
+            assert!(
+                !is_local_request(&headers(Some("localhost"), Some(origin))),
+                "{origin}"
+            );
+        }
+    }
+}
@@ -30,7 +30,7 @@ impl IdentityResolver {
         // Check if identity is fixed by --agent
         if self.fixed_agent.is_some() {
             return Err(BBError::InvalidInput(
-                "identity already fixed by --agent".into(),
+                "identity already fixed by --agent or the session header".into(),
             ));
         }
 
@@ -71,6 +71,21 @@ impl IdentityResolver {
         })
     }
 
+    /// Fixes the identity for the rest of the session, as `--agent` does.
+    /// Used when a transport supplies the identity, such as an HTTP header.
+    pub fn bind(&mut self, agent_id: &str) -> BBResult<()> {
+        validate_agent_id(agent_id)?;
+        if let Some(existing) = self.resolve()
+            && existing != agent_id
+        {
+            return Err(BBError::InvalidInput(
+                "identity already set to a different value".into(),
+            ));
+        }
+        self.fixed_agent = Some(agent_id.to_string());
+        Ok(())
+    }
+
     pub fn require_identity(&self) -> BBResult<&str> {
         self.resolve().ok_or(BBError::IdentityRequired)
     }
@@ -150,6 +165,17 @@ mod tests {
         assert!(resolver.identify("agent-2").is_err());
     }
 
+    #[test]
+    fn test_bind_fixes_identity() {
+        let mut resolver = IdentityResolver::new(None, None);
+        resolver.bind("agent-1").unwrap();
+        assert_eq!(resolver.resolve(), Some("agent-1"));
+        assert!(resolver.identify("agent-2").is_err());
+
+        let mut resolver = IdentityResolver::new(None, Some("from-env".to_string()));
+        assert!(resolver.bind("agent-1").is_err());
+    }
+
     #[test]
     fn test_require_identity() {
         let resolver = IdentityResolver::new(None, None);
@@ -1,5 +1,10 @@
+pub mod http;
 pub mod identity;
+pub mod prompts;
+pub mod resources;
 pub mod server;
 pub mod tools;
+pub mod transport;
 
+pub use http::run_http_server;
 pub use server::run_mcp_server;
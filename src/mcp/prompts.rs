@@ -0,0 +1,502 @@
+use rmcp::model::{GetPromptResult, Prompt, PromptArgument, PromptMessage, PromptMessageRole};
+use serde_json::{Map, Value as JsonValue};
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::core::config;
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::agent::{Agent, AgentStatus};
+use crate::core::models::artifact::Artifact;
+use crate::core::models::lease::Lease;
+use crate::core::models::message::Message;
+use crate::core::operations::agent as agent_ops;
+use crate::core::operations::artifact as artifact_ops;
+use crate::core::operations::inbox as inbox_ops;
+use crate::core::operations::lease as lease_ops;
+use crate::db::connection::ConnectionPool;
+use crate::mcp::identity::IdentityResolver;
+use crate::mcp::tools::{SummaryOutput, summary};
+
+/// Unread messages and artifacts listed in a prompt, at most.
+const PROMPT_LIST_LIMIT: usize = 20;
+
+/// A built-in coordination ritual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ritual {
+    SessionBriefing,
+    Handoff,
+    ReportBlocker,
+    SessionWrapUp,
+}
+
+impl Ritual {
+    pub const ALL: [Ritual; 4] = [
+        Self::SessionBriefing,
+        Self::Handoff,
+        Self::ReportBlocker,
+        Self::SessionWrapUp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SessionBriefing => "session_briefing",
+            Self::Handoff => "handoff",
+            Self::ReportBlocker => "report_blocker",
+            Self::SessionWrapUp => "session_wrap_up",
+        }
+    }
+
+    pub fn parse(name: &str) -> BBResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|r| r.name() == name)
+            .ok_or_else(|| BBError::invalid_field("name", format!("unknown prompt: {name}")))
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::SessionBriefing => {
+                "Start of session briefing: who is doing what, what is blocked, and your unread messages"
+            }
+            Self::Handoff => "Hand your current work over to another agent",
+            Self::ReportBlocker => "Report that you are blocked and ask for help",
+            Self::SessionWrapUp => {
+                "End of session wrap-up: report what you did and release what you hold"
+            }
+        }
+    }
+
+    /// Arguments as `(name, description, required)`.
+    fn arguments(&self) -> &'static [(&'static str, &'static str, bool)] {
+        match self {
+            Self::SessionBriefing | Self::SessionWrapUp => &[],
+            Self::Handoff => &[
+                ("to", "Agent taking over the work", true),
+                ("notes", "Anything the next agent should know", false),
+            ],
+            Self::ReportBlocker => &[
+                ("blocker", "What is blocking you", true),
+                ("waiting_on", "Agent who can unblock you", false),
+            ],
+        }
+    }
+
+    pub fn prompt(&self) -> Prompt {
+        let arguments = self
+            .arguments()
+            .iter()
+            .map(|(name, description, required)| PromptArgument {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                required: Some(*required),
+            })
+            .collect::<Vec<_>>();
+        Prompt::new(
+            self.name(),
+            Some(self.description()),
+            (!arguments.is_empty()).then_some(arguments),
+        )
+    }
+}
+
+/// Everything a prompt is filled from.
+#[derive(Debug)]
+pub struct PromptContext {
+    pub agent_id: String,
+    pub agent: Option<Agent>,
+    pub summary: SummaryOutput,
+    pub unread: Vec<Message>,
+    pub unread_remaining: i64,
+    pub artifacts: Vec<Artifact>,
+    pub claims: Vec<Lease>,
+}
+
+/// Gathers live board data for the calling agent. Peeks at the inbox, so
+/// the read cursor does not move.
+pub async fn load_context(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+) -> BBResult<PromptContext> {
+    let agent_id = identity.lock().await.require_identity()?.to_string();
+    let summary = summary(identity, db).await?;
+
+    let db = db.clone();
+    tokio::task::spawn_blocking(move || {
+        db.with(|conn| {
+            let agent = agent_ops::get_agent(conn, &agent_id)?;
+            let inbox = inbox_ops::read_inbox(conn, &agent_id, PROMPT_LIST_LIMIT, true)?;
+            let artifacts = artifact_ops::list_artifacts(
+                conn,
+                Some(&agent_id),
+                None,
+                None,
+                None,
+                None,
+                PROMPT_LIST_LIMIT,
+            )?;
+            let liveness = &config::current().liveness;
+            let claims = lease_ops::list_claims(conn, None, liveness)?
+                .into_iter()
+                .filter(|l| l.holder == agent_id)
+                .collect();
+            Ok(PromptContext {
+                agent_id,
+                agent,
+                summary,
+                unread: inbox.messages,
+                unread_remaining: inbox.remaining,
+                artifacts,
+                claims,
+            })
+        })
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))?
+}
+
+/// Renders `ritual` for `context`, checking required arguments.
+pub fn render(
+    ritual: Ritual,
+    context: &PromptContext,
+    arguments: &Map<String, JsonValue>,
+) -> BBResult<GetPromptResult> {
+    let arg = |name: &str| {
+        arguments
+            .get(name)
+            .and_then(JsonValue::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+    for (name, _, required) in ritual.arguments() {
+        if *required && arg(name).is_none() {
+            return Err(BBError::invalid_field(
+                name,
+                format!("{name} is required for {}", ritual.name()),
+            ));
+        }
+    }
+
+    let mut text = String::new();
+    match ritual {
+        Ritual::SessionBriefing => {
+            let _ = writeln!(
+                text,
+                "You are {} starting a session on a shared blackboard.\n",
+                context.agent_id
+            );
+            write_own_status(&mut text, context);
+            write_team(&mut text, context);
+            write_unread(&mut text, context);
+            write_high_priority(&mut text, context);
+            write_handoffs(&mut text, context);
+            write_stale_questions(&mut text, context);
+            write_claims(&mut text, context);
+            text.push_str(
+                "\nBefore you start:\n\
+                 1. Call `inbox` to read and acknowledge the messages above.\n\
+                 2. `accept_handoff` or `decline_handoff` any handoff waiting for you.\n\
+                 3. Pick work nobody else is doing and call `set_status` with your task.\n\
+                 4. `claim_path` the files you will edit.\n\
+                 5. `post_message` a short note saying what you are starting, tagged `todo`.\n",
+            );
+        }
+        Ritual::Handoff => {
+            let to = arg("to").unwrap_or_default();
+            let _ = writeln!(
+                text,
+                "You are {} handing your work over to {to}.\n",
+                context.agent_id
+            );
+            write_own_status(&mut text, context);
+            write_artifacts(&mut text, context);
+            write_claims(&mut text, context);
+            if let Some(notes) = arg("notes") {
+                let _ = writeln!(text, "\nNotes from you: {notes}");
+            }
+            let _ = write!(
+                text,
+                "\nTo hand over:\n\
+                 1. `handoff` to {to} with a `summary` covering what is done, what is left, \
+                 where the work lives and any traps, and your registered files in `artifacts`.\n\
+                 2. `release_path` every claim listed above so {to} can take them.\n\
+                 3. `set_status` to `idle` with progress reset.\n\
+                 4. `wait_for_messages` with `reply_to` set to the handoff's `message_id` to hear \
+                 whether {to} accepts.\n"
+            );
+        }
+        Ritual::ReportBlocker => {
+            let blocker = arg("blocker").unwrap_or_default();
+            let _ = writeln!(
+                text,
+                "You are {} and you are blocked: {blocker}\n",
+                context.agent_id
+            );
+            write_own_status(&mut text, context);
+            write_team(&mut text, context);
+            text.push_str("\nTo report it:\n");
+            let _ = writeln!(
+                text,
+                "1. `set_status` with `status: \"blocked\"` and `blockers: \"{blocker}\"`."
+            );
+            match arg("waiting_on") {
+                Some(agent) => {
+                    let _ = writeln!(
+                        text,
+                        "2. `post_message` to {agent} (`to: [\"{agent}\"]`), priority `high`, \
+                         tagged `blocked`, saying exactly what you need from them."
+                    );
+                }
+                None => text.push_str(
+                    "2. `post_message` with priority `high`, tagged `blocked`, saying exactly \
+                     what you need and who might help.\n",
+                ),
+            }
+            text.push_str(
+                "3. `wait_for_messages` for a reply, or pick up other work in the meantime.\n",
+            );
+        }
+        Ritual::SessionWrapUp => {
+            let _ = writeln!(text, "You are {} ending your session.\n", context.agent_id);
+            write_own_status(&mut text, context);
+            write_artifacts(&mut text, context);
+            write_claims(&mut text, context);
+            write_unread(&mut text, context);
+            text.push_str(
+                "\nBefore you stop:\n\
+                 1. `register_artifact` any significant file you produced that is not listed above.\n\
+                 2. `post_message` a summary of what you finished and what is left, tagged `done`.\n\
+                 3. `release_path` all of your claims.\n\
+                 4. `set_status` to `idle`.\n",
+            );
+        }
+    }
+
+    Ok(GetPromptResult {
+        description: Some(ritual.description().to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
+
+fn write_own_status(text: &mut String, context: &PromptContext) {
+    let Some(agent) = &context.agent else {
+        text.push_str("Your status: not set yet.\n");
+        return;
+    };
+    let task = if agent.current_task.is_empty() {
+        "no task"
+    } else {
+        &agent.current_task
+    };
+    let _ = writeln!(
+        text,
+        "Your status: {} ({task}, {}%)",
+        agent.status.as_str(),
+        agent.progress
+    );
+    if let Some(blockers) = &agent.blockers {
+        let _ = writeln!(text, "Your blockers: {blockers}");
+    }
+}
+
+fn write_team(text: &mut String, context: &PromptContext) {
+    let others: Vec<_> = context
+        .summary
+        .agents
+        .iter()
+        .filter(|a| a.agent.id != context.agent_id && a.liveness != "offline")
+        .collect();
+    if others.is_empty() {
+        text.push_str("\nNo other agents are active.\n");
+        return;
+    }
+    text.push_str("\nOther agents:\n");
+    for a in others {
+        let _ = write!(
+            text,
+            "- {} [{}, {}]: {}",
+            a.agent.id,
+            a.agent.status.as_str(),
+            a.liveness,
+            a.agent.current_task
+        );
+        if a.agent.status == AgentStatus::Blocked
+            && let Some(blockers) = &a.agent.blockers
+        {
+            let _ = write!(text, " (blocked on: {blockers})");
+        }
+        text.push('\n');
+    }
+}
+
+fn write_unread(text: &mut String, context: &PromptContext) {
+    if context.unread.is_empty() {
+        text.push_str("\nYou have no unread messages.\n");
+        return;
+    }
+    text.push_str("\nYour unread messages:\n");
+    for message in &context.unread {
+        write_message(text, message);
+    }
+    if context.unread_remaining > 0 {
+        let _ = writeln!(text, "... and {} more", context.unread_remaining);
+    }
+}
+
+fn write_high_priority(text: &mut String, context: &PromptContext) {
+    if context.summary.high_priority_messages.is_empty() {
+        return;
+    }
+    text.push_str("\nRecent high-priority messages:\n");
+    for message in &context.summary.high_priority_messages {
+        write_message(text, message);
+    }
+}
+
+fn write_stale_questions(text: &mut String, context: &PromptContext) {
+    if context.summary.stale_questions > 0 {
+        let _ = writeln!(
+            text,
+            "\n{} question(s) have been open for over {} min; `list_questions` shows them.",
+            context.summary.stale_questions,
+            config::current().questions.stale_minutes
+        );
+    }
+}
+
+fn write_handoffs(text: &mut String, context: &PromptContext) {
+    let waiting: Vec<_> = context
+        .summary
+        .pending_handoffs
+        .iter()
+        .filter(|h| h.to_agent == context.agent_id)
+        .collect();
+    if waiting.is_empty() {
+        return;
+    }
+    text.push_str("\nHandoffs waiting for you:\n");
+    for handoff in waiting {
+        let _ = writeln!(
+            text,
+            "- #{} from {} [{}]: {}",
+            handoff.id,
+            handoff.from_agent,
+            handoff.priority.as_str(),
+            handoff.summary
+        );
+    }
+}
+
+fn write_artifacts(text: &mut String, context: &PromptContext) {
+    if context.artifacts.is_empty() {
+        text.push_str("\nYou have not registered any artifacts.\n");
+        return;
+    }
+    text.push_str("\nYour artifacts:\n");
+    for artifact in &context.artifacts {
+        let _ = writeln!(text, "- {}: {}", artifact.path, artifact.description);
+    }
+}
+
+fn write_claims(text: &mut String, context: &PromptContext) {
+    if context.claims.is_empty() {
+        text.push_str("\nYou hold no claims.\n");
+        return;
+    }
+    text.push_str("\nYour claims:\n");
+    for lease in &context.claims {
+        let _ = writeln!(
+            text,
+            "- {} ({}, until {})",
+            lease.path,
+            lease.mode.as_str(),
+            lease.expires_at.format("%H:%M UTC")
+        );
+    }
+}
+
+fn write_message(text: &mut String, message: &Message) {
+    let _ = write!(
+        text,
+        "- #{} from {} [{}]",
+        message.id,
+        message.from_agent,
+        message.priority.as_str()
+    );
+    if !message.tags.is_empty() {
+        let _ = write!(text, " #{}", message.tags.join(" #"));
+    }
+    let _ = writeln!(text, ": {}", message.content);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::message::Priority;
+    use rmcp::model::PromptMessageContent;
+    use serde_json::json;
+
+    fn context() -> PromptContext {
+        PromptContext {
+            agent_id: "agent-1".to_string(),
+            agent: None,
+            summary: SummaryOutput {
+                agents: vec![],
+                blocked_agents: vec![],
+                recent_messages: vec![],
+                high_priority_messages: vec![],
+                recent_artifacts: vec![],
+                pending_handoffs: vec![],
+                stale_questions: 0,
+                unread_count: None,
+            },
+            unread: vec![Message {
+                id: 4,
+                from_agent: "agent-2".to_string(),
+                recipients: vec!["agent-1".to_string()],
+                content: "schema is ready".to_string(),
+                tags: vec!["info".to_string()],
+                priority: Priority::High,
+                in_reply_to: None,
+                refs: vec![],
+                created_at: chrono::Utc::now(),
+            }],
+            unread_remaining: 0,
+            artifacts: vec![],
+            claims: vec![],
+        }
+    }
+
+    fn text(result: &GetPromptResult) -> &str {
+        match &result.messages[0].content {
+            PromptMessageContent::Text { text } => text,
+            _ => panic!("not text"),
+        }
+    }
+
+    #[test]
+    fn test_ritual_names_round_trip() {
+        for ritual in Ritual::ALL {
+            assert_eq!(Ritual::parse(ritual.name()).unwrap(), ritual);
+        }
+        assert!(Ritual::parse("nope").is_err());
+    }
+
+    #[test]
+    fn test_briefing_lists_unread_messages() {
+        let result = render(Ritual::SessionBriefing, &context(), &Map::new()).unwrap();
+        let text = text(&result);
+        assert!(text.contains("#4 from agent-2 [high] #info: schema is ready"));
+        assert!(text.contains("You hold no claims."));
+    }
+
+    #[test]
+    fn test_required_argument_is_checked() {
+        let result = render(Ritual::Handoff, &context(), &Map::new());
+        assert!(matches!(result, Err(BBError::InvalidField { field, .. }) if field == "to"));
+
+        let arguments = json!({"to": "agent-2"}).as_object().unwrap().clone();
+        let result = render(Ritual::Handoff, &context(), &arguments).unwrap();
+        assert!(text(&result).contains("handing your work over to agent-2"));
+    }
+}
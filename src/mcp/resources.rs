@@ -0,0 +1,286 @@
+use rmcp::model::{
+    AnnotateAble, RawResource, RawResourceTemplate, Resource, ResourceTemplate,
+    ResourceUpdatedNotificationParam,
+};
+use rmcp::{Peer, RoleServer};
+use serde_json::Value as JsonValue;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::core::config;
+use crate::core::errors::{BBError, BBResult};
+use crate::core::operations::artifact as artifact_ops;
+use crate::core::operations::board::{self, BoardChanges};
+use crate::core::operations::message as message_ops;
+use crate::db::connection::ConnectionPool;
+use crate::mcp::identity::IdentityResolver;
+use crate::mcp::tools::{GetStatusInput, get_status, summary};
+
+/// How often the board is checked for changes while a client is subscribed.
+pub const WATCH_INTERVAL_MS: u64 = 1000;
+
+const MIME_TYPE: &str = "application/json";
+
+/// A piece of the board addressable as a `bb://` resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardResource {
+    Summary,
+    Agents,
+    Message(i64),
+    /// A message and its replies.
+    Thread(i64),
+    Artifact(String),
+}
+
+impl BoardResource {
+    pub fn parse(uri: &str) -> BBResult<Self> {
+        let invalid = || BBError::invalid_field("uri", format!("unknown resource: {uri}"));
+        let rest = uri.strip_prefix("bb://").ok_or_else(invalid)?;
+        let id = |s: &str| s.parse::<i64>().map_err(|_| invalid());
+
+        match rest.split_once('/') {
+            None if rest == "summary" => Ok(Self::Summary),
+            None if rest == "agents" => Ok(Self::Agents),
+            Some(("messages", id_str)) => Ok(Self::Message(id(id_str)?)),
+            Some(("threads", id_str)) => Ok(Self::Thread(id(id_str)?)),
+            Some(("artifacts", path)) if !path.is_empty() => Ok(Self::Artifact(path.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn uri(&self) -> String {
+        match self {
+            Self::Summary => "bb://summary".to_string(),
+            Self::Agents => "bb://agents".to_string(),
+            Self::Message(id) => format!("bb://messages/{id}"),
+            Self::Thread(id) => format!("bb://threads/{id}"),
+            Self::Artifact(path) => format!("bb://artifacts/{path}"),
+        }
+    }
+}
+
+pub fn resources() -> Vec<Resource> {
+    let resource = |board: BoardResource, name: &str, description: &str| {
+        RawResource {
+            description: Some(description.to_string()),
+            mime_type: Some(MIME_TYPE.to_string()),
+            ..RawResource::new(board.uri(), name)
+        }
+        .no_annotation()
+    };
+    vec![
+        resource(
+            BoardResource::Summary,
+            "summary",
+            "Overview of agents, recent messages and artifacts",
+        ),
+        resource(
+            BoardResource::Agents,
+            "agents",
+            "Status and liveness of every agent",
+        ),
+    ]
+}
+
+pub fn resource_templates() -> Vec<ResourceTemplate> {
+    let template = |uri_template: &str, name: &str, description: &str| {
+        RawResourceTemplate {
+            uri_template: uri_template.to_string(),
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            mime_type: Some(MIME_TYPE.to_string()),
+        }
+        .no_annotation()
+    };
+    vec![
+        template("bb://messages/{id}", "message", "A single message"),
+        template(
+            "bb://threads/{id}",
+            "thread",
+            "The whole thread a message belongs to, root first, replies nested by depth",
+        ),
+        template(
+            "bb://artifacts/{path}",
+            "artifact",
+            "A registered artifact, by path relative to the project root",
+        ),
+    ]
+}
+
+/// Reads a resource as JSON.
+pub async fn read(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    resource: BoardResource,
+) -> BBResult<JsonValue> {
+    match resource {
+        BoardResource::Summary => Ok(serde_json::to_value(summary(identity, db).await?)?),
+        BoardResource::Agents => Ok(serde_json::to_value(
+            get_status(identity, db, GetStatusInput::default()).await?,
+        )?),
+        BoardResource::Message(id) => {
+            with_connection_blocking(db, move |conn| {
+                Ok(serde_json::to_value(message_ops::get_message(conn, id)?)?)
+            })
+            .await
+        }
+        BoardResource::Thread(id) => {
+            with_connection_blocking(db, move |conn| {
+                Ok(serde_json::to_value(message_ops::get_message_thread(
+                    conn, id, None, 0, None,
+                )?)?)
+            })
+            .await
+        }
+        BoardResource::Artifact(path) => {
+            with_connection_blocking(db, move |conn| {
+                let artifact = artifact_ops::get_artifact(conn, &path)?
+                    .ok_or_else(|| BBError::NotFound(format!("artifact {path} not found")))?;
+                Ok(serde_json::to_value(artifact)?)
+            })
+            .await
+        }
+    }
+}
+
+async fn with_connection_blocking<T, F>(db: &ConnectionPool, f: F) -> BBResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut rusqlite::Connection) -> BBResult<T> + Send + 'static,
+{
+    let db = db.clone();
+    tokio::task::spawn_blocking(move || db.with(f))
+        .await
+        .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))?
+}
+
+/// Resources whose contents are affected by `changes`, summary first.
+pub fn updated_resources(changes: &BoardChanges) -> Vec<BoardResource> {
+    if changes.is_empty() {
+        return Vec::new();
+    }
+
+    let mut updated = vec![BoardResource::Summary];
+    if changes.agents {
+        updated.push(BoardResource::Agents);
+    }
+    for &(id, _) in &changes.messages {
+        updated.push(BoardResource::Message(id));
+        updated.push(BoardResource::Thread(id));
+    }
+    for &id in &changes.threads {
+        updated.push(BoardResource::Thread(id));
+    }
+    for path in &changes.artifacts {
+        updated.push(BoardResource::Artifact(path.clone()));
+    }
+    updated
+}
+
+/// Polls the board and sends `notifications/resources/updated` for each
+/// subscribed resource that changes. Returns once the client is gone.
+pub async fn watch(
+    db: ConnectionPool,
+    subscriptions: Arc<std::sync::Mutex<HashSet<String>>>,
+    peer: Peer<RoleServer>,
+) {
+    let mut cursor = match with_connection_blocking(&db, |conn| {
+        board::board_cursor(conn, &config::current().liveness)
+    })
+    .await
+    {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            tracing::error!("Cannot watch board: {}", e);
+            return;
+        }
+    };
+
+    let interval = std::time::Duration::from_millis(WATCH_INTERVAL_MS);
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let polled = with_connection_blocking(&db, {
+            let mut cursor = cursor.clone();
+            move |conn| {
+                let liveness = &config::current().liveness;
+                Ok((board::changes_since(conn, &mut cursor, liveness)?, cursor))
+            }
+        })
+        .await;
+        let changes = match polled {
+            Ok((changes, next)) => {
+                cursor = next;
+                changes
+            }
+            Err(e) => {
+                tracing::warn!("Error checking board for changes: {}", e);
+                continue;
+            }
+        };
+
+        let uris: Vec<String> = {
+            let subscriptions = subscriptions.lock().unwrap();
+            let mut seen = HashSet::new();
+            updated_resources(&changes)
+                .iter()
+                .map(BoardResource::uri)
+                .filter(|uri| subscriptions.contains(uri) && seen.insert(uri.clone()))
+                .collect()
+        };
+        for uri in uris {
+            let notified = peer
+                .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                .await;
+            if notified.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trips() {
+        for resource in [
+            BoardResource::Summary,
+            BoardResource::Agents,
+            BoardResource::Message(7),
+            BoardResource::Thread(3),
+            BoardResource::Artifact("src/lib.rs".to_string()),
+        ] {
+            assert_eq!(BoardResource::parse(&resource.uri()).unwrap(), resource);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_uris() {
+        for uri in [
+            "bb://nope",
+            "bb://messages/abc",
+            "bb://artifacts/",
+            "file:///etc/passwd",
+        ] {
+            assert!(BoardResource::parse(uri).is_err(), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_reply_updates_parent_thread() {
+        let changes = BoardChanges {
+            messages: vec![(5, Some(2))],
+            threads: vec![1, 2, 5],
+            ..Default::default()
+        };
+        let updated = updated_resources(&changes);
+        assert!(updated.contains(&BoardResource::Summary));
+        assert!(updated.contains(&BoardResource::Thread(1)));
+        assert!(updated.contains(&BoardResource::Thread(2)));
+        assert!(updated.contains(&BoardResource::Message(5)));
+        assert!(!updated.contains(&BoardResource::Agents));
+    }
+}
@@ -1,28 +1,60 @@
+use rmcp::handler::server::tool::cached_schema_for_type;
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, ErrorCode, GetPromptRequestParam,
+    GetPromptResult, Implementation, InitializeRequestParam, InitializeResult, ListPromptsResult,
+    ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, PaginatedRequestParam,
+    ProtocolVersion, ReadResourceRequestParam, ReadResourceResult, ResourceContents,
+    ServerCapabilities, ServerInfo, SubscribeRequestParam, Tool, UnsubscribeRequestParam,
+};
+use rmcp::service::RequestContext;
+use rmcp::{ErrorData, RoleServer, ServerHandler, ServiceExt};
+use schemars::JsonSchema;
 use serde_json::json;
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::Mutex;
 
-use crate::core::errors::BBError;
+use crate::core::errors::{BBError, BBResult};
+use crate::db::connection::ConnectionPool;
+use crate::mcp::http::AGENT_HEADER;
 use crate::mcp::identity::IdentityResolver;
+use crate::mcp::prompts::{self, Ritual};
+use crate::mcp::resources::{self, BoardResource};
 use crate::mcp::tools::*;
+use crate::mcp::transport::StdioTransport;
 
-// For now, we'll implement a simpler MCP server without the full rmcp integration
-// The rmcp crate API is complex and this basic implementation provides the functionality
-
+/// MCP front end for the blackboard. `rmcp` handles the protocol (handshake
+/// and version negotiation, ping, cancellation, notifications); this type
+/// only maps tool calls, resources and prompts onto `mcp::tools`,
+/// `mcp::resources` and `mcp::prompts`.
 pub struct BlackboardMcpServer {
     identity: Arc<Mutex<IdentityResolver>>,
-    project_dir: std::path::PathBuf,
+    db: ConnectionPool,
+    /// URIs the client has subscribed to.
+    subscriptions: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// Whether the change watcher has been started.
+    watching: AtomicBool,
 }
 
 impl BlackboardMcpServer {
-    pub fn new(identity: IdentityResolver, project_dir: &Path) -> Self {
+    pub fn new(identity: IdentityResolver, db: ConnectionPool) -> Self {
         Self {
             identity: Arc::new(Mutex::new(identity)),
-            project_dir: project_dir.to_path_buf(),
+            db,
+            subscriptions: Arc::default(),
+            watching: AtomicBool::new(false),
         }
     }
 
+    fn board_resource(&self, uri: &str) -> Result<BoardResource, ErrorData> {
+        if !crate::util::discovery::is_initialized(self.db.project_dir()) {
+            return Err(rpc_error(BBError::NotInitialized));
+        }
+        BoardResource::parse(uri).map_err(rpc_error)
+    }
+
     async fn handle_request(
         &self,
         method: &str,
@@ -31,125 +63,740 @@ impl BlackboardMcpServer {
         match method {
             "identify" => {
                 let input: IdentifyInput = params
-                    .map(|v| {
-                        serde_json::from_value(v)
-                            .map_err(|e| BBError::InvalidInput(format!("Parse error: {e}")))
-                    })
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
                     .transpose()?
                     .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
 
-                identify(self.identity.clone(), input)
+                identify(self.identity.clone(), &self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "set_status" => {
                 let input: SetStatusInput = params
-                    .map(|v| {
-                        serde_json::from_value(v)
-                            .map_err(|e| BBError::InvalidInput(format!("Parse error: {e}")))
-                    })
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
                     .transpose()?
                     .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
 
-                set_status(self.identity.clone(), &self.project_dir, input)
+                set_status(self.identity.clone(), &self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "get_status" => {
                 let input: GetStatusInput = params
-                    .map(|v| serde_json::from_value(v).unwrap_or_default())
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
                     .unwrap_or_default();
 
-                get_status(self.identity.clone(), &self.project_dir, input)
+                get_status(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "heartbeat" => heartbeat(self.identity.clone(), &self.db)
+                .await
+                .map(|r| serde_json::to_value(r).unwrap()),
+
+            "timeline" => {
+                let input: TimelineInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                timeline(&self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "post_message" => {
                 let input: PostMessageInput = params
-                    .map(|v| {
-                        serde_json::from_value(v)
-                            .map_err(|e| BBError::InvalidInput(format!("Parse error: {e}")))
-                    })
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
                     .transpose()?
                     .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
 
-                post_message(self.identity.clone(), &self.project_dir, input)
+                post_message(self.identity.clone(), &self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "read_messages" => {
                 let input: ReadMessagesInput = params
-                    .map(|v| serde_json::from_value(v).unwrap_or_default())
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                read_messages(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "get_thread" => {
+                let input: GetThreadInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                get_thread(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "search" => {
+                let input: SearchInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                search(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "list_tags" => list_tags(&self.db)
+                .await
+                .map(|r| serde_json::to_value(r).unwrap()),
+
+            "mentions" => {
+                let input: MentionsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                mentions(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "claim_path" => {
+                let input: ClaimPathInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                claim_path(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "release_path" => {
+                let input: ReleasePathInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                release_path(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "list_claims" => {
+                let input: ListClaimsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                list_claims(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "wait_for_messages" => {
+                let input: WaitForMessagesInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                wait_for_messages(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "inbox" => {
+                let input: InboxInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                inbox(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "handoff" => {
+                let input: HandoffInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                handoff(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "accept_handoff" => {
+                let input: AcceptHandoffInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                accept_handoff(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "decline_handoff" => {
+                let input: DeclineHandoffInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                decline_handoff(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "list_handoffs" => {
+                let input: ListHandoffsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
                     .unwrap_or_default();
 
-                read_messages(&self.project_dir, input)
+                list_handoffs(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "list_questions" => {
+                let input: ListQuestionsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                list_questions(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "accept_answer" => {
+                let input: AcceptAnswerInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                accept_answer(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "close_question" => {
+                let input: CloseQuestionInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                close_question(self.identity.clone(), &self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "register_artifact" => {
                 let input: RegisterArtifactInput = params
-                    .map(|v| {
-                        serde_json::from_value(v)
-                            .map_err(|e| BBError::InvalidInput(format!("Parse error: {e}")))
-                    })
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
                     .transpose()?
                     .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
 
-                register_artifact(self.identity.clone(), &self.project_dir, input)
+                register_artifact(self.identity.clone(), &self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "list_artifacts" => {
                 let input: ListArtifactsInput = params
-                    .map(|v| serde_json::from_value(v).unwrap_or_default())
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
                     .unwrap_or_default();
 
-                list_artifacts(&self.project_dir, input)
+                list_artifacts(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "check_artifacts" => {
+                let input: ListArtifactsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                check_artifacts(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "remove_artifact" => {
+                let input: RemoveArtifactInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                remove_artifact(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "rename_artifact" => {
+                let input: RenameArtifactInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                rename_artifact(self.identity.clone(), &self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "list_changed_artifacts" => {
+                let input: ChangedArtifactsInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                list_changed_artifacts(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "get_artifact_history" => {
+                let input: ArtifactHistoryInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                get_artifact_history(&self.db, input)
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap())
+            }
+
+            "get_artifact_graph" => {
+                let input: ArtifactGraphInput = params
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
+                    .transpose()?
+                    .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
+
+                get_artifact_graph(&self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
             "find_refs" => {
                 let input: FindRefsInput = params
-                    .map(|v| {
-                        serde_json::from_value(v)
-                            .map_err(|e| BBError::InvalidInput(format!("Parse error: {e}")))
-                    })
+                    .map(|v| serde_json::from_value(v).map_err(invalid_arguments))
                     .transpose()?
                     .ok_or_else(|| BBError::InvalidInput("Missing params".to_string()))?;
 
-                find_refs(&self.project_dir, input)
+                find_refs(&self.db, input)
                     .await
                     .map(|r| serde_json::to_value(r).unwrap())
             }
 
-            "summary" => summary(&self.project_dir)
+            "summary" => summary(self.identity.clone(), &self.db)
                 .await
                 .map(|r| serde_json::to_value(r).unwrap()),
 
-            _ => Err(BBError::InvalidInput(format!("Unknown method: {method}"))),
+            _ => Err(BBError::invalid_field(
+                "name",
+                format!("Unknown tool: {method}"),
+            )),
         }
     }
 }
 
+/// A tool whose input schema is generated from its `*Input` type.
+fn tool<T: JsonSchema + 'static>(name: &'static str, description: &'static str) -> Tool {
+    Tool::new(name, description, cached_schema_for_type::<T>())
+}
+
+fn tool_definitions() -> Vec<Tool> {
+    vec![
+        tool::<IdentifyInput>("identify", "Establish your agent identity for this session"),
+        tool::<SetStatusInput>(
+            "set_status",
+            "Update your status (task, progress, blockers)",
+        ),
+        tool::<GetStatusInput>("get_status", "Get the status and liveness of agents"),
+        tool::<HeartbeatInput>(
+            "heartbeat",
+            "Mark yourself as seen now, keeping your status, task and progress",
+        ),
+        tool::<TimelineInput>(
+            "timeline",
+            "Show how agents' status, task and progress changed, with time spent in each status",
+        ),
+        tool::<PostMessageInput>("post_message", "Post a message to the blackboard"),
+        tool::<ReadMessagesInput>("read_messages", "Read messages, newest first, with filters"),
+        tool::<GetThreadInput>(
+            "get_thread",
+            "Read the whole reply tree a message belongs to, root first, a page at a time",
+        ),
+        tool::<SearchInput>(
+            "search",
+            "Full-text search over messages and artifact descriptions",
+        ),
+        tool::<ListTagsInput>(
+            "list_tags",
+            "List the board's tags with descriptions, aliases and usage counts",
+        ),
+        tool::<MentionsInput>("mentions", "Read messages addressed to you"),
+        tool::<WaitForMessagesInput>(
+            "wait_for_messages",
+            "Block until a matching message arrives or the timeout elapses",
+        ),
+        tool::<InboxInput>("inbox", "Read your unread messages and mark them read"),
+        tool::<ClaimPathInput>(
+            "claim_path",
+            "Take an advisory lease on a path or glob before editing it",
+        ),
+        tool::<ReleasePathInput>(
+            "release_path",
+            "Release a lease on a path, or all of your leases",
+        ),
+        tool::<ListClaimsInput>(
+            "list_claims",
+            "List active leases, optionally those overlapping a path",
+        ),
+        tool::<HandoffInput>(
+            "handoff",
+            "Hand work over to another agent, who must accept or decline it",
+        ),
+        tool::<AcceptHandoffInput>("accept_handoff", "Accept a handoff addressed to you"),
+        tool::<DeclineHandoffInput>("decline_handoff", "Decline a handoff addressed to you"),
+        tool::<ListHandoffsInput>("list_handoffs", "List handoffs, newest first, with filters"),
+        tool::<ListQuestionsInput>(
+            "list_questions",
+            "List messages tagged question, oldest first; unanswered ones by default",
+        ),
+        tool::<AcceptAnswerInput>(
+            "accept_answer",
+            "Accept a reply as the answer to a question you asked",
+        ),
+        tool::<CloseQuestionInput>("close_question", "Close a question you asked"),
+        tool::<RegisterArtifactInput>(
+            "register_artifact",
+            "Register a file, directory or glob as an artifact, optionally with the artifacts it depends on or was derived from",
+        ),
+        tool::<ListArtifactsInput>("list_artifacts", "List artifacts with filters"),
+        tool::<ListArtifactsInput>(
+            "check_artifacts",
+            "Compare artifacts with their files: unchanged, modified or deleted since last registered, by whom, and which upstream artifacts changed since",
+        ),
+        tool::<RemoveArtifactInput>(
+            "remove_artifact",
+            "Remove one artifact from the registry; its history is kept, ending in a revision that records who removed it",
+        ),
+        tool::<RenameArtifactInput>(
+            "rename_artifact",
+            "Move an artifact to a new path, keeping its refs and history; the move is recorded as a revision by the caller",
+        ),
+        tool::<ChangedArtifactsInput>(
+            "list_changed_artifacts",
+            "List artifacts whose files changed since a git commit, in later commits or the working tree",
+        ),
+        tool::<ArtifactHistoryInput>(
+            "get_artifact_history",
+            "List every registration of an artifact, newest first: who registered it, when, what they said, and what it depended on or was derived from",
+        ),
+        tool::<ArtifactGraphInput>(
+            "get_artifact_graph",
+            "Follow depends_on and derived_from links from an artifact, upstream, downstream or both; each node lists the upstream artifacts that changed after it was registered",
+        ),
+        tool::<FindRefsInput>(
+            "find_refs",
+            "Find messages and artifacts that reference an external entity",
+        ),
+        tool::<SummaryInput>(
+            "summary",
+            "Get an overview of agents, messages and artifacts",
+        ),
+    ]
+}
+
+/// Turns an argument deserialization error into `INVALID_INPUT`, naming the
+/// field when serde reports one.
+fn invalid_arguments(e: serde_json::Error) -> BBError {
+    let message = e.to_string();
+    let field = ["missing field `", "unknown field `"]
+        .iter()
+        .find_map(|prefix| message.split_once(prefix))
+        .and_then(|(_, rest)| rest.split_once('`'))
+        .map(|(field, _)| field.to_string());
+    match field {
+        Some(field) => BBError::invalid_field(&field, format!("Parse error: {message}")),
+        None => BBError::InvalidInput(format!("Parse error: {message}")),
+    }
+}
+
+/// The caller-facing message for a failed tool call. Internal errors are not
+/// passed through verbatim.
+fn error_message(e: &BBError) -> String {
+    match e {
+        BBError::IdentityRequired => {
+            "Identity required. Configure --agent, set BB_AGENT_ID, or call identify.".to_string()
+        }
+        BBError::InvalidInput(msg) | BBError::NotFound(msg) | BBError::PathTraversal(msg) => {
+            msg.clone()
+        }
+        BBError::InvalidField { reason, .. } => reason.clone(),
+        BBError::SqliteError(_) if e.code() == "DATABASE_BUSY" => {
+            "Database busy. Please retry.".to_string()
+        }
+        BBError::IoError(_) => {
+            "An I/O error occurred. Please check file permissions and disk space.".to_string()
+        }
+        BBError::SqliteError(_) => {
+            "A database error occurred. Please try again or contact support.".to_string()
+        }
+        BBError::JsonError(_) => {
+            "A data serialization error occurred. Please check your input format.".to_string()
+        }
+        e => e.to_string(),
+    }
+}
+
+/// Wraps a tool's result in the `{success, data}` / `{success, error}`
+/// envelope. Failures are tool results with `isError` set, not JSON-RPC
+/// errors, so agents can read the code and details.
+fn envelope(result: Result<serde_json::Value, BBError>) -> CallToolResult {
+    match result {
+        Ok(data) => {
+            let body = json!({ "success": true, "data": data });
+            CallToolResult {
+                content: Some(vec![Content::text(body.to_string())]),
+                structured_content: Some(body),
+                is_error: Some(false),
+            }
+        }
+        Err(e) => {
+            let body = json!({
+                "success": false,
+                "error": {
+                    "code": e.code(),
+                    "message": error_message(&e),
+                    "details": e.details(),
+                },
+            });
+            CallToolResult {
+                content: Some(vec![Content::text(body.to_string())]),
+                structured_content: Some(body),
+                is_error: Some(true),
+            }
+        }
+    }
+}
+
+/// Maps a failed resource or prompt request onto a JSON-RPC error. The bb error code
+/// and details travel in `data`.
+fn rpc_error(e: BBError) -> ErrorData {
+    let data = Some(json!({ "code": e.code(), "details": e.details() }));
+    let message = error_message(&e);
+    match e {
+        BBError::NotFound(_) => ErrorData::resource_not_found(message, data),
+        BBError::InvalidInput(_) | BBError::InvalidField { .. } => {
+            ErrorData::invalid_params(message, data)
+        }
+        _ => ErrorData::internal_error(message, data),
+    }
+}
+
+impl ServerHandler for BlackboardMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        InitializeResult {
+            protocol_version: ProtocolVersion::LATEST,
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+            server_info: Implementation {
+                name: "bb".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            instructions: Some(
+                "Shared blackboard for coordinating agents: post and read messages, \
+                 share status, claim paths before editing, and register artifacts."
+                    .to_string(),
+            ),
+        }
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        // Over HTTP a session may name its agent up front instead of calling
+        // `identify`
+        let agent = context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|parts| parts.headers.get(AGENT_HEADER))
+            .map(|value| {
+                value.to_str().map_err(|_| {
+                    BBError::invalid_field("agent_id", format!("{AGENT_HEADER} is not valid text"))
+                })
+            })
+            .transpose()
+            .map_err(rpc_error)?;
+        if let Some(agent) = agent {
+            self.identity.lock().await.bind(agent).map_err(rpc_error)?;
+        }
+
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: tool_definitions(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult {
+            prompts: Ritual::ALL.iter().map(Ritual::prompt).collect(),
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let ritual = Ritual::parse(&request.name).map_err(rpc_error)?;
+        if !crate::util::discovery::is_initialized(self.db.project_dir()) {
+            return Err(rpc_error(BBError::NotInitialized));
+        }
+        let context = prompts::load_context(self.identity.clone(), &self.db)
+            .await
+            .map_err(rpc_error)?;
+        prompts::render(ritual, &context, &request.arguments.unwrap_or_default()).map_err(rpc_error)
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        Ok(ListResourcesResult {
+            resources: resources::resources(),
+            next_cursor: None,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: resources::resource_templates(),
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        let resource = self.board_resource(&request.uri)?;
+        let value = resources::read(self.identity.clone(), &self.db, resource)
+            .await
+            .map_err(rpc_error)?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/json".to_string()),
+                text: value.to_string(),
+            }],
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let resource = self.board_resource(&request.uri)?;
+        self.subscriptions.lock().unwrap().insert(resource.uri());
+
+        if !self.watching.swap(true, Ordering::SeqCst) {
+            tokio::spawn(resources::watch(
+                self.db.clone(),
+                self.subscriptions.clone(),
+                context.peer,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let resource = BoardResource::parse(&request.uri).map_err(rpc_error)?;
+        self.subscriptions.lock().unwrap().remove(&resource.uri());
+        Ok(())
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !crate::util::discovery::is_initialized(self.db.project_dir()) {
+            return Ok(envelope(Err(BBError::NotInitialized)));
+        }
+
+        let params = request.arguments.map(serde_json::Value::Object);
+        // A cancelled call gets no reply, so stop waiting on it straight away
+        let result = tokio::select! {
+            result = self.handle_request(&request.name, params) => result,
+            _ = context.ct.cancelled() => {
+                return Err(ErrorData::new(ErrorCode::INTERNAL_ERROR, "Request cancelled", None));
+            }
+        };
+
+        let mut result = envelope(result);
+        if let Some(notice) = unread_notice(self.identity.clone(), &self.db).await
+            && let Some(content) = result.content.as_mut()
+        {
+            content.push(Content::text(notice));
+        }
+        Ok(result)
+    }
+}
+
 pub async fn run_mcp_server(
     fixed_agent: Option<String>,
     env_agent: Option<String>,
     project_dir: &Path,
-) -> crate::core::errors::BBResult<()> {
-    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-
-    // Check if initialized - we'll handle NotInitialized errors as JSON-RPC responses
-    let initialized = crate::util::discovery::is_initialized(project_dir);
-
+) -> BBResult<()> {
     let identity = IdentityResolver::new(fixed_agent.clone(), env_agent.clone());
-    let server = Arc::new(BlackboardMcpServer::new(identity, project_dir));
+    let server = BlackboardMcpServer::new(identity, ConnectionPool::new(project_dir));
 
     // Log identity source for debugging
     let identity_source = if fixed_agent.is_some() {
@@ -161,162 +808,67 @@ pub async fn run_mcp_server(
     };
     tracing::debug!("MCP server identity source: {}", identity_source);
 
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-    let reader = BufReader::new(stdin);
-    let mut lines = reader.lines();
-    let mut stdout = stdout;
+    let service = server
+        .serve(StdioTransport::new())
+        .await
+        .map_err(std::io::Error::other)?;
+    service.waiting().await.map_err(std::io::Error::other)?;
 
-    // MCP protocol over stdio: read JSON-RPC requests, write responses
-    while let Ok(Some(line)) = lines.next_line().await {
-        if line.trim().is_empty() {
-            continue;
-        }
+    Ok(())
+}
 
-        // Parse request
-        let request: serde_json::Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(e) => {
-                let response = json!({
-                    "jsonrpc": "2.0",
-                    "error": {
-                        "code": -32700,
-                        "message": format!("Parse error: {}", e)
-                    },
-                    "id": null
-                });
-                let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-                let _ = stdout.flush().await;
-                continue;
-            }
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let id = request.get("id").cloned();
-        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
-        let params = request.get("params").cloned();
-
-        // Handle initialize
-        if method == "initialize" {
-            let response = json!({
-                "jsonrpc": "2.0",
-                "result": {
-                    "protocolVersion": "2024-11-05",
-                    "capabilities": {
-                        "tools": {}
-                    },
-                    "serverInfo": {
-                        "name": "bb",
-                        "version": "0.1.0"
-                    }
-                },
-                "id": id
-            });
-            let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-            let _ = stdout.flush().await;
-            continue;
-        }
+    #[test]
+    fn test_tool_schemas() {
+        insta::assert_json_snapshot!(tool_definitions());
+    }
 
-        // Handle tools/list
-        if method == "tools/list" {
-            let response = json!({
-                "jsonrpc": "2.0",
-                "result": {
-                    "tools": [
-                        { "name": "identify", "description": "Establish agent identity", "inputSchema": { "type": "object", "properties": { "agent_id": { "type": "string" }}, "required": ["agent_id"]}},
-                        { "name": "set_status", "description": "Update agent status", "inputSchema": { "type": "object", "properties": { "current_task": { "type": "string" }, "progress": { "type": "integer" }, "status": { "type": "string" }, "blockers": { "type": "string" }}}},
-                        { "name": "get_status", "description": "Get agent status", "inputSchema": { "type": "object", "properties": { "agent_id": { "type": "string" }}}},
-                        { "name": "post_message", "description": "Post a message", "inputSchema": { "type": "object", "properties": { "content": { "type": "string" }, "tags": { "type": "array" }, "priority": { "type": "string" }, "reply_to": { "type": "integer" }, "refs": { "type": "array" }}, "required": ["content"]}},
-                        { "name": "read_messages", "description": "Read messages", "inputSchema": { "type": "object", "properties": { "since": { "type": "string" }, "tags": { "type": "array" }, "from_agent": { "type": "string" }, "priority": { "type": "string" }, "limit": { "type": "integer" }}}},
-                        { "name": "register_artifact", "description": "Register artifact", "inputSchema": { "type": "object", "properties": { "path": { "type": "string" }, "description": { "type": "string" }, "version": { "type": "string" }, "refs": { "type": "array" }}, "required": ["path", "description"]}},
-                        { "name": "list_artifacts", "description": "List artifacts", "inputSchema": { "type": "object", "properties": { "by": { "type": "string" }, "limit": { "type": "integer" }}}},
-                        { "name": "find_refs", "description": "Find references", "inputSchema": { "type": "object", "properties": { "where": { "type": "string" }, "what": { "type": "string" }, "ref": { "type": "string" }}, "required": ["where", "what", "ref"]}},
-                        { "name": "summary", "description": "Get summary", "inputSchema": { "type": "object", "properties": {}}}
-                    ]
-                },
-                "id": id
-            });
-            let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-            let _ = stdout.flush().await;
-            continue;
-        }
+    #[test]
+    fn test_envelope_success() {
+        let result = envelope(Ok(json!({"id": 1})));
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(
+            result.structured_content,
+            Some(json!({"success": true, "data": {"id": 1}}))
+        );
+    }
 
-        // Handle tool calls
-        if method == "tools/call" {
-            // Check initialization first
-            if !initialized {
-                let response = json!({
-                    "jsonrpc": "2.0",
-                    "error": {
-                        "code": -32001,
-                        "message": "No blackboard found. Run 'bb init' to create one."
-                    },
-                    "id": id
-                });
-                let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-                let _ = stdout.flush().await;
-                continue;
-            }
-
-            let tool_name = params
-                .as_ref()
-                .and_then(|p| p.get("name"))
-                .and_then(|n| n.as_str())
-                .unwrap_or("");
-            let tool_params = params.as_ref().and_then(|p| p.get("arguments")).cloned();
-
-            let result = server.handle_request(tool_name, tool_params).await;
-
-            let response = match result {
-                Ok(content) => {
-                    json!({
-                        "jsonrpc": "2.0",
-                        "result": {
-                            "content": [{"type": "text", "text": content.to_string()}]
-                        },
-                        "id": id
-                    })
-                }
-                Err(e) => {
-                    let (code, message) = match e {
-                        BBError::NotInitialized => (-32001, "No blackboard found. Run 'bb init' to create one.".to_string()),
-                        BBError::IdentityRequired => (-32002, "Identity required. Configure --agent, set BB_AGENT_ID, or call identify.".to_string()),
-                        BBError::InvalidInput(msg) => (-32003, msg),
-                        BBError::NotFound(msg) => (-32004, msg),
-                        BBError::InvalidRefFormat(msg) => (-32005, msg),
-                        BBError::PathTraversal(msg) => (-32006, msg),
-                        BBError::DatabaseBusy => (-32007, "Database busy. Please retry.".to_string()),
-                        BBError::IoError(_) => (-32008, "An I/O error occurred. Please check file permissions and disk space.".to_string()),
-                        BBError::SqliteError(_) => (-32009, "A database error occurred. Please try again or contact support.".to_string()),
-                        BBError::JsonError(_) => (-32010, "A data serialization error occurred. Please check your input format.".to_string()),
-                    };
-                    json!({
-                        "jsonrpc": "2.0",
-                        "error": {
-                            "code": code,
-                            "message": message
-                        },
-                        "id": id
-                    })
-                }
-            };
-
-            let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-            let _ = stdout.flush().await;
-            continue;
-        }
+    #[test]
+    fn test_envelope_error_carries_code_and_details() {
+        let expires_at = chrono::Utc::now();
+        let result = envelope(Err(BBError::LeaseConflict {
+            path: "src/lib.rs".to_string(),
+            holder: "agent-1".to_string(),
+            expires_at,
+        }));
+        assert_eq!(result.is_error, Some(true));
+        let body = result.structured_content.unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "LEASE_CONFLICT");
+        assert_eq!(body["error"]["details"]["holder"], "agent-1");
+    }
 
-        // Unknown method
-        let response = json!({
-            "jsonrpc": "2.0",
-            "error": {
-                "code": -32601,
-                "message": format!("Method not found: {}", method)
-            },
-            "id": id
-        });
-        let _ = stdout.write_all(format!("{response}\n").as_bytes()).await;
-        let _ = stdout.flush().await;
+    #[test]
+    fn test_envelope_hides_internal_errors() {
+        let e = rusqlite::Error::InvalidColumnName("secret".to_string());
+        let body = envelope(Err(e.into())).structured_content.unwrap();
+        assert_eq!(body["error"]["code"], "INTERNAL_ERROR");
+        assert!(
+            !body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("secret")
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_invalid_arguments_names_missing_field() {
+        let e = serde_json::from_value::<PostMessageInput>(json!({})).unwrap_err();
+        let e = invalid_arguments(e);
+        assert_eq!(e.code(), "INVALID_INPUT");
+        assert_eq!(e.details(), json!({"field": "content"}));
+    }
 }
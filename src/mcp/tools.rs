@@ -1,26 +1,48 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::core::config;
 use crate::core::errors::{BBError, BBResult};
 use crate::core::models::agent::{Agent, AgentStatus};
-use crate::core::models::artifact::Artifact;
-use crate::core::models::message::{Message, Priority};
+use crate::core::models::artifact::{
+    Artifact, ArtifactCheck, ArtifactGraph, ArtifactRevision, GraphDirection,
+};
+use crate::core::models::handoff::{Handoff, HandoffStatus};
+use crate::core::models::lease::{Lease, LeaseMode};
+use crate::core::models::message::{Message, Priority, Thread};
+use crate::core::models::question::{Question, QuestionState};
 use crate::core::models::reference::Reference;
 use crate::core::operations::agent as agent_ops;
+use crate::core::operations::agent::AgentTimeline;
 use crate::core::operations::artifact as artifact_ops;
 use crate::core::operations::classify_liveness;
+use crate::core::operations::handoff as handoff_ops;
+use crate::core::operations::inbox as inbox_ops;
+use crate::core::operations::inbox::InboxPage;
+use crate::core::operations::lease as lease_ops;
 use crate::core::operations::message as message_ops;
+use crate::core::operations::message::WaitOutcome;
+use crate::core::operations::question as question_ops;
 use crate::core::operations::reference::{ReferenceResults, find_references};
-use crate::core::validation::limits::validate_agent_id;
-use crate::db::connection::with_connection;
+use crate::core::operations::search as search_ops;
+use crate::core::operations::search::SearchResults;
+use crate::core::operations::tag as tag_ops;
+use crate::core::operations::tag::TagList;
+use crate::core::validation::limits::{validate_agent_id, validate_wait_timeout};
+use crate::db::connection::ConnectionPool;
+use crate::db::queries::handoff::HandoffFilter;
+use crate::db::queries::message::MessageFilter;
+use crate::db::queries::question::QuestionFilter;
 use crate::mcp::identity::IdentityResolver;
-use std::path::Path;
 
 // Input types for MCP tools
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct IdentifyInput {
+    /// Agent ID to use for this session (e.g., "backend-agent")
     pub agent_id: String,
 }
 
@@ -28,19 +50,45 @@ pub struct IdentifyInput {
 pub struct IdentifyOutput {
     pub agent_id: String,
     pub source: String,
+    pub unread_count: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SetStatusInput {
+    /// What you are working on
     pub current_task: Option<String>,
+    /// Progress through the current task (0-100)
+    #[schemars(range(max = 100))]
     pub progress: Option<u8>,
+    /// Your current state
+    #[schemars(with = "Option<AgentStatus>")]
     pub status: Option<String>,
+    /// What is blocking you, if anything
     pub blockers: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+/// `heartbeat` takes no arguments.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HeartbeatInput {}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct GetStatusInput {
+    /// Only report this agent; all agents when omitted
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimelineInput {
+    /// Only this agent; all agents when omitted
     pub agent_id: Option<String>,
+    /// Only changes within this duration (e.g., "30m", "2h")
+    pub since: Option<String>,
+    /// Most recent changes per agent (default 50, at most 100)
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -49,60 +97,380 @@ pub struct AgentWithLiveness {
     pub agent: Agent,
     pub liveness: String,
     pub minutes_since_last_seen: i64,
+    /// Only reported for the caller's own entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct PostMessageInput {
+    /// Message text; `@agent` mentions also address the message
     pub content: String,
+    /// Tags without the leading `#` (e.g., "decision"); aliases are stored
+    /// as the tag they stand for. See `list_tags`
     pub tags: Option<Vec<String>>,
+    /// Message priority (default: the board's messages.default_priority)
+    #[schemars(with = "Option<Priority>")]
     pub priority: Option<String>,
+    /// ID of the message this replies to
     pub reply_to: Option<i64>,
+    /// References to external entities
     pub refs: Option<Vec<RefInput>>,
+    /// Agent IDs the message is addressed to
+    pub to: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A reference to an external entity, e.g. `tt:task:123`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct RefInput {
+    /// Tool or system the entity lives in (e.g., "tt", "gh")
+    #[serde(rename = "where", alias = "where_")]
     pub where_: String,
+    /// Kind of entity (e.g., "task", "issue")
     pub what: String,
+    /// Identifier of the entity
+    #[serde(rename = "ref", alias = "ref_")]
+    #[schemars(with = "RefValue")]
     pub ref_: JsonValue,
 }
 
-#[derive(Debug, Default, Deserialize)]
+/// Schema stand-in for a reference identifier: a string or a number.
+#[derive(JsonSchema)]
+#[schemars(untagged)]
+#[allow(dead_code)]
+enum RefValue {
+    Number(i64),
+    String(String),
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct ReadMessagesInput {
+    /// Only messages newer than this duration (e.g., "10m", "1h", "2d")
     pub since: Option<String>,
+    /// Only messages with any of these tags
     pub tags: Option<Vec<String>>,
+    /// Only messages from this agent
     pub from_agent: Option<String>,
+    /// Only messages at or above this priority
+    #[schemars(with = "Option<Priority>")]
     pub priority: Option<String>,
+    /// Reference filter: tool or system (use with `ref_what` and `ref_ref`)
     pub ref_where: Option<String>,
+    /// Reference filter: kind of entity
     pub ref_what: Option<String>,
+    /// Reference filter: identifier
     pub ref_ref: Option<String>,
+    /// Only messages addressed to this agent
+    pub to: Option<String>,
+    /// Only messages addressed to you
+    pub to_me: Option<bool>,
+    /// Maximum number of messages (default: the board's log.default_limit, at most 100)
     pub limit: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GetThreadInput {
+    /// ID of any message in the thread
+    pub id: i64,
+    /// Only follow replies this many levels below the root
+    pub max_depth: Option<usize>,
+    /// Number of messages to skip, in thread order (default 0)
+    pub offset: Option<usize>,
+    /// Maximum number of messages (default 50, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SearchInput {
+    /// Words to find, all of them; a trailing * matches a prefix
+    pub query: String,
+    /// Read `query` as FTS5 syntax: "phrases", prefix*, AND/OR/NOT (default false)
+    pub raw: Option<bool>,
+    /// Only results newer than this duration (e.g., "10m", "1h", "2d")
+    pub since: Option<String>,
+    /// Only messages with any of these tags
+    pub tags: Option<Vec<String>>,
+    /// Only messages from (or artifacts produced by) this agent
+    pub from_agent: Option<String>,
+    /// Only messages at or above this priority
+    #[schemars(with = "Option<Priority>")]
+    pub priority: Option<String>,
+    /// Reference filter: tool or system (use with `ref_what` and `ref_ref`)
+    pub ref_where: Option<String>,
+    /// Reference filter: kind of entity
+    pub ref_what: Option<String>,
+    /// Reference filter: identifier
+    pub ref_ref: Option<String>,
+    /// Maximum number of hits of each kind (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MentionsInput {
+    /// Only messages newer than this duration (e.g., "10m", "1h", "2d")
+    pub since: Option<String>,
+    /// Maximum number of messages (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WaitForMessagesInput {
+    /// Wait for a reply to this message ID
+    pub reply_to: Option<i64>,
+    /// Wait for a message with any of these tags
+    pub tags: Option<Vec<String>>,
+    /// Wait for a message addressed to this agent
+    pub mention: Option<String>,
+    /// Wait for a message from this agent
+    pub from_agent: Option<String>,
+    /// Wait for a message at or above this priority
+    #[schemars(with = "Option<Priority>")]
+    pub priority: Option<String>,
+    /// Only consider messages after this ID (defaults to the newest message)
+    pub after_id: Option<i64>,
+    /// How long to wait (e.g., "30s", "5m"; default "30s")
+    pub timeout: Option<String>,
+    /// Maximum number of messages to return (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InboxInput {
+    /// Maximum number of messages (default 20, at most 100)
+    pub limit: Option<usize>,
+    /// Return unread messages without marking them read
+    pub peek: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClaimPathInput {
+    /// Project-relative path or glob (e.g., "src/auth.rs", "src/db/**")
+    pub path: String,
+    /// Lease mode (default "exclusive")
+    #[schemars(with = "Option<LeaseMode>")]
+    pub mode: Option<String>,
+    /// How long the lease lasts (e.g., "30m", "2h"; default "30m")
+    pub ttl: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ReleasePathInput {
+    /// Path or glob exactly as claimed; omit when `all` is set
+    pub path: Option<String>,
+    /// Release all of your leases
+    pub all: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReleasePathOutput {
+    pub released: usize,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListClaimsInput {
+    /// Only leases overlapping this path or glob
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HandoffInput {
+    /// Agent to hand the work to
+    pub to: String,
+    /// What is done, what is left, and where to start
+    pub summary: String,
+    /// References to external entities
+    pub refs: Option<Vec<RefInput>>,
+    /// Paths of registered artifacts that go with the work
+    pub artifacts: Option<Vec<String>>,
+    /// Handoff priority (default: the board's messages.default_priority)
+    #[schemars(with = "Option<Priority>")]
+    pub priority: Option<String>,
+    /// Raise the priority if still pending after this long (e.g., "30m"; default "30m")
+    pub escalate_after: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptHandoffInput {
+    /// ID of a pending handoff addressed to you
+    pub id: i64,
+    /// Note for the sender
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DeclineHandoffInput {
+    /// ID of a pending handoff addressed to you
+    pub id: i64,
+    /// Why you are declining
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListHandoffsInput {
+    /// Only handoffs to this agent
+    pub to: Option<String>,
+    /// Only handoffs from this agent
+    pub from: Option<String>,
+    /// Only handoffs in this state
+    #[schemars(with = "Option<HandoffStatus>")]
+    pub status: Option<String>,
+    /// Maximum number of handoffs (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListQuestionsInput {
+    /// Only questions in this state (default "open")
+    #[schemars(with = "Option<QuestionState>")]
+    pub state: Option<String>,
+    /// Only questions addressed to this agent
+    pub to: Option<String>,
+    /// Only questions addressed to you
+    pub to_me: Option<bool>,
+    /// Only questions from this agent
+    pub from: Option<String>,
+    /// Maximum number of questions (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptAnswerInput {
+    /// ID of a question you asked
+    pub question_id: i64,
+    /// ID of a direct reply to accept as the answer
+    pub answer_id: i64,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CloseQuestionInput {
+    /// ID of a question you asked
+    pub question_id: i64,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct RegisterArtifactInput {
+    /// Project-relative path of an existing file or directory, or a glob such
+    /// as "src/auth/**"
     pub path: String,
+    /// What the file is or contains
     pub description: String,
+    /// Free-form version label (e.g., "v1.2.0"); defaults to the HEAD commit,
+    /// with "-dirty" if the file has uncommitted changes
     pub version: Option<String>,
+    /// References to external entities
     pub refs: Option<Vec<RefInput>>,
+    /// Paths of registered artifacts this one needs; replaces earlier links
+    pub depends_on: Option<Vec<String>>,
+    /// Paths of registered artifacts this one was generated from (e.g., a
+    /// schema for generated code); replaces earlier links
+    pub derived_from: Option<Vec<String>>,
+    /// Drop every link this artifact has; cannot be combined with
+    /// depends_on or derived_from (default false)
+    pub clear_links: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RemoveArtifactInput {
+    /// Project-relative path of a registered artifact
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RenameArtifactInput {
+    /// Path the artifact is registered under
+    pub old_path: String,
+    /// Path to move it to; must exist unless it is a glob
+    pub new_path: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ChangedArtifactsInput {
+    /// Commit SHA or other git revision (e.g., "HEAD~3", "main")
+    pub since_commit: String,
+    /// Only artifacts produced by this agent
+    pub by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ArtifactHistoryInput {
+    /// Project-relative path of a registered artifact
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ArtifactGraphInput {
+    /// Project-relative path of a registered artifact
+    pub path: String,
+    /// Which links to follow (default: both)
+    pub direction: Option<GraphDirection>,
+    /// Follow at most this many links
+    pub depth: Option<usize>,
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct ListArtifactsInput {
+    /// Only artifacts produced by this agent
     pub by: Option<String>,
+    /// Only the artifact at this directory and those below it (e.g., "src/auth")
+    pub under: Option<String>,
+    /// Reference filter: tool or system (use with `ref_what` and `ref_ref`)
     pub ref_where: Option<String>,
+    /// Reference filter: kind of entity
     pub ref_what: Option<String>,
+    /// Reference filter: identifier
     pub ref_ref: Option<String>,
+    /// Maximum number of artifacts (default 20, at most 100)
     pub limit: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct FindRefsInput {
+    /// Tool or system the entity lives in (e.g., "tt", "gh")
+    #[serde(rename = "where", alias = "where_")]
     pub where_: String,
+    /// Kind of entity (e.g., "task", "issue")
     pub what: String,
+    /// Identifier of the entity
+    #[serde(rename = "ref", alias = "ref_")]
     pub ref_: String,
 }
 
+/// `list_tags` takes no arguments.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ListTagsInput {}
+
+/// `summary` takes no arguments.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SummaryInput {}
+
 #[derive(Debug, Serialize)]
 pub struct SummaryOutput {
     pub agents: Vec<AgentWithLiveness>,
@@ -110,37 +478,76 @@ pub struct SummaryOutput {
     pub recent_messages: Vec<Message>,
     pub high_priority_messages: Vec<Message>,
     pub recent_artifacts: Vec<Artifact>,
+    pub pending_handoffs: Vec<Handoff>,
+    /// Questions open for longer than `questions.stale_minutes`.
+    pub stale_questions: i64,
+    /// Unread messages for the caller, when an identity is resolved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<i64>,
+}
+
+/// Parses a choice such as a priority, ignoring case and naming `field` if
+/// the value is not one of the choices.
+fn parse_choice<T: serde::de::DeserializeOwned>(
+    field: &str,
+    value: Option<String>,
+) -> BBResult<Option<T>> {
+    value
+        .map(|v| {
+            serde_json::from_value(JsonValue::String(v.to_lowercase()))
+                .map_err(|e| BBError::invalid_field(field, format!("invalid {field}: {e}")))
+        })
+        .transpose()
+}
+
+/// Parses a duration argument, naming `field` if it is malformed.
+fn parse_duration_arg(field: &str, s: &str) -> BBResult<chrono::Duration> {
+    crate::util::duration::parse_duration(s).map_err(|e| match e {
+        BBError::InvalidInput(reason) => BBError::invalid_field(field, reason),
+        e => e,
+    })
 }
 
 // Tool implementations
 pub async fn identify(
     identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
     input: IdentifyInput,
 ) -> BBResult<IdentifyOutput> {
     let mut resolver = identity.lock().await;
     let result = resolver.identify(&input.agent_id)?;
+    drop(resolver);
+
+    let unread_count = tokio::task::spawn_blocking({
+        let db = db.clone();
+        let agent_id = result.agent_id.clone();
+        move || db.with(|conn| inbox_ops::unread_count(conn, &agent_id))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
 
     Ok(IdentifyOutput {
         agent_id: result.agent_id,
         source: result.source,
+        unread_count,
     })
 }
 
 pub async fn set_status(
     identity: Arc<Mutex<IdentityResolver>>,
-    project_dir: &Path,
+    db: &ConnectionPool,
     input: SetStatusInput,
 ) -> BBResult<Agent> {
     let resolver = identity.lock().await;
     let agent_id = resolver.require_identity()?.to_string();
     drop(resolver);
 
-    let status = input.status.map(|s| AgentStatus::parse(&s));
+    let status: Option<AgentStatus> = parse_choice("status", input.status)?;
 
     let agent = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
+            db.with(|conn| {
                 agent_ops::update_agent_status(
                     conn,
                     &agent_id,
@@ -158,20 +565,64 @@ pub async fn set_status(
     Ok(agent)
 }
 
+/// Marks the caller as seen now without touching its status.
+pub async fn heartbeat(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+) -> BBResult<Agent> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let agent = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| agent_ops::touch_agent(conn, &agent_id))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(agent)
+}
+
+pub async fn timeline(db: &ConnectionPool, input: TimelineInput) -> BBResult<Vec<AgentTimeline>> {
+    let since = if let Some(s) = input.since {
+        let duration = parse_duration_arg("since", &s)?;
+        Some(chrono::Utc::now() - duration)
+    } else {
+        None
+    };
+    let limit = input.limit.unwrap_or(50).min(100);
+
+    let timelines = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            let liveness = &config::current().liveness;
+            db.with(|conn| {
+                agent_ops::timeline(conn, liveness, input.agent_id.as_deref(), since, limit)
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(timelines)
+}
+
 pub async fn get_status(
     identity: Arc<Mutex<IdentityResolver>>,
-    project_dir: &Path,
+    db: &ConnectionPool,
     input: GetStatusInput,
 ) -> BBResult<Vec<AgentWithLiveness>> {
+    let self_id = identity.lock().await.resolve().map(String::from);
+
     // Touch the agent's last_seen if we have an identity
     {
         let resolver = identity.lock().await;
         if let Some(agent_id) = resolver.resolve() {
             let agent_id = agent_id.to_string();
-            let project_dir = project_dir.to_path_buf();
+            let db = db.clone();
             tokio::task::spawn_blocking(move || {
-                let _ =
-                    with_connection(&project_dir, |conn| agent_ops::touch_agent(conn, &agent_id));
+                let _ = db.with(|conn| agent_ops::touch_agent(conn, &agent_id));
             })
             .await
             .ok();
@@ -179,38 +630,41 @@ pub async fn get_status(
     }
 
     let result = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         let input = input.clone();
         move || {
-            with_connection(&project_dir, |conn| {
+            let liveness = &config::current().liveness;
+            db.with(|conn| {
                 if let Some(agent_id) = &input.agent_id {
                     validate_agent_id(agent_id)?;
                     let agent = agent_ops::get_agent(conn, agent_id)?.ok_or_else(|| {
                         BBError::NotFound(format!("agent '{agent_id}' not found"))
                     })?;
 
-                    let liveness = classify_liveness(agent.last_seen);
+                    let status = classify_liveness(liveness, &agent.id, agent.last_seen);
                     let minutes = chrono::Utc::now()
                         .signed_duration_since(agent.last_seen)
                         .num_minutes();
 
                     Ok(vec![AgentWithLiveness {
-                        liveness: format!("{liveness:?}").to_lowercase(),
+                        liveness: format!("{status:?}").to_lowercase(),
                         minutes_since_last_seen: minutes,
+                        unread_count: None,
                         agent,
                     }])
                 } else {
-                    let agents = agent_ops::get_all_agents_with_liveness(conn)?;
+                    let agents = agent_ops::get_all_agents(conn)?;
                     let now = chrono::Utc::now();
 
                     Ok(agents
                         .into_iter()
                         .map(|a| {
-                            let liveness = classify_liveness(a.last_seen);
+                            let status = classify_liveness(liveness, &a.id, a.last_seen);
                             let minutes = now.signed_duration_since(a.last_seen).num_minutes();
                             AgentWithLiveness {
-                                liveness: format!("{liveness:?}").to_lowercase(),
+                                liveness: format!("{status:?}").to_lowercase(),
                                 minutes_since_last_seen: minutes,
+                                unread_count: None,
                                 agent: a,
                             }
                         })
@@ -222,22 +676,33 @@ pub async fn get_status(
     .await
     .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
 
+    let mut result = result;
+    if let Some(self_id) = self_id
+        && let Some(entry) = result.iter_mut().find(|a| a.agent.id == self_id)
+    {
+        let db = db.clone();
+        let unread = tokio::task::spawn_blocking(move || {
+            db.with(|conn| inbox_ops::unread_count(conn, &self_id))
+        })
+        .await
+        .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+        entry.unread_count = Some(unread);
+    }
+
     Ok(result)
 }
 
 pub async fn post_message(
     identity: Arc<Mutex<IdentityResolver>>,
-    project_dir: &Path,
+    db: &ConnectionPool,
     input: PostMessageInput,
 ) -> BBResult<Message> {
     let resolver = identity.lock().await;
     let agent_id = resolver.require_identity()?.to_string();
     drop(resolver);
 
-    let priority = input
-        .priority
-        .map(|p| Priority::parse(&p))
-        .unwrap_or(Priority::Normal);
+    let priority = parse_choice("priority", input.priority)?
+        .unwrap_or(config::current().messages.default_priority);
 
     let refs: Vec<Reference> = input
         .refs
@@ -255,9 +720,9 @@ pub async fn post_message(
     let tags = input.tags.unwrap_or_default();
 
     let message = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
+            db.with(|conn| {
                 message_ops::post_message(
                     conn,
                     &agent_id,
@@ -266,6 +731,7 @@ pub async fn post_message(
                     priority,
                     input.reply_to,
                     refs,
+                    input.to.unwrap_or_default(),
                 )
             })
         }
@@ -276,31 +742,105 @@ pub async fn post_message(
     Ok(message)
 }
 
-pub async fn read_messages(project_dir: &Path, input: ReadMessagesInput) -> BBResult<Vec<Message>> {
+pub async fn read_messages(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: ReadMessagesInput,
+) -> BBResult<Vec<Message>> {
+    let to = if input.to_me.unwrap_or(false) {
+        Some(identity.lock().await.require_identity()?.to_string())
+    } else {
+        input.to
+    };
+
     let since = if let Some(s) = input.since {
-        let duration = crate::util::duration::parse_duration(&s)?;
+        let duration = parse_duration_arg("since", &s)?;
         Some(chrono::Utc::now() - duration)
     } else {
         None
     };
 
-    let priority = input.priority.map(|p| Priority::parse(&p));
+    let priority = parse_choice("priority", input.priority)?;
     let tags = input.tags.unwrap_or_default();
-    let limit = input.limit.unwrap_or(20);
+    let limit = input.limit.unwrap_or(config::current().log.default_limit);
 
     let messages = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
-                message_ops::list_messages(
-                    conn,
+            db.with(|conn| {
+                let filter = MessageFilter {
                     since,
-                    &tags,
-                    input.from_agent.as_deref(),
+                    tags,
+                    from_agent: input.from_agent,
                     priority,
-                    input.ref_where.as_deref(),
-                    input.ref_what.as_deref(),
-                    input.ref_ref.as_deref(),
+                    ref_where: input.ref_where,
+                    ref_what: input.ref_what,
+                    ref_ref: input.ref_ref,
+                    to,
+                    ..Default::default()
+                };
+                message_ops::list_messages(conn, &filter, limit)
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(messages)
+}
+
+pub async fn get_thread(db: &ConnectionPool, input: GetThreadInput) -> BBResult<Thread> {
+    let limit = input.limit.unwrap_or(50).min(100);
+
+    let thread = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                message_ops::get_message_thread(
+                    conn,
+                    input.id,
+                    input.max_depth,
+                    input.offset.unwrap_or(0),
+                    Some(limit),
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(thread)
+}
+
+pub async fn search(db: &ConnectionPool, input: SearchInput) -> BBResult<SearchResults> {
+    let since = if let Some(s) = input.since {
+        let duration = parse_duration_arg("since", &s)?;
+        Some(chrono::Utc::now() - duration)
+    } else {
+        None
+    };
+
+    let filter = MessageFilter {
+        since,
+        tags: input.tags.unwrap_or_default(),
+        from_agent: input.from_agent,
+        priority: parse_choice("priority", input.priority)?,
+        ref_where: input.ref_where,
+        ref_what: input.ref_what,
+        ref_ref: input.ref_ref,
+        ..Default::default()
+    };
+    let limit = input.limit.unwrap_or(20);
+
+    let results = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                search_ops::search(
+                    conn,
+                    &input.query,
+                    input.raw.unwrap_or(false),
+                    &filter,
                     limit,
                 )
             })
@@ -309,12 +849,444 @@ pub async fn read_messages(project_dir: &Path, input: ReadMessagesInput) -> BBRe
     .await
     .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
 
+    Ok(results)
+}
+
+pub async fn list_tags(db: &ConnectionPool) -> BBResult<TagList> {
+    let list = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| tag_ops::list_tags(conn, tag_ops::registry()))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(list)
+}
+
+pub async fn mentions(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: MentionsInput,
+) -> BBResult<Vec<Message>> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let since = if let Some(s) = input.since {
+        let duration = parse_duration_arg("since", &s)?;
+        Some(chrono::Utc::now() - duration)
+    } else {
+        None
+    };
+    let limit = input.limit.unwrap_or(20);
+
+    let messages = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                agent_ops::touch_agent(conn, &agent_id)?;
+                let filter = MessageFilter {
+                    since,
+                    to: Some(agent_id),
+                    ..Default::default()
+                };
+                message_ops::list_messages(conn, &filter, limit)
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
     Ok(messages)
 }
 
+pub async fn wait_for_messages(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: WaitForMessagesInput,
+) -> BBResult<WaitOutcome> {
+    let timeout = parse_duration_arg("timeout", input.timeout.as_deref().unwrap_or("30s"))?;
+    let agent_id = identity.lock().await.resolve().map(String::from);
+
+    let filter = MessageFilter {
+        tags: input.tags.unwrap_or_default(),
+        from_agent: input.from_agent,
+        priority: parse_choice("priority", input.priority)?,
+        in_reply_to: input.reply_to,
+        to: input.mention,
+        ..Default::default()
+    };
+    let limit = input.limit.unwrap_or(20);
+    validate_wait_timeout(timeout)?;
+
+    // Wait in one-second slices, each on its own blocking task, so a
+    // cancelled call or a closing server is never stuck behind a long wait
+    let mut after_id = input.after_id;
+    let mut outcome = None;
+    for slice in 0..timeout.num_seconds() {
+        let filter = filter.clone();
+        let agent_id = agent_id.clone();
+        let next = tokio::task::spawn_blocking({
+            let db = db.clone();
+            move || {
+                db.with(|conn| {
+                    // Waiting is a sign of life; record it before blocking
+                    if let Some(agent_id) = agent_id.filter(|_| slice == 0) {
+                        agent_ops::touch_agent(conn, &agent_id)?;
+                    }
+                    message_ops::wait_for_messages(
+                        conn,
+                        &filter,
+                        after_id,
+                        chrono::Duration::seconds(1),
+                        limit,
+                    )
+                })
+            }
+        })
+        .await
+        .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+        after_id = Some(next.last_id);
+        let done = !next.timed_out;
+        outcome = Some(next);
+        if done {
+            break;
+        }
+    }
+
+    Ok(outcome.expect("validated timeout is at least one second"))
+}
+
+pub async fn inbox(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: InboxInput,
+) -> BBResult<InboxPage> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let limit = input.limit.unwrap_or(20);
+    let peek = input.peek.unwrap_or(false);
+
+    let page = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                agent_ops::touch_agent(conn, &agent_id)?;
+                inbox_ops::read_inbox(conn, &agent_id, limit, peek)
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(page)
+}
+
+/// Builds the unread notice appended to every tool result. Returns `None`
+/// when there is no identity, nothing unread, or the lookup fails, so a
+/// notice can never turn a successful call into an error.
+pub async fn unread_notice(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+) -> Option<String> {
+    let agent_id = identity.lock().await.resolve()?.to_string();
+
+    let summary = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| inbox_ops::unread_summary(conn, &agent_id))
+    })
+    .await
+    .ok()?
+    .ok()?;
+
+    if summary.unread == 0 {
+        return None;
+    }
+
+    let noun = if summary.unread == 1 {
+        "message"
+    } else {
+        "messages"
+    };
+    Some(format!(
+        "You have {} unread {noun}, {} addressed to you, {} critical. Call inbox to read them.",
+        summary.unread, summary.addressed, summary.critical
+    ))
+}
+
+pub async fn claim_path(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: ClaimPathInput,
+) -> BBResult<Lease> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let mode = parse_choice("mode", input.mode)?.unwrap_or(LeaseMode::Exclusive);
+    let ttl = parse_duration_arg("ttl", input.ttl.as_deref().unwrap_or("30m"))?;
+
+    let lease = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            let liveness = &config::current().liveness;
+            db.with(|conn| {
+                lease_ops::claim_path(
+                    conn,
+                    &agent_id,
+                    &input.path,
+                    mode,
+                    ttl,
+                    db.project_dir(),
+                    liveness,
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(lease)
+}
+
+pub async fn release_path(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: ReleasePathInput,
+) -> BBResult<ReleasePathOutput> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let all = input.all.unwrap_or(false);
+    if all == input.path.is_some() {
+        return Err(BBError::invalid_field("path", "Specify either path or all"));
+    }
+
+    let released = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| match input.path {
+                Some(path) => lease_ops::release_path(conn, &agent_id, &path).map(|()| 1),
+                None => lease_ops::release_all(conn, &agent_id),
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(ReleasePathOutput { released })
+}
+
+pub async fn list_claims(db: &ConnectionPool, input: ListClaimsInput) -> BBResult<Vec<Lease>> {
+    let leases = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            let liveness = &config::current().liveness;
+            db.with(|conn| lease_ops::list_claims(conn, input.path.as_deref(), liveness))
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(leases)
+}
+
+pub async fn handoff(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: HandoffInput,
+) -> BBResult<Handoff> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let priority = parse_choice("priority", input.priority)?
+        .unwrap_or(config::current().messages.default_priority);
+    let escalate_after = match input.escalate_after.as_deref() {
+        Some(s) => parse_duration_arg("escalate_after", s)?,
+        None => chrono::Duration::minutes(handoff_ops::DEFAULT_ESCALATE_AFTER_MINS),
+    };
+
+    let refs: Vec<Reference> = input
+        .refs
+        .map(|refs| {
+            refs.into_iter()
+                .map(|r| Reference {
+                    where_: r.where_,
+                    what: r.what,
+                    ref_: r.ref_,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let handoff = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                handoff_ops::create_handoff(
+                    conn,
+                    &agent_id,
+                    &input.to,
+                    &input.summary,
+                    refs,
+                    input.artifacts.unwrap_or_default(),
+                    priority,
+                    escalate_after,
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(handoff)
+}
+
+pub async fn accept_handoff(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: AcceptHandoffInput,
+) -> BBResult<Handoff> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let handoff = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                handoff_ops::accept_handoff(conn, &agent_id, input.id, input.note.as_deref())
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(handoff)
+}
+
+pub async fn decline_handoff(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: DeclineHandoffInput,
+) -> BBResult<Handoff> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let handoff = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                handoff_ops::decline_handoff(conn, &agent_id, input.id, input.reason.as_deref())
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(handoff)
+}
+
+pub async fn list_handoffs(
+    db: &ConnectionPool,
+    input: ListHandoffsInput,
+) -> BBResult<Vec<Handoff>> {
+    let filter = HandoffFilter {
+        to: input.to,
+        from: input.from,
+        status: parse_choice::<HandoffStatus>("status", input.status)?,
+    };
+    let limit = input.limit.unwrap_or(20).min(100);
+
+    let handoffs = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| handoff_ops::list_handoffs(conn, &filter, limit))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(handoffs)
+}
+
+pub async fn list_questions(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: ListQuestionsInput,
+) -> BBResult<Vec<Question>> {
+    let to = if input.to_me.unwrap_or(false) {
+        Some(identity.lock().await.require_identity()?.to_string())
+    } else {
+        input.to
+    };
+    let filter = QuestionFilter {
+        state: Some(parse_choice("state", input.state)?.unwrap_or(QuestionState::Open)),
+        to,
+        from: input.from,
+        before: None,
+    };
+    let limit = input.limit.unwrap_or(20).min(100);
+
+    let questions = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| question_ops::list_questions(conn, &filter, limit))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(questions)
+}
+
+pub async fn accept_answer(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: AcceptAnswerInput,
+) -> BBResult<Question> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let question = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                question_ops::accept_answer(conn, &agent_id, input.question_id, input.answer_id)
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(question)
+}
+
+pub async fn close_question(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: CloseQuestionInput,
+) -> BBResult<Question> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let question = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| question_ops::close_question(conn, &agent_id, input.question_id))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(question)
+}
+
 pub async fn register_artifact(
     identity: Arc<Mutex<IdentityResolver>>,
-    project_dir: &Path,
+    db: &ConnectionPool,
     input: RegisterArtifactInput,
 ) -> BBResult<Artifact> {
     let resolver = identity.lock().await;
@@ -335,17 +1307,20 @@ pub async fn register_artifact(
         .unwrap_or_default();
 
     let artifact = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
-                artifact_ops::register_artifact(
+            db.with(|conn| {
+                artifact_ops::register_linked_artifact(
                     conn,
                     &input.path,
                     &agent_id,
                     &input.description,
                     input.version.as_deref(),
                     refs,
-                    &project_dir,
+                    input.depends_on.as_deref().unwrap_or_default(),
+                    input.derived_from.as_deref().unwrap_or_default(),
+                    input.clear_links.unwrap_or(false),
+                    db.project_dir(),
                 )
             })
         }
@@ -357,22 +1332,123 @@ pub async fn register_artifact(
 }
 
 pub async fn list_artifacts(
-    project_dir: &Path,
+    db: &ConnectionPool,
     input: ListArtifactsInput,
 ) -> BBResult<Vec<Artifact>> {
     let limit = input.limit.unwrap_or(50);
 
     let artifacts = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
+            db.with(|conn| {
                 artifact_ops::list_artifacts(
                     conn,
                     input.by.as_deref(),
+                    input.under.as_deref(),
+                    input.ref_where.as_deref(),
+                    input.ref_what.as_deref(),
+                    input.ref_ref.as_deref(),
+                    limit,
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(artifacts)
+}
+
+pub async fn check_artifacts(
+    db: &ConnectionPool,
+    input: ListArtifactsInput,
+) -> BBResult<Vec<ArtifactCheck>> {
+    let limit = input.limit.unwrap_or(50);
+
+    let checks = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                artifact_ops::check_artifacts(
+                    conn,
+                    input.by.as_deref(),
+                    input.under.as_deref(),
                     input.ref_where.as_deref(),
                     input.ref_what.as_deref(),
                     input.ref_ref.as_deref(),
                     limit,
+                    db.project_dir(),
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(checks)
+}
+
+pub async fn remove_artifact(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: RemoveArtifactInput,
+) -> BBResult<Artifact> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let artifact = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| artifact_ops::remove_artifact(conn, &agent_id, &input.path))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(artifact)
+}
+
+pub async fn rename_artifact(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+    input: RenameArtifactInput,
+) -> BBResult<Artifact> {
+    let resolver = identity.lock().await;
+    let agent_id = resolver.require_identity()?.to_string();
+    drop(resolver);
+
+    let artifact = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                artifact_ops::rename_artifact(
+                    conn,
+                    &agent_id,
+                    &input.old_path,
+                    &input.new_path,
+                    db.project_dir(),
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(artifact)
+}
+
+pub async fn list_changed_artifacts(
+    db: &ConnectionPool,
+    input: ChangedArtifactsInput,
+) -> BBResult<Vec<Artifact>> {
+    let artifacts = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                artifact_ops::changed_since_commit(
+                    conn,
+                    &input.since_commit,
+                    input.by.as_deref(),
+                    db.project_dir(),
                 )
             })
         }
@@ -383,7 +1459,45 @@ pub async fn list_artifacts(
     Ok(artifacts)
 }
 
-pub async fn find_refs(project_dir: &Path, input: FindRefsInput) -> BBResult<ReferenceResults> {
+pub async fn get_artifact_history(
+    db: &ConnectionPool,
+    input: ArtifactHistoryInput,
+) -> BBResult<Vec<ArtifactRevision>> {
+    let revisions = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || db.with(|conn| artifact_ops::artifact_history(conn, &input.path))
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(revisions)
+}
+
+pub async fn get_artifact_graph(
+    db: &ConnectionPool,
+    input: ArtifactGraphInput,
+) -> BBResult<ArtifactGraph> {
+    let graph = tokio::task::spawn_blocking({
+        let db = db.clone();
+        move || {
+            db.with(|conn| {
+                artifact_ops::artifact_graph(
+                    conn,
+                    &input.path,
+                    input.direction.unwrap_or(GraphDirection::Both),
+                    input.depth,
+                    db.project_dir(),
+                )
+            })
+        }
+    })
+    .await
+    .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
+
+    Ok(graph)
+}
+
+pub async fn find_refs(db: &ConnectionPool, input: FindRefsInput) -> BBResult<ReferenceResults> {
     // Parse the ref value (try number first, then string)
     let ref_value: JsonValue = if let Ok(num) = input.ref_.parse::<i64>() {
         JsonValue::Number(num.into())
@@ -392,12 +1506,8 @@ pub async fn find_refs(project_dir: &Path, input: FindRefsInput) -> BBResult<Ref
     };
 
     let results = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
-        move || {
-            with_connection(&project_dir, |conn| {
-                find_references(conn, &input.where_, &input.what, &ref_value)
-            })
-        }
+        let db = db.clone();
+        move || db.with(|conn| find_references(conn, &input.where_, &input.what, &ref_value))
     })
     .await
     .map_err(|e| BBError::InvalidInput(format!("Task join error: {e}")))??;
@@ -405,22 +1515,29 @@ pub async fn find_refs(project_dir: &Path, input: FindRefsInput) -> BBResult<Ref
     Ok(results)
 }
 
-pub async fn summary(project_dir: &Path) -> BBResult<SummaryOutput> {
+pub async fn summary(
+    identity: Arc<Mutex<IdentityResolver>>,
+    db: &ConnectionPool,
+) -> BBResult<SummaryOutput> {
+    let agent_id = identity.lock().await.resolve().map(String::from);
+
     let result = tokio::task::spawn_blocking({
-        let project_dir = project_dir.to_path_buf();
+        let db = db.clone();
         move || {
-            with_connection(&project_dir, |conn| {
-                let agents = agent_ops::get_all_agents_with_liveness(conn)?;
+            let liveness = &config::current().liveness;
+            db.with(|conn| {
+                let agents = agent_ops::get_all_agents(conn)?;
                 let now = chrono::Utc::now();
 
                 let agents_with_liveness: Vec<_> = agents
                     .into_iter()
                     .map(|a| {
-                        let liveness = classify_liveness(a.last_seen);
+                        let status = classify_liveness(liveness, &a.id, a.last_seen);
                         let minutes = now.signed_duration_since(a.last_seen).num_minutes();
                         AgentWithLiveness {
-                            liveness: format!("{liveness:?}").to_lowercase(),
+                            liveness: format!("{status:?}").to_lowercase(),
                             minutes_since_last_seen: minutes,
+                            unread_count: None,
                             agent: a,
                         }
                     })
@@ -433,36 +1550,25 @@ pub async fn summary(project_dir: &Path) -> BBResult<SummaryOutput> {
                     .collect();
 
                 let recent_since = now - chrono::Duration::minutes(30);
-                let recent_messages = message_ops::list_messages(
-                    conn,
-                    Some(recent_since),
-                    &[],
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    20,
-                )?;
-
-                let high_priority_messages = message_ops::list_messages(
-                    conn,
-                    None,
-                    &[],
-                    None,
-                    Some(Priority::High),
-                    None,
-                    None,
-                    None,
-                    10,
-                )?;
+                let recent_filter = MessageFilter {
+                    since: Some(recent_since),
+                    ..Default::default()
+                };
+                let recent_messages = message_ops::list_messages(conn, &recent_filter, 20)?;
+
+                let high_priority_filter = MessageFilter {
+                    priority: Some(Priority::High),
+                    ..Default::default()
+                };
+                let high_priority_messages =
+                    message_ops::list_messages(conn, &high_priority_filter, 10)?;
 
                 let artifact_since = now - chrono::Duration::hours(1);
                 let recent_artifacts =
-                    artifact_ops::list_artifacts(conn, None, None, None, None, 20)?;
+                    artifact_ops::list_artifacts(conn, None, None, None, None, None, 20)?;
                 let recent_artifacts: Vec<_> = recent_artifacts
                     .into_iter()
-                    .filter(|a| a.created_at >= artifact_since)
+                    .filter(|a| a.updated_at >= artifact_since)
                     .collect();
 
                 Ok(SummaryOutput {
@@ -471,6 +1577,14 @@ pub async fn summary(project_dir: &Path) -> BBResult<SummaryOutput> {
                     recent_messages,
                     high_priority_messages,
                     recent_artifacts,
+                    pending_handoffs: handoff_ops::pending_handoffs(conn)?,
+                    stale_questions: question_ops::stale_question_count(
+                        conn,
+                        &config::current().questions,
+                    )?,
+                    unread_count: agent_id
+                        .map(|id| inbox_ops::unread_count(conn, &id))
+                        .transpose()?,
                 })
             })
         }
@@ -0,0 +1,253 @@
+use rmcp::RoleServer;
+use rmcp::model::{
+    ClientJsonRpcMessage, ClientNotification, ErrorCode, JsonRpcMessage, JsonRpcNotification,
+    RequestId, ServerJsonRpcMessage,
+};
+use rmcp::transport::Transport;
+use serde_json::{Value, json};
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines, Stdin, Stdout};
+use tokio::sync::{Mutex, Notify};
+
+/// Request methods a client may send. A known method that fails to parse has
+/// bad params; anything else is an unknown method.
+const CLIENT_REQUEST_METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "completion/complete",
+    "logging/setLevel",
+    "prompts/get",
+    "prompts/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "resources/subscribe",
+    "resources/unsubscribe",
+    "tools/call",
+    "tools/list",
+];
+
+/// Newline-delimited JSON-RPC over stdin/stdout.
+///
+/// `rmcp`'s own stdio transport ends the session on the first message it
+/// cannot parse. This one answers such messages with a JSON-RPC error and
+/// carries on, unpacks batches into individual messages, drops the reply to
+/// a request the client has cancelled, and on EOF waits for in-flight
+/// requests to be answered so piped input gets every reply.
+pub struct StdioTransport {
+    lines: Lines<BufReader<Stdin>>,
+    stdout: Arc<Mutex<Stdout>>,
+    pending: VecDeque<ClientJsonRpcMessage>,
+    in_flight: Arc<InFlight>,
+    cancelled: Arc<std::sync::Mutex<HashSet<RequestId>>>,
+}
+
+/// Replies still owed to the client.
+#[derive(Default)]
+struct InFlight {
+    count: AtomicUsize,
+    done: Notify,
+}
+
+impl InFlight {
+    fn start(&self) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn finish(&self) {
+        if self.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.done.notify_waiters();
+        }
+    }
+}
+
+impl StdioTransport {
+    pub fn new() -> Self {
+        Self {
+            lines: BufReader::new(tokio::io::stdin()).lines(),
+            stdout: Arc::new(Mutex::new(tokio::io::stdout())),
+            pending: VecDeque::new(),
+            in_flight: Arc::default(),
+            cancelled: Arc::default(),
+        }
+    }
+
+    async fn write_line(stdout: &Mutex<Stdout>, line: String) -> std::io::Result<()> {
+        let mut stdout = stdout.lock().await;
+        stdout.write_all(line.as_bytes()).await?;
+        stdout.write_all(b"\n").await?;
+        stdout.flush().await
+    }
+
+    /// Writes a reply that did not come from the service. `receive` must stay
+    /// cancel-safe, so the write happens on its own task.
+    fn reply(&self, message: Value) {
+        let stdout = self.stdout.clone();
+        let in_flight = self.in_flight.clone();
+        in_flight.start();
+        tokio::spawn(async move {
+            if let Err(e) = Self::write_line(&stdout, message.to_string()).await {
+                tracing::error!("Error writing to stdout: {}", e);
+            }
+            in_flight.finish();
+        });
+    }
+
+    /// Queues the messages on one input line, answering any that are invalid.
+    fn accept_line(&mut self, line: &str) {
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                self.reply(error_response(
+                    Value::Null,
+                    ErrorCode::PARSE_ERROR,
+                    format!("Parse error: {e}"),
+                ));
+                return;
+            }
+        };
+
+        let items = match value {
+            Value::Array(items) if items.is_empty() => {
+                self.reply(error_response(
+                    Value::Null,
+                    ErrorCode::INVALID_REQUEST,
+                    "Empty batch".to_string(),
+                ));
+                return;
+            }
+            Value::Array(items) => items,
+            item => vec![item],
+        };
+
+        for item in items {
+            match serde_json::from_value::<ClientJsonRpcMessage>(item.clone()) {
+                Ok(message) => self.pending.push_back(message),
+                Err(e) => {
+                    if let Some(error) = rejection(&item, e) {
+                        self.reply(error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for StdioTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error reply for a message that did not parse, or `None` for a
+/// notification, which never gets a reply.
+fn rejection(item: &Value, e: serde_json::Error) -> Option<Value> {
+    let id = item.get("id").cloned();
+    let method = item.get("method").and_then(Value::as_str);
+
+    match (id, method) {
+        (None, Some(_)) => None,
+        (Some(id), Some(method)) if CLIENT_REQUEST_METHODS.contains(&method) => {
+            Some(error_response(
+                id,
+                ErrorCode::INVALID_PARAMS,
+                format!("Invalid params: {e}"),
+            ))
+        }
+        (Some(id), Some(method)) => Some(error_response(
+            id,
+            ErrorCode::METHOD_NOT_FOUND,
+            format!("Method not found: {method}"),
+        )),
+        (id, None) => Some(error_response(
+            id.unwrap_or(Value::Null),
+            ErrorCode::INVALID_REQUEST,
+            format!("Invalid request: {e}"),
+        )),
+    }
+}
+
+fn error_response(id: Value, code: ErrorCode, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code.0, "message": message }
+    })
+}
+
+impl Transport<RoleServer> for StdioTransport {
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: ServerJsonRpcMessage,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let stdout = self.stdout.clone();
+        let in_flight = self.in_flight.clone();
+        let answered = match &item {
+            JsonRpcMessage::Response(response) => Some(response.id.clone()),
+            JsonRpcMessage::Error(error) => Some(error.id.clone()),
+            _ => None,
+        };
+        let skip = answered
+            .as_ref()
+            .is_some_and(|id| self.cancelled.lock().unwrap().remove(id));
+
+        async move {
+            let result = if skip {
+                Ok(())
+            } else {
+                match serde_json::to_string(&item) {
+                    Ok(line) => Self::write_line(&stdout, line).await,
+                    Err(e) => Err(e.into()),
+                }
+            };
+            if answered.is_some() {
+                in_flight.finish();
+            }
+            result
+        }
+    }
+
+    async fn receive(&mut self) -> Option<ClientJsonRpcMessage> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                match &message {
+                    JsonRpcMessage::Request(_) => self.in_flight.start(),
+                    JsonRpcMessage::Notification(JsonRpcNotification {
+                        notification: ClientNotification::CancelledNotification(cancelled),
+                        ..
+                    }) => {
+                        self.cancelled
+                            .lock()
+                            .unwrap()
+                            .insert(cancelled.params.request_id.clone());
+                    }
+                    _ => {}
+                }
+                return Some(message);
+            }
+
+            match self.lines.next_line().await {
+                Ok(Some(line)) if line.trim().is_empty() => continue,
+                Ok(Some(line)) => self.accept_line(&line),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        // Input is closed; let outstanding replies go out before shutting down
+        loop {
+            let done = self.in_flight.done.notified();
+            if self.in_flight.count.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+            done.await;
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! Reads the git repository a project lives in, through the `git` binary.
+//!
+//! A project that is not in a repository, or a machine without git, is not
+//! an error: the functions that describe a path return `None`. Only those
+//! that are asked about a specific commit fail.
+
+use crate::core::errors::{BBError, BBResult};
+use crate::core::models::artifact::GitVersion;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// The repository state of `path`, relative to `dir`: the HEAD commit,
+/// whether the path differs from it, and the last commit that touched it.
+/// `None` if `dir` is not in a repository with at least one commit.
+pub fn version(dir: &Path, path: &str) -> BBResult<Option<GitVersion>> {
+    let paths = [path.to_string()];
+    Ok(versions(dir, &paths)?.and_then(|mut versions| versions.remove(path)))
+}
+
+/// Like `version`, for many paths at once: git runs three times however
+/// many paths there are.
+pub fn versions(dir: &Path, paths: &[String]) -> BBResult<Option<HashMap<String, GitVersion>>> {
+    // The prefix of `dir` within the repository, then HEAD
+    let Some(out) = output(dir, &["rev-parse", "--show-prefix", "HEAD"])? else {
+        return Ok(None);
+    };
+    let mut lines: Vec<&str> = out.lines().collect();
+    let head = lines.pop().unwrap_or_default().to_string();
+    let prefix = lines.pop().unwrap_or_default();
+    if paths.is_empty() {
+        return Ok(Some(HashMap::new()));
+    }
+
+    // Porcelain status paths are relative to the repository root
+    let mut args = vec![
+        "status",
+        "--porcelain",
+        "-z",
+        "--untracked-files=all",
+        "--ignored",
+        "--",
+    ];
+    args.extend(paths.iter().map(String::as_str));
+    let status = raw_output(dir, &args)?.unwrap_or_default();
+    let mut changed = Vec::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        let Some((code, path)) = entry.split_at_checked(3) else {
+            continue;
+        };
+        changed.push(path.strip_prefix(prefix).unwrap_or(path));
+        // A rename or copy is followed by the path it came from
+        if code.contains(['R', 'C']) {
+            entries.next();
+        }
+    }
+
+    // Newest first, so the first commit that lists a path last touched it
+    let mut args = vec![
+        "log",
+        "--format=%x01%H",
+        "--name-only",
+        "-z",
+        "--relative",
+        "HEAD",
+        "--",
+    ];
+    args.extend(paths.iter().map(String::as_str));
+    let log = output(dir, &args)?.unwrap_or_default();
+    let mut last_commits: HashMap<&str, &str> = HashMap::new();
+    for commit in log.split('\x01').filter(|c| !c.is_empty()) {
+        let (sha, files) = commit.split_once(['\0', '\n']).unwrap_or((commit, ""));
+        for file in files.split('\0').map(str::trim).filter(|f| !f.is_empty()) {
+            // A commit that touched a file also touched its directories
+            let dirs = file.char_indices().filter(|&(_, c)| c == '/');
+            for end in dirs.map(|(i, _)| i).chain([file.len()]) {
+                last_commits.entry(&file[..end]).or_insert(sha.trim());
+            }
+        }
+    }
+
+    Ok(Some(
+        paths
+            .iter()
+            .map(|path| {
+                let version = GitVersion {
+                    head: head.clone(),
+                    dirty: changed.iter().any(|c| covers(c, path)),
+                    last_commit: last_commits.get(path.as_str()).map(|sha| sha.to_string()),
+                };
+                (path.clone(), version)
+            })
+            .collect(),
+    ))
+}
+
+/// Whether status entry `changed` is `path`, a directory holding it, or a
+/// file under it.
+fn covers(changed: &str, path: &str) -> bool {
+    changed == path
+        || (changed.ends_with('/') && path.starts_with(changed))
+        || changed
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// The full SHA of `rev`, which must name a commit.
+pub fn resolve_commit(dir: &Path, rev: &str) -> BBResult<String> {
+    if output(dir, &["rev-parse", "--is-inside-work-tree"])?.is_none() {
+        return Err(BBError::InvalidInput(format!(
+            "{} is not in a git repository",
+            dir.display()
+        )));
+    }
+    if rev.is_empty() || rev.starts_with('-') {
+        return Err(BBError::invalid_field(
+            "commit",
+            format!("invalid commit: {rev:?}"),
+        ));
+    }
+    output(
+        dir,
+        &["rev-parse", "--verify", "-q", &format!("{rev}^{{commit}}")],
+    )?
+    .ok_or_else(|| BBError::invalid_field("commit", format!("unknown commit: {rev}")))
+}
+
+/// The first line of the message of `commit`, a full SHA.
+pub fn commit_subject(dir: &Path, commit: &str) -> BBResult<String> {
+    Ok(output(dir, &["log", "-1", "--format=%s", commit])?.unwrap_or_default())
+}
+
+/// Files under `dir` that `commit` added or modified, relative to `dir`.
+pub fn files_in_commit(dir: &Path, commit: &str) -> BBResult<Vec<String>> {
+    paths(
+        dir,
+        &[
+            "diff-tree",
+            "--no-commit-id",
+            "--name-only",
+            "-r",
+            "-z",
+            "--root",
+            "--relative",
+            "--diff-filter=d",
+            commit,
+        ],
+    )
+}
+
+/// Files under `dir` that differ from `commit`: changed in later commits,
+/// changed in the working tree, or new and untracked.
+pub fn changed_since(dir: &Path, commit: &str) -> BBResult<Vec<String>> {
+    let mut changed = paths(
+        dir,
+        &["diff", "--name-only", "-z", "--relative", commit, "--"],
+    )?;
+    for path in paths(dir, &["ls-files", "--others", "--exclude-standard", "-z"])? {
+        if !changed.contains(&path) {
+            changed.push(path);
+        }
+    }
+    Ok(changed)
+}
+
+fn paths(dir: &Path, args: &[&str]) -> BBResult<Vec<String>> {
+    let out = output(dir, args)?.ok_or_else(|| {
+        BBError::InvalidInput(format!("git {} failed in {}", args[0], dir.display()))
+    })?;
+    Ok(out
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Runs git in `dir` and returns its trimmed output, or `None` if git is
+/// not installed or the command failed.
+fn output(dir: &Path, args: &[&str]) -> BBResult<Option<String>> {
+    Ok(raw_output(dir, args)?.map(|out| out.trim().to_string()))
+}
+
+/// Like `output`, but untrimmed, for formats where leading spaces matter.
+fn raw_output(dir: &Path, args: &[&str]) -> BBResult<Option<String>> {
+    let out = match Command::new("git").arg("-C").arg(dir).args(args).output() {
+        Ok(out) => out,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if !out.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&out.stdout).into_owned()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Runs git in `dir` as a test author, panicking if it fails.
+    pub(crate) fn git(dir: &Path, args: &[&str]) -> String {
+        let out = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    }
+
+    /// A repository with one commit adding `a.txt` and `b.txt`.
+    pub(crate) fn repo() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        git(temp.path(), &["init", "-q"]);
+        fs::write(temp.path().join("a.txt"), "a").unwrap();
+        fs::write(temp.path().join("b.txt"), "b").unwrap();
+        git(temp.path(), &["add", "."]);
+        git(temp.path(), &["commit", "-q", "-m", "Add a and b"]);
+        temp
+    }
+
+    #[test]
+    fn test_version_tracks_dirty_and_last_commit() {
+        let temp = repo();
+        let first = git(temp.path(), &["rev-parse", "HEAD"]);
+        fs::write(temp.path().join("b.txt"), "b2").unwrap();
+        git(temp.path(), &["commit", "-q", "-am", "Change b"]);
+        let second = git(temp.path(), &["rev-parse", "HEAD"]);
+
+        let a = version(temp.path(), "a.txt").unwrap().unwrap();
+        assert_eq!(a.head, second);
+        assert_eq!(a.last_commit.as_deref(), Some(first.as_str()));
+        assert!(!a.dirty);
+
+        fs::write(temp.path().join("a.txt"), "a2").unwrap();
+        assert!(version(temp.path(), "a.txt").unwrap().unwrap().dirty);
+
+        fs::write(temp.path().join("new.txt"), "").unwrap();
+        let new = version(temp.path(), "new.txt").unwrap().unwrap();
+        assert!(new.dirty);
+        assert!(new.last_commit.is_none());
+    }
+
+    #[test]
+    fn test_versions_in_one_batch() {
+        let temp = repo();
+        let first = git(temp.path(), &["rev-parse", "HEAD"]);
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("sub/c.txt"), "c").unwrap();
+        fs::write(temp.path().join("sub/d.txt"), "d").unwrap();
+        git(temp.path(), &["add", "."]);
+        git(temp.path(), &["commit", "-q", "-m", "Add c and d"]);
+        let second = git(temp.path(), &["rev-parse", "HEAD"]);
+        fs::write(temp.path().join("sub/c.txt"), " c2").unwrap();
+        fs::write(temp.path().join("sub/e.txt"), "e").unwrap();
+
+        // Paths are relative to `dir`, even below the repository root
+        let sub = temp.path().join("sub");
+        let paths = ["c.txt", "d.txt", "e.txt"].map(String::from);
+        let versions = versions(&sub, &paths).unwrap().unwrap();
+        let c = &versions["c.txt"];
+        assert_eq!(c.head, second);
+        assert!(c.dirty);
+        assert_eq!(c.last_commit.as_deref(), Some(second.as_str()));
+        assert!(!versions["d.txt"].dirty);
+        assert!(versions["e.txt"].dirty);
+        assert!(versions["e.txt"].last_commit.is_none());
+
+        let paths = ["a.txt", "sub"].map(String::from);
+        let versions = super::versions(temp.path(), &paths).unwrap().unwrap();
+        assert_eq!(
+            versions["a.txt"].last_commit.as_deref(),
+            Some(first.as_str())
+        );
+        assert!(!versions["a.txt"].dirty);
+        assert!(versions["sub"].dirty);
+        assert_eq!(
+            versions["sub"].last_commit.as_deref(),
+            Some(second.as_str())
+        );
+    }
+
+    #[test]
+    fn test_outside_a_repository() {
+        let temp = TempDir::new().unwrap();
+        assert!(version(temp.path(), "a.txt").unwrap().is_none());
+        assert!(resolve_commit(temp.path(), "HEAD").is_err());
+    }
+
+    #[test]
+    fn test_commit_files_and_changes_since() {
+        let temp = repo();
+        let first = resolve_commit(temp.path(), "HEAD").unwrap();
+        assert_eq!(commit_subject(temp.path(), &first).unwrap(), "Add a and b");
+        assert_eq!(
+            files_in_commit(temp.path(), &first).unwrap(),
+            vec!["a.txt", "b.txt"]
+        );
+        assert!(resolve_commit(temp.path(), "nope").is_err());
+        assert!(resolve_commit(temp.path(), "--all").is_err());
+
+        fs::write(temp.path().join("a.txt"), "a2").unwrap();
+        fs::write(temp.path().join("c.txt"), "c").unwrap();
+        assert_eq!(
+            changed_since(temp.path(), &first).unwrap(),
+            vec!["a.txt", "c.txt"]
+        );
+    }
+}
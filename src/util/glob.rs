@@ -0,0 +1,163 @@
+//! Minimal glob matching for project-relative paths.
+//!
+//! Supports `*` and `?` within a path segment and `**` across segments.
+//! Paths are `/`-separated and already normalised.
+
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Returns true if `path` matches `pattern`.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    wildcard_match(
+        &pattern,
+        &path,
+        |segment| *segment == "**",
+        |segment, name| match_segment(segment, name),
+    )
+}
+
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    wildcard_match(&pattern, &name, |&c| c == '*', |&c, &n| c == '?' || c == n)
+}
+
+/// Matches `items` against `pattern`, in which `is_star` picks out the
+/// elements that match any run of items and `matches` says whether any
+/// other element matches one item. Only the latest star is ever retried,
+/// so this takes at most `pattern.len() * items.len()` steps.
+fn wildcard_match<P, T>(
+    pattern: &[P],
+    items: &[T],
+    is_star: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut i) = (0, 0);
+    // Where the latest star is, and the first item it has not yet taken
+    let mut retry: Option<(usize, usize)> = None;
+    while i < items.len() {
+        if p < pattern.len() && is_star(&pattern[p]) {
+            retry = Some((p, i));
+            p += 1;
+        } else if p < pattern.len() && matches(&pattern[p], &items[i]) {
+            p += 1;
+            i += 1;
+        } else if let Some((star, taken)) = retry {
+            // Let the star take one more item and go on from there
+            retry = Some((star, taken + 1));
+            p = star + 1;
+            i = taken + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(is_star)
+}
+
+/// Returns true if two claimed paths or globs could refer to the same file.
+///
+/// A plain path also covers everything below it. When both sides are globs
+/// the check is conservative: they overlap unless their literal leading
+/// directories diverge.
+pub fn patterns_overlap(a: &str, b: &str) -> bool {
+    if a == b || is_within(a, b) || is_within(b, a) {
+        return true;
+    }
+
+    match (is_glob(a), is_glob(b)) {
+        (false, false) => false,
+        (true, false) => glob_covers(a, b),
+        (false, true) => glob_covers(b, a),
+        (true, true) => {
+            let prefix_a = literal_prefix(a);
+            let prefix_b = literal_prefix(b);
+            let common = prefix_a.len().min(prefix_b.len());
+            prefix_a[..common] == prefix_b[..common]
+        }
+    }
+}
+
+/// True if `path` is strictly inside the directory `dir`.
+fn is_within(dir: &str, path: &str) -> bool {
+    !is_glob(dir)
+        && path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// True if `pattern` matches `path` or one of its parent directories.
+fn glob_covers(pattern: &str, path: &str) -> bool {
+    let mut candidate = path;
+    loop {
+        if glob_match(pattern, candidate) {
+            return true;
+        }
+        match candidate.rfind('/') {
+            Some(i) => candidate = &candidate[..i],
+            None => return false,
+        }
+    }
+}
+
+/// The leading segments of `pattern` that contain no wildcards.
+fn literal_prefix(pattern: &str) -> Vec<&str> {
+    pattern
+        .split('/')
+        .take_while(|segment| !is_glob(segment))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/*.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "src/db/mod.rs"));
+        assert!(glob_match("src/**/*.rs", "src/main.rs"));
+        assert!(glob_match("src/**/*.rs", "src/db/queries/mod.rs"));
+        assert!(glob_match("src/ma?n.rs", "src/main.rs"));
+        assert!(!glob_match("src/*.rs", "tests/main.rs"));
+        assert!(glob_match("**", "anything/at/all"));
+        assert!(glob_match("src/**", "src"));
+        assert!(!glob_match("src/**/x.rs", "src/a/b/y.rs"));
+    }
+
+    #[test]
+    fn test_glob_match_pathological_patterns() {
+        // Each star used to retry every split of the rest, which took
+        // exponential time on a near miss like these
+        let name = "a".repeat(60);
+        let pattern = format!("{}b", "*a".repeat(30));
+        assert!(!glob_match(&pattern, &name));
+        assert!(glob_match(&format!("{}a", "*a".repeat(30)), &name));
+
+        let path = vec!["a"; 60].join("/");
+        let pattern = format!("{}/b", vec!["**/a"; 30].join("/"));
+        assert!(!glob_match(&pattern, &path));
+    }
+
+    #[test]
+    fn test_glob_match_non_ascii() {
+        assert!(glob_match("docs/r?sum?.md", "docs/résumé.md"));
+        assert!(glob_match("docs/*é.md", "docs/résumé.md"));
+        assert!(!glob_match("docs/r?sum?.md", "docs/résuméé.md"));
+        assert!(glob_match("日本/**/?.txt", "日本/語/文.txt"));
+    }
+
+    #[test]
+    fn test_patterns_overlap() {
+        assert!(patterns_overlap("src/auth.rs", "src/auth.rs"));
+        assert!(patterns_overlap("src/auth", "src/auth/token.rs"));
+        assert!(!patterns_overlap("src/auth", "src/authz.rs"));
+        assert!(patterns_overlap("src/*.rs", "src/auth.rs"));
+        assert!(patterns_overlap("src/*", "src/auth/token.rs"));
+        assert!(!patterns_overlap("src/*.rs", "tests/auth.rs"));
+        assert!(patterns_overlap("src/**/*.rs", "src/db/*.rs"));
+        assert!(!patterns_overlap("src/db/*.rs", "tests/*.rs"));
+    }
+}
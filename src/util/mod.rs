@@ -1,3 +1,5 @@
 pub mod discovery;
 pub mod duration;
+pub mod git;
+pub mod glob;
 pub mod ref_;
@@ -11,6 +11,14 @@ fn bb_in_temp(temp_dir: &Path) -> Command {
     cmd
 }
 
+/// Writes a `.bb/blackboard.db` as an old (schema version 1) bb would have
+fn bb_v1_database(temp_dir: &Path) {
+    std::fs::create_dir(temp_dir.join(".bb")).unwrap();
+    let conn = rusqlite::Connection::open(temp_dir.join(".bb/blackboard.db")).unwrap();
+    conn.execute_batch(include_str!("fixtures/v1_database.sql"))
+        .unwrap();
+}
+
 /// Helper to run bb init in a temp directory
 fn bb_init(temp_dir: &Path) {
     bb_in_temp(temp_dir)
@@ -223,6 +231,396 @@ fn test_log_filter_by_priority() {
         .stdout(predicates::str::contains("high priority"));
 }
 
+#[test]
+fn test_wait_returns_reply_to_question() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["post", "which db?", "--as", "agent1"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["post", "sqlite", "--as", "agent2", "--reply-to", "1"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["wait", "--reply-to", "1", "--after", "1", "--timeout", "5s"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("sqlite"));
+}
+
+#[test]
+fn test_wait_times_out() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["wait", "--tag", "never", "--timeout", "1s"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Timed out"));
+}
+
+#[test]
+fn test_log_to_me_filters_addressed_messages() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["post", "@agent2 schema is ready", "--as", "agent1"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["post", "lunch?", "--as", "agent1", "--to", "agent3"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["log", "--to-me", "--as", "agent2"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("schema is ready"))
+        .stdout(predicates::str::contains("lunch?").not());
+
+    bb_in_temp(temp.path())
+        .args(["mentions", "--as", "agent3"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("→ To: agent3"));
+}
+
+#[test]
+fn test_inbox_shows_unread_once() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["post", "please review auth", "--as", "agent1"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["inbox", "--as", "agent2"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("please review auth"));
+
+    bb_in_temp(temp.path())
+        .args(["inbox", "--as", "agent2"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No unread messages"));
+}
+
+#[test]
+fn test_search_finds_messages_and_artifacts() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    std::fs::write(temp.path().join("auth.rs"), "").unwrap();
+    bb_in_temp(temp.path())
+        .args(["artifact-add", "auth.rs", "OAuth token refresh"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["post", "Decided to rotate the refresh token hourly"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["search", "refresh"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("**refresh**"))
+        .stdout(predicates::str::contains("auth.rs"));
+
+    // Plain text is matched word for word; only --raw is FTS5 syntax
+    bb_in_temp(temp.path())
+        .args(["search", "\"unbalanced"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["search", "--raw", "\"unbalanced"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("invalid search query"));
+}
+
+#[test]
+fn test_claim_conflict_names_holder() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["--as", "agent-1", "claim", "src/**", "--ttl", "1h"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Claimed src/** (exclusive)"));
+
+    bb_in_temp(temp.path())
+        .args(["--as", "agent-2", "claim", "src/main.rs", "--shared"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Conflicts with claim on 'src/**' held by agent-1 until",
+        ));
+
+    bb_in_temp(temp.path())
+        .args(["--as", "agent-1", "release", "--all"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["--as", "agent-2", "claim", "src/main.rs"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_heartbeat_keeps_status() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args([
+            "--as", "agent-1", "status", "set", "parser", "--status", "coding",
+        ])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["--as", "agent-1", "heartbeat"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("agent-1 seen at"));
+    bb_in_temp(temp.path())
+        .args(["status", "get", "agent-1"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("coding"));
+
+    std::fs::write(
+        temp.path().join(".bb/config.toml"),
+        "[liveness]\nactive_minutes = 60\nstale_minutes = 10\n",
+    )
+    .unwrap();
+    bb_in_temp(temp.path())
+        .arg("status")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "stale_minutes (10) must be at least",
+        ));
+}
+
+#[test]
+fn test_invalid_config_still_allows_recovery() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+    std::fs::write(
+        temp.path().join(".bb/config.toml"),
+        "[log]\ndefault_limit = 0\n",
+    )
+    .unwrap();
+
+    bb_in_temp(temp.path())
+        .arg("log")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "log.default_limit must be positive",
+        ));
+    bb_in_temp(temp.path())
+        .args(["config", "get", "liveness.stale_minutes"])
+        .assert()
+        .success()
+        .stdout("30\n")
+        .stderr(predicates::str::contains("using the default settings"));
+    bb_in_temp(temp.path())
+        .args(["config", "set", "log.default_limit", "5"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path()).arg("log").assert().success();
+
+    std::fs::write(temp.path().join(".bb/config.toml"), "[log\n").unwrap();
+    bb_in_temp(temp.path())
+        .args(["destroy", "--confirm"])
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("using the default settings"));
+    assert!(!temp.path().join(".bb").exists());
+}
+
+#[test]
+fn test_config_template_get_set() {
+    let temp = TempDir::new().unwrap();
+    bb_in_temp(temp.path())
+        .args(["init", "--template", "team"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("config.toml"));
+
+    bb_in_temp(temp.path())
+        .args(["config", "get", "liveness.stale_minutes"])
+        .assert()
+        .success()
+        .stdout("120\n");
+    bb_in_temp(temp.path())
+        .args(["config", "set", "log.default_limit", "2"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["config", "set", "log.default_limit", "none"])
+        .assert()
+        .failure();
+    bb_in_temp(temp.path())
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("log.default_limit = 2"))
+        .stdout(predicates::str::contains("# People step away").not());
+
+    // The team template only allows defined tags
+    bb_in_temp(temp.path())
+        .args(["post", "Needs a look", "--tag", "urgent"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown tag: urgent"));
+    for i in 0..3 {
+        bb_in_temp(temp.path())
+            .args(["post", &format!("Note {i}"), "--tag", "fyi"])
+            .assert()
+            .success();
+    }
+    bb_in_temp(temp.path())
+        .arg("log")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Note 0").not())
+        .stdout(predicates::str::contains("Note 2"));
+}
+
+#[test]
+fn test_tag_aliases_and_usage() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["post", "Waiting on the schema", "--tag", "blocked"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["post", "Trying a new parser", "--tag", "wip"])
+        .assert()
+        .success();
+
+    // Stored as the tag the alias stands for, and found by either spelling
+    bb_in_temp(temp.path())
+        .args(["--json", "log", "--tag", "blocking"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Waiting on the schema"))
+        .stdout(predicates::str::contains("\"blocker\""));
+
+    let output = bb_in_temp(temp.path())
+        .args(["--json", "tags"])
+        .output()
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tags = list["tags"].as_array().unwrap();
+    let blocker = tags.iter().find(|t| t["name"] == "blocker").unwrap();
+    assert_eq!(blocker["count"], 1);
+    assert_eq!(blocker["source"], "standard");
+    let wip = tags.iter().find(|t| t["name"] == "wip").unwrap();
+    assert_eq!(wip["source"], "undefined");
+
+    bb_in_temp(temp.path())
+        .args(["config", "set", "tags.strict", "true"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["post", "Still trying", "--tag", "wip"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown tag: wip"));
+    bb_in_temp(temp.path())
+        .arg("tags")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Strict"));
+}
+
+#[test]
+fn test_migrate_status_and_upgrade_v1_database() {
+    let temp = TempDir::new().unwrap();
+    bb_v1_database(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["migrate", "--status"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Schema version: 1"))
+        .stdout(predicates::str::contains("002_read_cursors  pending"));
+
+    bb_in_temp(temp.path())
+        .args(["migrate", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("004_full_text_search"));
+
+    bb_in_temp(temp.path())
+        .arg("migrate")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Applied 002_read_cursors"));
+
+    bb_in_temp(temp.path())
+        .args(["search", "JWT"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("**JWT**"));
+}
+
+#[test]
+fn test_commands_upgrade_v1_database_automatically() {
+    let temp = TempDir::new().unwrap();
+    bb_v1_database(temp.path());
+
+    bb_in_temp(temp.path())
+        .args(["inbox", "--as", "agent-2"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("we settled on JWT"));
+}
+
+#[test]
+fn test_refuses_database_from_newer_bb() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+
+    let conn = rusqlite::Connection::open(temp.path().join(".bb/blackboard.db")).unwrap();
+    conn.execute("INSERT INTO schema_version (version) VALUES (9999)", [])
+        .unwrap();
+    drop(conn);
+
+    bb_in_temp(temp.path())
+        .arg("log")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("newer than this bb supports"));
+
+    bb_in_temp(temp.path())
+        .arg("init")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("newer than this bb supports"));
+}
+
 // ============================================================================
 // Task #16: Test refs attach and find
 // ============================================================================
@@ -273,6 +671,208 @@ fn test_artifact_add_and_list() {
         .stdout(predicates::str::contains("test.txt"));
 }
 
+#[test]
+fn test_artifacts_check_reports_changes() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+    std::fs::write(temp.path().join("api.md"), "v1").unwrap();
+    std::fs::write(temp.path().join("old.md"), "v1").unwrap();
+
+    for path in ["api.md", "old.md"] {
+        bb_in_temp(temp.path())
+            .args(["--as", "writer", "artifact-add", path, "Docs"])
+            .assert()
+            .success();
+    }
+    std::fs::write(temp.path().join("api.md"), "v2").unwrap();
+    std::fs::remove_file(temp.path().join("old.md")).unwrap();
+
+    bb_in_temp(temp.path())
+        .args(["artifacts", "--check"])
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match(r"modified\s+api\.md\s+writer").unwrap())
+        .stdout(predicates::str::is_match(r"deleted\s+old\.md").unwrap());
+
+    // Registering again records the new contents
+    bb_in_temp(temp.path())
+        .args(["artifact-add", "api.md", "Docs, v2"])
+        .assert()
+        .success();
+    let output = bb_in_temp(temp.path())
+        .args(["--json", "artifacts", "--check", "--by", "human"])
+        .output()
+        .unwrap();
+    let checks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(checks[0]["state"], "unchanged");
+    assert_eq!(checks[0]["artifact"]["size"], 2);
+}
+
+#[test]
+fn test_artifact_history_keeps_provenance() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+    std::fs::write(temp.path().join("api.md"), "v1").unwrap();
+
+    bb_in_temp(temp.path())
+        .args(["--as", "writer", "artifact", "add", "api.md", "First draft"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["--as", "reviewer", "artifact-add", "api.md", "Reviewed"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "show", "api.md"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("reviewer"));
+    bb_in_temp(temp.path())
+        .args(["artifact", "history", "api.md"])
+        .assert()
+        .success()
+        .stdout(predicates::str::is_match(r"(?s)reviewer.*Reviewed.*writer.*First draft").unwrap());
+
+    let output = bb_in_temp(temp.path())
+        .args(["--json", "artifact", "show", "api.md"])
+        .output()
+        .unwrap();
+    let artifacts: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_ne!(
+        artifacts[0]["first_registered_at"],
+        artifacts[0]["updated_at"]
+    );
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "history", "missing.md"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_artifact_directories_remove_and_rename() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+    std::fs::create_dir_all(temp.path().join("src/auth")).unwrap();
+    std::fs::write(temp.path().join("src/auth/token.rs"), "").unwrap();
+    std::fs::write(temp.path().join("notes.md"), "").unwrap();
+
+    bb_in_temp(temp.path())
+        .args([
+            "--as",
+            "security",
+            "artifact",
+            "add",
+            "src/auth/**",
+            "Auth, owned by security",
+        ])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["artifact", "add", "notes.md", "Notes"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "show", "src/auth/token.rs"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("covered by"))
+        .stdout(predicates::str::contains("security"));
+    bb_in_temp(temp.path())
+        .args(["artifacts", "--under", "src/auth"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("src/auth/**"))
+        .stdout(predicates::str::contains("notes.md").not());
+
+    std::fs::rename(temp.path().join("notes.md"), temp.path().join("NOTES.md")).unwrap();
+    bb_in_temp(temp.path())
+        .args(["artifact", "rename", "notes.md", "NOTES.md"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["artifact", "history", "NOTES.md"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Path: notes.md"));
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "remove", "NOTES.md", "--as", "cleaner"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["artifact", "show", "NOTES.md"])
+        .assert()
+        .failure();
+    bb_in_temp(temp.path())
+        .args(["artifact", "history", "NOTES.md"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("cleaner"))
+        .stdout(predicates::str::contains("Removed"))
+        .stdout(predicates::str::contains("Path: notes.md"));
+    bb_in_temp(temp.path())
+        .arg("artifacts")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("src/auth/**"));
+}
+
+/// Runs git in `dir` as a test author
+fn git(dir: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_artifact_add_from_commit_and_changed_since() {
+    let temp = TempDir::new().unwrap();
+    git(temp.path(), &["init", "-q"]);
+    bb_init(temp.path());
+    std::fs::write(temp.path().join("api.md"), "v1").unwrap();
+    std::fs::write(temp.path().join("schema.sql"), "v1").unwrap();
+    git(temp.path(), &["add", "api.md", "schema.sql"]);
+    git(temp.path(), &["commit", "-q", "-m", "Document the API"]);
+
+    bb_in_temp(temp.path())
+        .args(["--as", "writer", "artifact", "add", "--from-commit", "HEAD"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Registered 2 artifact(s)"));
+
+    let output = bb_in_temp(temp.path())
+        .args(["--json", "artifact", "show", "api.md"])
+        .output()
+        .unwrap();
+    let artifacts: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(artifacts[0]["description"], "Document the API");
+    assert_eq!(artifacts[0]["git"]["dirty"], false);
+    let head = artifacts[0]["git"]["head"].as_str().unwrap().to_string();
+    assert_eq!(artifacts[0]["version"], head[..12]);
+
+    std::fs::write(temp.path().join("api.md"), "v2").unwrap();
+    bb_in_temp(temp.path())
+        .args(["artifacts", "--since-commit", &head])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("api.md"))
+        .stdout(predicates::str::contains("schema.sql").not());
+
+    bb_in_temp(temp.path())
+        .args(["artifacts", "--since-commit", "no-such-commit"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("unknown commit"));
+}
+
 // ============================================================================
 // Task #18: Test --json output is valid JSON
 // ============================================================================
@@ -358,3 +958,58 @@ fn test_clear_command_exists() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_artifact_graph_and_upstream_changes() {
+    let temp = TempDir::new().unwrap();
+    bb_init(temp.path());
+    std::fs::write(temp.path().join("schema.sql"), "").unwrap();
+    std::fs::write(temp.path().join("api.rs"), "").unwrap();
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "add", "schema.sql", "Schema"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["artifact", "add", "api.rs", "Generated API"])
+        .args(["--derived-from", "missing.sql"])
+        .assert()
+        .failure();
+    bb_in_temp(temp.path())
+        .args(["artifact", "add", "api.rs", "Generated API"])
+        .args(["--derived-from", "schema.sql"])
+        .assert()
+        .success();
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "graph", "schema.sql", "--dot"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "\"schema.sql\" -> \"api.rs\" [label=\"derived_from\"];",
+        ));
+    bb_in_temp(temp.path())
+        .args(["artifact", "graph", "api.rs", "--mermaid"])
+        .args(["--direction", "downstream"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("flowchart LR"))
+        .stdout(predicates::str::contains("schema.sql").not());
+
+    bb_in_temp(temp.path())
+        .args(["artifact", "add", "schema.sql", "Schema v2"])
+        .assert()
+        .success();
+    bb_in_temp(temp.path())
+        .args(["artifacts", "--check"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Upstream changed: schema.sql"));
+    bb_in_temp(temp.path())
+        .args(["artifact", "graph", "api.rs"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Derived from: schema.sql (changed since)",
+        ));
+}
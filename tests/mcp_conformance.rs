@@ -0,0 +1,665 @@
+//! Drives `bb mcp` over stdio and checks it speaks MCP / JSON-RPC correctly.
+
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A running `bb mcp` process with line-oriented access to its stdio.
+struct McpSession {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    lines: Receiver<String>,
+}
+
+impl McpSession {
+    /// Starts `bb mcp`, fixing its identity to `agent` if given.
+    fn start(dir: &TempDir, agent: impl Into<Option<&'static str>>) -> Self {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_bb"));
+        command.arg("mcp").env_remove("BB_AGENT_ID");
+        if let Some(agent) = agent.into() {
+            command.args(["--agent", agent]);
+        }
+        let mut child = command
+            .current_dir(dir.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let stdout = child.stdout.take().unwrap();
+        let (tx, lines) = channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stdin = child.stdin.take();
+        Self {
+            child,
+            stdin,
+            lines,
+        }
+    }
+
+    /// Starts a session and completes the initialize handshake.
+    fn initialized(dir: &TempDir, agent: impl Into<Option<&'static str>>) -> Self {
+        let mut session = Self::start(dir, agent);
+        let response = session.request(1, "initialize", initialize_params("2024-11-05"));
+        assert!(response.get("result").is_some(), "{response}");
+        session.send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}));
+        session
+    }
+
+    fn send_raw(&mut self, line: &str) {
+        let stdin = self.stdin.as_mut().unwrap();
+        writeln!(stdin, "{line}").unwrap();
+        stdin.flush().unwrap();
+    }
+
+    fn send(&mut self, message: Value) {
+        self.send_raw(&message.to_string());
+    }
+
+    fn recv(&self) -> Value {
+        let line = self.lines.recv_timeout(TIMEOUT).expect("no response");
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn request(&mut self, id: u32, method: &str, params: Value) -> Value {
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}));
+        let response = self.recv();
+        assert_eq!(response["id"], id, "{response}");
+        response
+    }
+
+    fn call_tool(&mut self, id: u32, name: &str, arguments: Value) -> Value {
+        self.request(
+            id,
+            "tools/call",
+            json!({"name": name, "arguments": arguments}),
+        )
+    }
+
+    /// Closes stdin and returns everything written before the process exits.
+    fn finish(mut self) -> Vec<Value> {
+        self.stdin.take();
+        let mut rest = Vec::new();
+        loop {
+            match self.lines.recv_timeout(TIMEOUT) {
+                Ok(line) => rest.push(serde_json::from_str(&line).unwrap()),
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => panic!("bb mcp did not exit"),
+            }
+        }
+        assert!(self.child.wait().unwrap().success());
+        rest
+    }
+}
+
+impl Drop for McpSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn initialize_params(version: &str) -> Value {
+    json!({
+        "protocolVersion": version,
+        "capabilities": {},
+        "clientInfo": {"name": "conformance", "version": "1.0"}
+    })
+}
+
+fn board() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_bb"))
+        .arg("init")
+        .current_dir(temp.path())
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    temp
+}
+
+/// The `{success, ...}` envelope of a tool call.
+fn tool_envelope(response: &Value) -> Value {
+    let text = response["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap_or_else(|| panic!("not a tool result: {response}"));
+    serde_json::from_str(text).unwrap()
+}
+
+/// The data of a successful tool call.
+fn tool_payload(response: &Value) -> Value {
+    let envelope = tool_envelope(response);
+    assert_eq!(envelope["success"], true, "{response}");
+    envelope["data"].clone()
+}
+
+/// The error of a failed tool call, which is a tool result, not a JSON-RPC error.
+fn tool_error(response: &Value) -> Value {
+    assert_eq!(response["result"]["isError"], true, "{response}");
+    let envelope = tool_envelope(response);
+    assert_eq!(envelope["success"], false, "{response}");
+    envelope["error"].clone()
+}
+
+#[test]
+fn test_initialize_negotiates_protocol_and_capabilities() {
+    let temp = board();
+
+    let mut session = McpSession::start(&temp, "agent-1");
+    let response = session.request(1, "initialize", initialize_params("2024-11-05"));
+    let result = &response["result"];
+    assert_eq!(result["protocolVersion"], "2024-11-05");
+    assert_eq!(result["serverInfo"]["name"], "bb");
+    assert!(result["capabilities"]["tools"].is_object());
+    assert_eq!(result["capabilities"]["resources"]["subscribe"], true);
+
+    let mut session = McpSession::start(&temp, "agent-1");
+    let response = session.request(1, "initialize", initialize_params("2099-01-01"));
+    assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+}
+
+#[test]
+fn test_notifications_get_no_reply_and_ping_works() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    // Known and unknown notifications alike are never answered
+    session.send(json!({"jsonrpc": "2.0", "method": "notifications/roots/list_changed"}));
+    session.send(json!({"jsonrpc": "2.0", "method": "notifications/made_up"}));
+
+    let response = session.request(2, "ping", json!({}));
+    assert_eq!(response["result"], json!({}));
+    assert!(session.finish().is_empty());
+}
+
+#[test]
+fn test_tools_list_describes_every_tool() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let response = session.request(2, "tools/list", json!({}));
+    let tools = response["result"]["tools"].as_array().unwrap();
+    let names: Vec<_> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    for expected in [
+        "identify",
+        "post_message",
+        "read_messages",
+        "inbox",
+        "claim_path",
+        "summary",
+    ] {
+        assert!(names.contains(&expected), "missing {expected}");
+    }
+    for tool in tools {
+        assert_eq!(tool["inputSchema"]["type"], "object", "{tool}");
+    }
+}
+
+#[test]
+fn test_tool_calls_round_trip() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let response = session.call_tool(2, "post_message", json!({"content": "hello"}));
+    assert_eq!(response["result"]["isError"], false);
+    assert_eq!(tool_payload(&response)["from_agent"], "agent-1");
+
+    let response = session.call_tool(3, "read_messages", json!({}));
+    assert_eq!(tool_payload(&response)[0]["content"], "hello");
+
+    // References use the documented `where` / `ref` names
+    let refs = json!([{"where": "tt", "what": "task", "ref": 42}]);
+    let response = session.call_tool(
+        4,
+        "post_message",
+        json!({"content": "linked", "refs": refs}),
+    );
+    assert_eq!(response["result"]["isError"], false, "{response}");
+
+    let response = session.call_tool(
+        5,
+        "find_refs",
+        json!({"where": "tt", "what": "task", "ref": "42"}),
+    );
+    assert_eq!(tool_payload(&response)["messages"][0]["content"], "linked");
+}
+
+#[test]
+fn test_tool_errors_carry_bb_codes() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+    session.call_tool(2, "claim_path", json!({"path": "src/lib.rs"}));
+
+    let mut other = McpSession::initialized(&temp, "agent-2");
+    let response = other.call_tool(2, "claim_path", json!({"path": "src/*.rs"}));
+    let error = tool_error(&response);
+    assert_eq!(error["code"], "LEASE_CONFLICT");
+    assert_eq!(error["details"]["holder"], "agent-1");
+    assert_eq!(error["details"]["path"], "src/lib.rs");
+
+    let response = other.call_tool(3, "post_message", json!({"content": ""}));
+    let error = tool_error(&response);
+    assert_eq!(error["code"], "INVALID_INPUT");
+    assert_eq!(error["details"]["field"], "content");
+
+    let response = other.call_tool(4, "post_message", json!({"tags": ["x"]}));
+    assert_eq!(tool_error(&response)["details"]["field"], "content");
+
+    let response = other.call_tool(5, "no_such_tool", json!({}));
+    assert_eq!(tool_error(&response)["code"], "INVALID_INPUT");
+}
+
+#[test]
+fn test_bad_choices_and_mistyped_arguments_are_rejected() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let response = session.call_tool(
+        2,
+        "post_message",
+        json!({"content": "hi", "priority": "urgent"}),
+    );
+    let error = tool_error(&response);
+    assert_eq!(error["code"], "INVALID_INPUT");
+    assert_eq!(error["details"]["field"], "priority");
+
+    let response = session.call_tool(3, "set_status", json!({"status": "sleeping"}));
+    let error = tool_error(&response);
+    assert_eq!(error["code"], "INVALID_INPUT");
+    assert_eq!(error["details"]["field"], "status");
+
+    // Case does not matter for known choices
+    let response = session.call_tool(4, "set_status", json!({"status": "Coding"}));
+    assert_eq!(tool_payload(&response)["status"], "coding");
+
+    // Mistyped filters fail instead of becoming an unfiltered read
+    for (id, tool) in [
+        (5, "read_messages"),
+        (6, "list_artifacts"),
+        (7, "get_status"),
+    ] {
+        let response = session.call_tool(id, tool, json!({"limit": "ten", "agent_id": 7}));
+        assert_eq!(tool_error(&response)["code"], "INVALID_INPUT", "{tool}");
+    }
+}
+
+#[test]
+fn test_misspelled_keys_are_rejected() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+    session.call_tool(2, "post_message", json!({"content": "hi"}));
+
+    let response = session.call_tool(3, "read_messages", json!({"form_agent": "nobody"}));
+    let error = tool_error(&response);
+    assert_eq!(error["code"], "INVALID_INPUT");
+    assert_eq!(error["details"]["field"], "form_agent");
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .contains("unknown field `form_agent`"),
+        "{error}"
+    );
+
+    // Nested inputs are checked too
+    let response = session.call_tool(
+        4,
+        "post_message",
+        json!({"content": "hi", "refs": [{"where": "tt", "what": "task", "ref": 1, "kind": "x"}]}),
+    );
+    assert_eq!(tool_error(&response)["code"], "INVALID_INPUT");
+}
+
+#[test]
+fn test_write_without_identity_requires_identity() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, None);
+
+    let response = session.call_tool(2, "post_message", json!({"content": "hi"}));
+    assert_eq!(tool_error(&response)["code"], "IDENTITY_REQUIRED");
+}
+
+#[test]
+fn test_invalid_messages_are_answered_without_ending_session() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    session.send_raw("this is not json");
+    let response = session.recv();
+    assert_eq!(response["error"]["code"], -32700);
+    assert_eq!(response["id"], Value::Null);
+
+    let response = session.request(2, "no/such/method", json!({}));
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = session.request(3, "tools/call", json!({"arguments": {}}));
+    assert_eq!(response["error"]["code"], -32602);
+
+    let response = session.request(4, "ping", json!({}));
+    assert_eq!(response["result"], json!({}));
+}
+
+#[test]
+fn test_batch_requests_get_every_reply() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    session.send(json!([
+        {"jsonrpc": "2.0", "id": 2, "method": "ping"},
+        {"jsonrpc": "2.0", "method": "notifications/made_up"},
+        {"jsonrpc": "2.0", "id": 3, "method": "no/such/method"},
+        {"jsonrpc": "2.0", "id": 4, "method": "tools/list"}
+    ]));
+
+    let mut ids: Vec<_> = (0..3).map(|_| session.recv()["id"].clone()).collect();
+    ids.sort_by_key(|id| id.as_i64());
+    assert_eq!(ids, vec![json!(2), json!(3), json!(4)]);
+}
+
+#[test]
+fn test_cancelled_request_gets_no_reply() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    session.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": {"name": "wait_for_messages", "arguments": {"timeout": "30s"}}
+    }));
+    session.send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": {"requestId": 2, "reason": "no longer needed"}
+    }));
+
+    let response = session.request(3, "ping", json!({}));
+    assert_eq!(response["result"], json!({}));
+    assert!(session.finish().is_empty());
+}
+
+#[test]
+fn test_piped_input_gets_every_reply_before_exit() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    for id in 2..6 {
+        session.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": "post_message", "arguments": {"content": format!("message {id}")}}
+        }));
+    }
+
+    assert_eq!(session.finish().len(), 4);
+}
+
+#[test]
+fn test_uninitialized_board_reports_error() {
+    let temp = TempDir::new().unwrap();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let response = session.call_tool(2, "summary", json!({}));
+    assert_eq!(tool_error(&response)["code"], "NOT_INITIALIZED");
+}
+
+#[test]
+fn test_resources_list_and_read() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let response = session.request(3, "resources/list", json!({}));
+    let uris: Vec<_> = response["result"]["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(uris, vec!["bb://summary", "bb://agents"]);
+
+    let response = session.request(4, "resources/templates/list", json!({}));
+    assert_eq!(
+        response["result"]["resourceTemplates"]
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let id = tool_payload(&session.call_tool(5, "post_message", json!({"content": "root"})))["id"]
+        .clone();
+    session.call_tool(
+        6,
+        "post_message",
+        json!({"content": "reply", "reply_to": id}),
+    );
+
+    let response = session.request(
+        7,
+        "resources/read",
+        json!({"uri": format!("bb://threads/{id}")}),
+    );
+    let contents = &response["result"]["contents"][0];
+    assert_eq!(contents["mimeType"], "application/json");
+    let thread: Value = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    assert_eq!(thread["messages"][1]["content"], "reply");
+    assert_eq!(thread["messages"][1]["depth"], 1);
+
+    let response = session.request(8, "resources/read", json!({"uri": "bb://messages/999"}));
+    assert_eq!(response["error"]["code"], -32002);
+    assert_eq!(response["error"]["data"]["code"], "NOT_FOUND");
+
+    let response = session.request(9, "resources/read", json!({"uri": "bb://nope"}));
+    assert_eq!(response["error"]["code"], -32602);
+}
+
+#[test]
+fn test_subscribed_resource_gets_update_notification() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+    let response = session.request(3, "resources/subscribe", json!({"uri": "bb://summary"}));
+    assert_eq!(response["result"], json!({}));
+
+    let mut other = McpSession::initialized(&temp, "agent-2");
+    other.call_tool(2, "post_message", json!({"content": "news"}));
+
+    let notification = session.recv();
+    assert_eq!(notification["method"], "notifications/resources/updated");
+    assert_eq!(notification["params"]["uri"], "bb://summary");
+    assert!(session.finish().is_empty());
+}
+
+#[test]
+fn test_prompts_are_filled_from_live_data() {
+    let temp = board();
+    let mut other = McpSession::initialized(&temp, "agent-2");
+    other.call_tool(
+        2,
+        "post_message",
+        json!({"content": "@agent-1 the schema is ready"}),
+    );
+
+    let mut session = McpSession::initialized(&temp, "agent-1");
+    session.call_tool(2, "claim_path", json!({"path": "src/db/**"}));
+
+    let response = session.request(3, "prompts/list", json!({}));
+    let names: Vec<_> = response["result"]["prompts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "session_briefing",
+            "handoff",
+            "report_blocker",
+            "session_wrap_up"
+        ]
+    );
+
+    let response = session.request(4, "prompts/get", json!({"name": "session_briefing"}));
+    let text = response["result"]["messages"][0]["content"]["text"]
+        .as_str()
+        .unwrap();
+    assert!(text.contains("the schema is ready"), "{text}");
+    assert!(text.contains("src/db/**"), "{text}");
+
+    // Peeking for the prompt leaves the message unread
+    let response = session.call_tool(5, "inbox", json!({}));
+    assert_eq!(
+        tool_payload(&response)["messages"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let response = session.request(6, "prompts/get", json!({"name": "handoff"}));
+    assert_eq!(response["error"]["code"], -32602);
+    assert_eq!(response["error"]["data"]["details"]["field"], "to");
+}
+
+#[test]
+fn test_handoff_round_trip() {
+    let temp = board();
+    let mut sender = McpSession::initialized(&temp, "agent-1");
+    let mut recipient = McpSession::initialized(&temp, "agent-2");
+
+    let response = sender.call_tool(
+        2,
+        "handoff",
+        json!({"to": "agent-2", "summary": "Finish the parser", "refs": [{"where": "tt", "what": "task", "ref": 42}]}),
+    );
+    let handoff = tool_payload(&response);
+    assert_eq!(handoff["status"], "pending");
+    let id = handoff["id"].as_i64().unwrap();
+
+    let response = recipient.call_tool(2, "summary", json!({}));
+    let pending = &tool_payload(&response)["pending_handoffs"];
+    assert_eq!(pending[0]["id"], id, "{pending}");
+
+    // Only the recipient can answer
+    let response = sender.call_tool(3, "accept_handoff", json!({"id": id}));
+    assert_eq!(tool_error(&response)["code"], "INVALID_INPUT");
+
+    let response = recipient.call_tool(3, "accept_handoff", json!({"id": id, "note": "on it"}));
+    assert_eq!(tool_payload(&response)["status"], "accepted");
+
+    let response = sender.call_tool(
+        4,
+        "wait_for_messages",
+        json!({"reply_to": handoff["message_id"], "after_id": 0, "timeout": "1s"}),
+    );
+    let messages = &tool_payload(&response)["messages"];
+    assert_eq!(
+        messages[0]["content"],
+        format!("Accepted handoff #{id}: on it")
+    );
+
+    let response = recipient.call_tool(4, "list_handoffs", json!({"status": "pending"}));
+    assert_eq!(tool_payload(&response), json!([]));
+}
+
+#[test]
+fn test_question_answer_round_trip() {
+    let temp = board();
+    let mut asker = McpSession::initialized(&temp, "agent-1");
+    let mut expert = McpSession::initialized(&temp, "agent-2");
+
+    let response = asker.call_tool(
+        2,
+        "post_message",
+        json!({"content": "Which port does the API use?", "tags": ["question"], "to": ["agent-2"]}),
+    );
+    let question_id = tool_payload(&response)["id"].as_i64().unwrap();
+
+    let response = expert.call_tool(2, "list_questions", json!({"to_me": true}));
+    let open = tool_payload(&response);
+    assert_eq!(open[0]["message"]["id"], question_id, "{open}");
+    assert_eq!(open[0]["state"], "open");
+
+    let response = expert.call_tool(
+        3,
+        "post_message",
+        json!({"content": "8080", "reply_to": question_id}),
+    );
+    let answer_id = tool_payload(&response)["id"].as_i64().unwrap();
+
+    // Only the asker can accept an answer
+    let response = expert.call_tool(
+        4,
+        "accept_answer",
+        json!({"question_id": question_id, "answer_id": answer_id}),
+    );
+    assert_eq!(tool_error(&response)["code"], "INVALID_INPUT");
+
+    let response = asker.call_tool(
+        3,
+        "accept_answer",
+        json!({"question_id": question_id, "answer_id": answer_id}),
+    );
+    let answered = tool_payload(&response);
+    assert_eq!(answered["state"], "answered");
+    assert_eq!(answered["answer_id"], answer_id);
+
+    let response = expert.call_tool(5, "list_questions", json!({}));
+    assert_eq!(tool_payload(&response), json!([]));
+
+    let response = expert.call_tool(6, "summary", json!({}));
+    assert_eq!(tool_payload(&response)["stale_questions"], 0);
+}
+
+#[test]
+fn test_get_thread_returns_nested_replies() {
+    let temp = board();
+    let mut session = McpSession::initialized(&temp, "agent-1");
+
+    let mut parent = None;
+    let mut ids = Vec::new();
+    for (n, content) in ["root", "reply", "reply to reply"].iter().enumerate() {
+        let response = session.call_tool(
+            2 + n as u32,
+            "post_message",
+            json!({"content": content, "reply_to": parent}),
+        );
+        let id = tool_payload(&response)["id"].as_i64().unwrap();
+        ids.push(id);
+        parent = Some(id);
+    }
+
+    // Asking from the deepest message still starts at the root
+    let response = session.call_tool(5, "get_thread", json!({"id": ids[2]}));
+    let thread = tool_payload(&response);
+    assert_eq!(thread["root_id"], ids[0]);
+    assert_eq!(thread["total"], 3);
+    assert_eq!(thread["messages"][2]["content"], "reply to reply");
+    assert_eq!(thread["messages"][2]["depth"], 2);
+
+    let response = session.call_tool(
+        6,
+        "get_thread",
+        json!({"id": ids[0], "max_depth": 1, "offset": 1, "limit": 1}),
+    );
+    let page = tool_payload(&response);
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["has_more"], false);
+    assert_eq!(page["messages"][0]["id"], ids[1]);
+}
@@ -0,0 +1,319 @@
+//! Drives `bb serve` over Streamable HTTP on localhost.
+
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+use tempfile::TempDir;
+
+/// A running `bb serve` process.
+struct Server {
+    child: Child,
+    /// Where the server said it is listening, e.g. `http://127.0.0.1:1234/mcp`.
+    listening: String,
+}
+
+impl Server {
+    fn start(dir: &TempDir, args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_bb"))
+            .arg("serve")
+            .args(args)
+            .env_remove("BB_AGENT_ID")
+            .current_dir(dir.path())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let listening = line
+            .trim()
+            .strip_prefix("Listening on ")
+            .unwrap_or_else(|| panic!("unexpected output: {line}"))
+            .to_string();
+        Self { child, listening }
+    }
+
+    /// Connects a client to a TCP server.
+    fn connect(&self) -> Client<TcpStream> {
+        let addr = self
+            .listening
+            .strip_prefix("http://")
+            .and_then(|rest| rest.strip_suffix("/mcp"))
+            .unwrap()
+            .to_string();
+        Client::new(move || TcpStream::connect(&addr).unwrap())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// One MCP session; every request goes out on a fresh connection.
+struct Client<S> {
+    connect: Box<dyn Fn() -> S>,
+    session_id: Option<String>,
+    next_id: u32,
+}
+
+impl<S: Read + Write> Client<S> {
+    fn new(connect: impl Fn() -> S + 'static) -> Self {
+        Self {
+            connect: Box::new(connect),
+            session_id: None,
+            next_id: 1,
+        }
+    }
+
+    /// POSTs `message` and returns the response headers and every SSE
+    /// `data` payload in the body.
+    fn post(&self, message: &Value, agent: Option<&str>) -> (String, Vec<Value>) {
+        let body = message.to_string();
+        let mut request = format!(
+            "POST /mcp HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Type: application/json\r\n\
+             Accept: application/json, text/event-stream\r\n\
+             Content-Length: {}\r\n",
+            body.len()
+        );
+        if let Some(session_id) = &self.session_id {
+            request.push_str(&format!("Mcp-Session-Id: {session_id}\r\n"));
+        }
+        if let Some(agent) = agent {
+            request.push_str(&format!("X-BB-Agent: {agent}\r\n"));
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+
+        let mut stream = (self.connect)();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let response = String::from_utf8(response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let body = if head
+            .to_ascii_lowercase()
+            .contains("transfer-encoding: chunked")
+        {
+            dechunk(body)
+        } else {
+            body.to_string()
+        };
+        let data = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .filter(|data| !data.trim().is_empty())
+            .map(|data| serde_json::from_str(data.trim()).unwrap())
+            .collect();
+        (head.to_string(), data)
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        let (head, data) = self.post(&message, None);
+        data.into_iter()
+            .find(|message| message["id"] == id)
+            .unwrap_or_else(|| panic!("no response to {method}: {head}"))
+    }
+
+    /// Opens a session, binding `agent` through the header if given.
+    fn initialize(&mut self, agent: Option<&str>) -> Value {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "mcp-http-test", "version": "0.0.0"}
+            }
+        });
+        let (head, data) = self.post(&message, agent);
+        self.session_id = head.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("mcp-session-id")
+                .then(|| value.trim().to_string())
+        });
+        let response = data.into_iter().next().expect("no initialize response");
+        if response.get("result").is_some() {
+            assert!(self.session_id.is_some(), "{head}");
+            self.post(
+                &json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                None,
+            );
+        }
+        response
+    }
+
+    fn tool(&mut self, name: &str, arguments: Value) -> Value {
+        let response = self.request("tools/call", json!({"name": name, "arguments": arguments}));
+        response["result"]["structuredContent"].clone()
+    }
+}
+
+fn dechunk(body: &str) -> String {
+    let mut out = String::new();
+    let mut rest = body;
+    while let Some((size, after)) = rest.split_once("\r\n") {
+        let size = usize::from_str_radix(size.trim(), 16).unwrap();
+        if size == 0 {
+            break;
+        }
+        out.push_str(&after[..size]);
+        rest = &after[size + 2..];
+    }
+    out
+}
+
+fn init_dir() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_bb"))
+        .arg("init")
+        .current_dir(dir.path())
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    dir
+}
+
+#[test]
+fn test_header_binds_session_identity() {
+    let dir = init_dir();
+    let server = Server::start(&dir, &["--port", "0"]);
+    let mut client = server.connect();
+
+    let response = client.initialize(Some("alice"));
+    assert_eq!(response["result"]["serverInfo"]["name"], "bb");
+
+    let posted = client.tool("post_message", json!({"content": "hello"}));
+    assert_eq!(posted["success"], true, "{posted}");
+    assert_eq!(posted["data"]["from_agent"], "alice");
+
+    let changed = client.tool("identify", json!({"agent_id": "mallory"}));
+    assert_eq!(changed["success"], false, "{changed}");
+}
+
+#[test]
+fn test_sessions_have_separate_identities() {
+    let dir = init_dir();
+    let server = Server::start(&dir, &["--port", "0"]);
+
+    let mut alice = server.connect();
+    alice.initialize(Some("alice"));
+    let mut bob = server.connect();
+    bob.initialize(None);
+
+    let anonymous = bob.tool("post_message", json!({"content": "who am I"}));
+    assert_eq!(
+        anonymous["error"]["code"], "IDENTITY_REQUIRED",
+        "{anonymous}"
+    );
+
+    let identified = bob.tool("identify", json!({"agent_id": "bob"}));
+    assert_eq!(identified["success"], true, "{identified}");
+    bob.tool("post_message", json!({"content": "from bob"}));
+    alice.tool("post_message", json!({"content": "from alice"}));
+
+    let messages = alice.tool("read_messages", json!({}));
+    let senders: Vec<&str> = messages["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["from_agent"].as_str().unwrap())
+        .collect();
+    assert!(senders.contains(&"alice"), "{messages}");
+    assert!(senders.contains(&"bob"), "{messages}");
+}
+
+#[test]
+fn test_invalid_header_identity_is_rejected() {
+    let dir = init_dir();
+    let server = Server::start(&dir, &["--port", "0"]);
+    let mut client = server.connect();
+
+    let response = client.initialize(Some(&"a".repeat(1000)));
+    assert_eq!(response["error"]["code"], -32602, "{response}");
+}
+
+#[test]
+fn test_rejects_foreign_host_and_origin() {
+    let dir = init_dir();
+    let server = Server::start(&dir, &["--port", "0"]);
+    let addr = server
+        .listening
+        .strip_prefix("http://")
+        .and_then(|rest| rest.strip_suffix("/mcp"))
+        .unwrap();
+
+    // The status line of an initialize request sent with `headers`
+    let status = |headers: &str| {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "mcp-http-test", "version": "0.0.0"}
+            }
+        })
+        .to_string();
+        let request = format!(
+            "POST /mcp HTTP/1.1\r\n{headers}Connection: close\r\n\
+             Content-Type: application/json\r\n\
+             Accept: application/json, text/event-stream\r\n\
+             Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).unwrap();
+        response
+    };
+
+    assert!(status("Host: localhost\r\nOrigin: https://evil.example\r\n").contains(" 403 "));
+    assert!(status("Host: evil.example\r\n").contains(" 403 "));
+    assert!(status(&format!("Host: {addr}\r\nOrigin: http://{addr}\r\n")).contains(" 200 "));
+}
+
+#[test]
+fn test_refuses_non_loopback_address() {
+    let dir = init_dir();
+    let output = Command::new(env!("CARGO_BIN_EXE_bb"))
+        .args(["serve", "--host", "0.0.0.0", "--port", "0"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("non-loopback"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_serves_over_unix_socket() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+
+    let dir = init_dir();
+    let socket = dir.path().join("bb.sock");
+    let server = Server::start(&dir, &["--socket", socket.to_str().unwrap()]);
+    assert_eq!(server.listening, format!("unix:{}", socket.display()));
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600, "socket mode {mode:o}");
+
+    let mut client = Client::new(move || UnixStream::connect(&socket).unwrap());
+    client.initialize(Some("alice"));
+    let posted = client.tool("post_message", json!({"content": "over a socket"}));
+    assert_eq!(posted["data"]["from_agent"], "alice", "{posted}");
+}